@@ -2,6 +2,7 @@ import cv2
 import numpy as np
 import os
 import io
+import threading
 from typing import List, Tuple, Optional
 
 # 统一入口：检测与识别（内部自带检测实现）
@@ -9,14 +10,52 @@ from typing import List, Tuple, Optional
 # —— 识别模型（InsightFace）合并至本文件 ——
 _APP = None
 _TARGETS = {}
+_TARGETS_LOCK = threading.Lock()
+# 最近一次带角度检测的逐脸判定 (x, y, w, h, score, covered)，供审计缩略图使用；score 为 None 表示无识别分数
+_LAST_DECISIONS: list = []
+# 最近一次带角度检测中达到阈值的目标人名（去重），供 detection-cycle 事件使用
+_LAST_MATCHES: list = []
 _RECOG_THRESHOLD = 0.35
+# 离线模型根目录（其下为 models/<_MODEL_NAME>），None 表示 insightface 默认目录（缺失时联网下载）
+_MODEL_ROOT = None
+# insightface 模型包：buffalo_l 精度高，buffalo_s 更轻量，适合低配机器
+_MODEL_NAME = 'buffalo_l'
+# 最近一次初始化使用的 provider，释放后惰性重建时沿用
+_PROVIDER = "auto"
 
-def init_model(provider: str = "auto") -> bool:
-    global _APP
+
+def _face_analysis(providers):
+    from insightface.app import FaceAnalysis
+    if _MODEL_ROOT:
+        return FaceAnalysis(name=_MODEL_NAME, root=_MODEL_ROOT, providers=providers)
+    return FaceAnalysis(name=_MODEL_NAME, providers=providers)
+
+def init_model(provider: str = "auto", model_root: Optional[str] = None, download_timeout: Optional[float] = None,
+               model: Optional[str] = None) -> bool:
+    global _MODEL_ROOT, _PROVIDER, _MODEL_NAME
     if _APP is not None:
         return True
+    _PROVIDER = provider
+    if model_root:
+        _MODEL_ROOT = model_root
+    if model:
+        _MODEL_NAME = model
+    if not download_timeout:
+        return _load_model(provider)
+    # 受限网络下模型下载可能无限挂起，为 socket 设置默认超时使其尽快失败；
+    # 该默认值对整个解释器生效，加载结束后恢复原值
+    import socket
+    prev_timeout = socket.getdefaulttimeout()
+    socket.setdefaulttimeout(float(download_timeout))
+    try:
+        return _load_model(provider)
+    finally:
+        socket.setdefaulttimeout(prev_timeout)
+
+
+def _load_model(provider: str) -> bool:
+    global _APP
     try:
-        from insightface.app import FaceAnalysis
         # 根据可用性选择最优 provider
         providers = None
         try:
@@ -41,7 +80,7 @@ def init_model(provider: str = "auto") -> bool:
         else:
             providers = ["CPUExecutionProvider"]
 
-        app = FaceAnalysis(name='buffalo_l', providers=providers)
+        app = _face_analysis(providers)
         app.prepare(ctx_id=0, det_size=(640, 640))
         _APP = app
         return True
@@ -56,8 +95,7 @@ def init_model(provider: str = "auto") -> bool:
         # 优先 DML 回退
         if 'DmlExecutionProvider' in avail:
             try:
-                from insightface.app import FaceAnalysis
-                app = FaceAnalysis(name='buffalo_l', providers=["DmlExecutionProvider", "CPUExecutionProvider"])
+                app = _face_analysis(["DmlExecutionProvider", "CPUExecutionProvider"])
                 app.prepare(ctx_id=0, det_size=(640, 640))
                 _APP = app
                 return True
@@ -65,8 +103,7 @@ def init_model(provider: str = "auto") -> bool:
                 print(f"fallback DML init failed: {e2}")
         # 最后 CPU
         try:
-            from insightface.app import FaceAnalysis
-            app = FaceAnalysis(name='buffalo_l', providers=["CPUExecutionProvider"])
+            app = _face_analysis(["CPUExecutionProvider"])
             app.prepare(ctx_id=0, det_size=(640, 640))
             _APP = app
             return True
@@ -76,9 +113,63 @@ def init_model(provider: str = "auto") -> bool:
             return False
 
 
+_PROVIDER_NAMES = {
+    'cpu': 'CPUExecutionProvider',
+    'cuda': 'CUDAExecutionProvider',
+    'dml': 'DmlExecutionProvider',
+}
+
+
+def set_provider(provider: str) -> List[str]:
+    """运行时切换推理 provider（cpu/cuda/dml），不重装 onnxruntime。先校验 ort 是否提供该 provider，再用它重建模型并替换；
+    与 init_model 不同，不做回退，失败时抛出异常且原模型保持不变。返回新模型实际使用的 provider 列表。"""
+    global _APP, _PROVIDER
+    pl = provider.lower()
+    name = _PROVIDER_NAMES.get(pl)
+    if name is None:
+        raise ValueError(f"unknown provider '{provider}', expected cpu/cuda/dml")
+    import onnxruntime as ort
+    avail = ort.get_available_providers()
+    if name not in avail:
+        raise RuntimeError(f"{name} is not available in the installed onnxruntime (available: {', '.join(avail)})")
+    providers = [name] if pl == 'cpu' else [name, "CPUExecutionProvider"]
+    app = _face_analysis(providers)
+    app.prepare(ctx_id=0, det_size=(640, 640))
+    _APP = app
+    _PROVIDER = pl
+    return _session_providers(app) or providers
+
+
+def _session_providers(app) -> List[str]:
+    """识别模型 ORT 会话实际启用的 provider（按优先级）；请求的 provider 初始化失败时 ORT 会静默退回 CPU，以此为准。"""
+    try:
+        return list(app.models['recognition'].session.get_providers())
+    except Exception:
+        return []
+
+
+def get_active_provider() -> str:
+    """当前模型实际使用的首选 provider（如 CUDAExecutionProvider），模型未加载时返回空串。"""
+    if _APP is None:
+        return ""
+    providers = _session_providers(_APP)
+    return providers[0] if providers else ""
+
+
+def release_model() -> bool:
+    """释放识别模型（ORT 会话随之释放显存），保留 _TARGETS；下次使用时按 _PROVIDER 重新加载。返回此前是否已加载。"""
+    global _APP
+    if _APP is None:
+        return False
+    _APP = None
+    import gc
+    gc.collect()
+    return True
+
+
 def _ensure_model() -> None:
     if _APP is None:
-        ok = init_model('auto')
+        ok = init_model(_PROVIDER)
         if not ok:
             raise RuntimeError('face recognition model not initialized')
 
@@ -90,40 +181,45 @@ def compute_embedding(image_bytes: bytes) -> Optional[List[float]]:
     """
     try:
         _ensure_model()
-        # 优先走 Pillow + EXIF 矫正，失败则回退 OpenCV 解码
-        img = None
-        try:
-            from PIL import Image, ImageOps
-            pil = Image.open(io.BytesIO(image_bytes))
-            pil = ImageOps.exif_transpose(pil)
-            img_rgb = np.array(pil)
-            if img_rgb.ndim == 2:  # 灰度
-                img = cv2.cvtColor(img_rgb, cv2.COLOR_GRAY2BGR)
-            else:
-                img = cv2.cvtColor(img_rgb, cv2.COLOR_RGB2BGR)
-        except Exception:
-            arr = np.frombuffer(image_bytes, dtype=np.uint8)
-            img = cv2.imdecode(arr, cv2.IMREAD_COLOR)
+        img = _decode_image_bytes(image_bytes)
         if img is None:
             return None
         faces = _APP.get(img)
-        if not faces:
-            return None
-        faces.sort(key=lambda f: (f.bbox[2]-f.bbox[0]) * (f.bbox[3]-f.bbox[1]), reverse=True)
-        face = faces[0]
-        emb = face.normed_embedding
-        if emb is None:
-            return None
-        emb = np.asarray(emb, dtype=np.float32)
-        norm = np.linalg.norm(emb)
-        if norm > 0:
-            emb = emb / norm
-        return emb.astype(np.float32).tolist()
+        return _largest_face_embedding(faces)
     except Exception as e:
         print(f"compute_embedding failed: {e}")
         return None
 
 
+def _decode_image_bytes(image_bytes: bytes) -> Optional[np.ndarray]:
+    # 优先走 Pillow + EXIF 矫正，失败则回退 OpenCV 解码
+    try:
+        from PIL import Image, ImageOps
+        pil = Image.open(io.BytesIO(image_bytes))
+        pil = ImageOps.exif_transpose(pil)
+        img_rgb = np.array(pil)
+        if img_rgb.ndim == 2:  # 灰度
+            return cv2.cvtColor(img_rgb, cv2.COLOR_GRAY2BGR)
+        return cv2.cvtColor(img_rgb, cv2.COLOR_RGB2BGR)
+    except Exception:
+        arr = np.frombuffer(image_bytes, dtype=np.uint8)
+        return cv2.imdecode(arr, cv2.IMREAD_COLOR)
+
+
+def _largest_face_embedding(faces) -> Optional[List[float]]:
+    if not faces:
+        return None
+    faces = sorted(faces, key=lambda f: (f.bbox[2]-f.bbox[0]) * (f.bbox[3]-f.bbox[1]), reverse=True)
+    emb = faces[0].normed_embedding
+    if emb is None:
+        return None
+    emb = np.asarray(emb, dtype=np.float32)
+    norm = np.linalg.norm(emb)
+    if norm > 0:
+        emb = emb / norm
+    return emb.astype(np.float32).tolist()
+
+
 def batch_compute_embeddings(images_bytes: List[bytes]) -> List[Optional[List[float]]]:
     _ensure_model()
     results: List[Optional[List[float]]] = []
@@ -132,6 +228,97 @@ def batch_compute_embeddings(images_bytes: List[bytes]) -> List[Optional[List[fl
     return results
 
 
+def _presence_gate(scores: list, thr: float) -> List[bool]:
+    """
+    在场门控的逐脸判定：未得到识别分数或分数低于阈值的脸视为“未授权”，需要遮罩；
+    全员均被识别时全部为 False（不遮罩任何人）。
+    """
+    return [s is None or s < thr for s in scores]
+
+
+def _presence_matches(embeddings: list) -> List[Tuple[Optional[str], Optional[float]]]:
+    """逐脸与目标库比对，返回 (最相似的人名, 相似度)；没有 embedding 或目标库为空时为 (None, None)。"""
+    return [
+        (None, None) if emb is None or not _TARGETS else _best_match(np.asarray(emb, dtype=np.float32))
+        for emb in embeddings
+    ]
+
+
+def _detect_unknown_present(
+    image_data: bytes,
+    width: int,
+    height: int,
+    image_scale: float,
+    recognition_threshold: float | None,
+) -> List[Tuple[int, int, int, int, float, Optional[float]]]:
+    """
+    unknown_present 模式：InsightFace 检测并逐脸比对目标库，仅当画面中存在未识别的人时返回这些人的框、角度与相似度
+    （无法比对时为 None）；目标库为空时所有人均视为未识别。同时记录 _LAST_DECISIONS 供审计。
+    """
+    # 每帧重新记录，避免不同入口的调用在模块级列表中累积
+    _LAST_DECISIONS.clear()
+    _LAST_MATCHES.clear()
+    if not init_model(_PROVIDER):
+        raise RuntimeError('model init failed')
+
+    arr = np.frombuffer(image_data, dtype=np.uint8).reshape(height, width, 4)
+    bgr = cv2.cvtColor(arr, cv2.COLOR_BGRA2BGR)
+    scale = float(image_scale) if image_scale and image_scale > 0 else 1.0
+    if abs(scale - 1.0) > 1e-6:
+        sw = max(1, int(round(width * scale)))
+        sh = max(1, int(round(height * scale)))
+        bgr_scaled = cv2.resize(bgr, (sw, sh), interpolation=cv2.INTER_LINEAR)
+        inv = 1.0 / scale
+    else:
+        bgr_scaled = bgr
+        inv = 1.0
+
+    faces_info = _APP.get(bgr_scaled) or []
+    thr = float(recognition_threshold) if recognition_threshold is not None else float(_RECOG_THRESHOLD)
+
+    matches = _presence_matches([getattr(f, 'normed_embedding', None) for f in faces_info])
+    scores = [score for _, score in matches]
+    for name, score in matches:
+        if score is not None and score >= thr and name not in _LAST_MATCHES:
+            _LAST_MATCHES.append(name)
+    covered = _presence_gate(scores, thr)
+
+    result = []
+    for f, score, cover in zip(faces_info, scores, covered):
+        x0, y0, x1, y1 = map(float, f.bbox)
+        x0 = int(round(x0 * inv)); y0 = int(round(y0 * inv))
+        x1 = int(round(x1 * inv)); y1 = int(round(y1 * inv))
+        x0 = max(0, min(x0, width - 1))
+        y0 = max(0, min(y0, height - 1))
+        x1 = max(x0 + 1, min(x1, width))
+        y1 = max(y0 + 1, min(y1, height))
+        box = (x0, y0, max(1, x1 - x0), max(1, y1 - y0))
+        _LAST_DECISIONS.append((*box, score, cover))
+        if cover:
+            pts = getattr(f, 'kps', None)
+            angle = _estimate_roll_deg_from_points(np.asarray(pts, dtype=np.float32) if pts is not None else None)
+            result.append((*box, float(angle), score))
+    return result
+
+
+def _best_match(emb: np.ndarray) -> Tuple[Optional[str], Optional[float]]:
+    """与目标库逐人比对，返回 (最相似的人名, 相似度)；目标库为空时为 (None, None)。"""
+    best_name, best = None, None
+    for name, target in _TARGETS.items():
+        score = float(np.dot(emb, target))
+        if best is None or score > best:
+            best_name, best = name, score
+    return best_name, best
+
+
+def _use_targets(coverage_mode: str) -> bool:
+    if coverage_mode == "all":
+        return False
+    if coverage_mode == "targets":
+        return True
+    return bool(_TARGETS)
+
+
 def detect_targets_or_all_faces(
     image_data: bytes,
     width: int,
@@ -144,16 +331,29 @@ def detect_targets_or_all_faces(
     min_neighbors: int,
     confidence_threshold: float,
     recognition_threshold: float | None = None,
-) -> List[Tuple[int,int,int,int]]:
+    coverage_mode: str = "auto",
+    multi_target: bool = True,
+) -> List[Tuple[int,int,int,int,Optional[float]]]:
     """
-    行为统一：
-    - 若存在目标库(_TARGETS 非空)且识别模型可用：按与检测相同的 image_scale 缩放整图，使用 InsightFace 检测+嵌入，选出命中最佳目标并返回其框。
+    行为统一（返回 (x, y, w, h, score)，score 为与目标库的余弦相似度，无识别时为 None）：
+    - 若存在目标库(_TARGETS 非空)且识别模型可用：按与检测相同的 image_scale 缩放整图，使用 InsightFace 检测+嵌入，
+      返回所有与目标库最高相似度 ≥ 阈值的人脸框（multi_target=False 时只返回相似度最高的一个）。
     - 否则：按现有配置走 Haar 全人脸检测并返回所有人脸框。
     """
-    # 若存在目标，优先走“目标检测”路径
-    if _TARGETS:
+    # 覆盖策略：auto 按目标库是否为空自动选择；all 强制遮罩所有人脸；targets 仅遮罩命中目标；
+    # unknown_present 仅在出现未识别的人时遮罩这些人
+    if coverage_mode == "unknown_present":
+        try:
+            return [(x, y, w, h, s) for (x, y, w, h, _a, s) in _detect_unknown_present(image_data, width, height, image_scale, recognition_threshold)]
+        except Exception:
+            # 无法识别时无法确认在场者身份，退回普通检测遮罩所有人脸；丢弃中途留下的部分记录
+            _LAST_DECISIONS.clear()
+            _LAST_MATCHES.clear()
+    elif _use_targets(coverage_mode):
+        if not _TARGETS:
+            return []
         try:
-            if not init_model('auto'):
+            if not init_model(_PROVIDER):
                 # 模型不可用则退回普通检测
                 raise RuntimeError('model init failed')
 
@@ -180,22 +380,22 @@ def detect_targets_or_all_faces(
             def cosine(a: np.ndarray, b: np.ndarray) -> float:
                 return float(np.dot(a, b))
 
-            best_bbox = None
-            best_score = -1.0
+            # 每张脸取与目标库的最高相似度（目标已归一化，InsightFace 输出通常已归一化）
+            matched = []
             for f in faces_info:
                 emb = f.normed_embedding
                 if emb is None:
                     continue
                 emb = np.asarray(emb, dtype=np.float32)
-                # 与目标库计算相似度（目标已归一化，InsightFace 输出通常已归一化）
-                for _person, target in _TARGETS.items():
-                    score = cosine(emb, target)
-                    if score > best_score:
-                        best_score = score
-                        best_bbox = f.bbox
-
-            if best_bbox is not None and best_score >= thr:
-                x0, y0, x1, y1 = map(float, best_bbox)
+                face_best = max(cosine(emb, target) for target in _TARGETS.values())
+                if face_best >= thr:
+                    matched.append((f.bbox, face_best))
+            if not multi_target and matched:
+                matched = [max(matched, key=lambda m: m[1])]
+
+            result = []
+            for bbox, score in matched:
+                x0, y0, x1, y1 = map(float, bbox)
                 # 映射回原分辨率
                 x0 = int(round(x0 * inv)); y0 = int(round(y0 * inv))
                 x1 = int(round(x1 * inv)); y1 = int(round(y1 * inv))
@@ -205,15 +405,14 @@ def detect_targets_or_all_faces(
                 y1 = max(y0 + 1, min(y1, height))
                 w = max(1, x1 - x0)
                 h = max(1, y1 - y0)
-                return [(x0, y0, w, h)]
-
-            return []
+                result.append((x0, y0, w, h, float(score)))
+            return result
         except Exception:
             # 任意异常回退到普通检测
             pass
 
-    # 普通全人脸检测（统一使用相同 image_scale）
-    return detect_faces_with_config(
+    # 普通全人脸检测（统一使用相同 image_scale），Haar 无置信度，score 为 None
+    rects = detect_faces_with_config(
         image_data,
         width,
         height,
@@ -225,6 +424,7 @@ def detect_targets_or_all_faces(
         min_neighbors=int(min_neighbors),
         confidence_threshold=float(confidence_threshold),
     )
+    return [(x, y, w, h, None) for (x, y, w, h) in rects]
 
 
 # 旧的“基于已有检测框再识别”与“整图重新检测再识别”逻辑已移除，
@@ -350,16 +550,31 @@ def detect_targets_or_all_faces_with_angle(
     min_neighbors: int,
     confidence_threshold: float,
     recognition_threshold: float | None = None,
-) -> List[Tuple[int,int,int,int,float]]:
+    coverage_mode: str = "auto",
+    multi_target: bool = True,
+) -> List[Tuple[int,int,int,int,float,Optional[float]]]:
     """
-    与 detect_targets_or_all_faces 一致，但额外返回每个框对应的滚转角（roll, 度数，逆时针为正）。
-    - 目标路径（_TARGETS 存在）：返回命中目标的单个人脸与角度。
+    与 detect_targets_or_all_faces 一致，但额外返回每个框对应的滚转角（roll, 度数，逆时针为正），
+    返回 (x, y, w, h, angle, score)。
+    - 目标路径（_TARGETS 存在）：返回所有命中目标的人脸与角度（multi_target=False 时只返回最佳的一个）。
     - 普通检测路径：返回所有检测框，角度为 0.0（Haar 无关键点估计）。
     """
-    # 若存在目标，优先走“目标检测”路径
-    if _TARGETS:
+    _LAST_DECISIONS.clear()
+    _LAST_MATCHES.clear()
+    # 覆盖策略：auto 按目标库是否为空自动选择；all 强制遮罩所有人脸；targets 仅遮罩命中目标；
+    # unknown_present 仅在出现未识别的人时遮罩这些人
+    if coverage_mode == "unknown_present":
         try:
-            if not init_model('auto'):
+            return _detect_unknown_present(image_data, width, height, image_scale, recognition_threshold)
+        except Exception:
+            # 无法识别时无法确认在场者身份，退回普通检测遮罩所有人脸
+            _LAST_DECISIONS.clear()
+            _LAST_MATCHES.clear()
+    elif _use_targets(coverage_mode):
+        if not _TARGETS:
+            return []
+        try:
+            if not init_model(_PROVIDER):
                 raise RuntimeError('model init failed')
 
             arr = np.frombuffer(image_data, dtype=np.uint8).reshape(height, width, 4)
@@ -378,51 +593,60 @@ def detect_targets_or_all_faces_with_angle(
             if not faces_info:
                 return []
 
-            thr = float(recognition_threshold) if recognition_threshold is not None else float(_RECOG_THRESHOLD)
+            def to_original(bbox) -> Tuple[int, int, int, int]:
+                x0, y0, x1, y1 = map(float, bbox)
+                x0 = int(round(x0 * inv)); y0 = int(round(y0 * inv))
+                x1 = int(round(x1 * inv)); y1 = int(round(y1 * inv))
+                x0 = max(0, min(x0, width - 1))
+                y0 = max(0, min(y0, height - 1))
+                x1 = max(x0 + 1, min(x1, width))
+                y1 = max(y0 + 1, min(y1, height))
+                return (x0, y0, max(1, x1 - x0), max(1, y1 - y0))
 
-            def cosine(a: np.ndarray, b: np.ndarray) -> float:
-                return float(np.dot(a, b))
+            thr = float(recognition_threshold) if recognition_threshold is not None else float(_RECOG_THRESHOLD)
 
-            best = None
-            best_score = -1.0
+            face_scores = []
+            face_names = []
             for f in faces_info:
                 emb = getattr(f, 'normed_embedding', None)
                 if emb is None:
+                    face_scores.append((f, None))
+                    face_names.append(None)
                     continue
-                emb = np.asarray(emb, dtype=np.float32)
-                for _person, target in _TARGETS.items():
-                    score = cosine(emb, target)
-                    if score > best_score:
-                        best_score = score
-                        best = f
-
-            if best is not None and best_score >= thr:
-                x0, y0, x1, y1 = map(float, best.bbox)
+                name, score = _best_match(np.asarray(emb, dtype=np.float32))
+                face_scores.append((f, score))
+                face_names.append(name)
+
+            covered = [i for i, (_f, score) in enumerate(face_scores) if score is not None and score >= thr]
+            if not multi_target and covered:
+                covered = [max(covered, key=lambda i: face_scores[i][1])]
+            for i in covered:
+                if face_names[i] not in _LAST_MATCHES:
+                    _LAST_MATCHES.append(face_names[i])
+            for i, (f, score) in enumerate(face_scores):
+                _LAST_DECISIONS.append((*to_original(f.bbox), score, i in covered))
+
+            result = []
+            for i in covered:
+                f, score = face_scores[i]
                 # 关键点字段兼容：kps 或 landmark/landmark_2d_106
                 pts = None
-                if hasattr(best, 'kps') and best.kps is not None:
-                    pts = np.asarray(best.kps, dtype=np.float32)
-                elif hasattr(best, 'landmark') and best.landmark is not None:
-                    pts = np.asarray(best.landmark, dtype=np.float32)
-                elif hasattr(best, 'landmark_2d_106') and best.landmark_2d_106 is not None:
-                    pts = np.asarray(best.landmark_2d_106, dtype=np.float32)
+                if hasattr(f, 'kps') and f.kps is not None:
+                    pts = np.asarray(f.kps, dtype=np.float32)
+                elif hasattr(f, 'landmark') and f.landmark is not None:
+                    pts = np.asarray(f.landmark, dtype=np.float32)
+                elif hasattr(f, 'landmark_2d_106') and f.landmark_2d_106 is not None:
+                    pts = np.asarray(f.landmark_2d_106, dtype=np.float32)
                 angle = _estimate_roll_deg_from_points(pts)
 
                 # 映射回原分辨率
-                x0 = int(round(x0 * inv)); y0 = int(round(y0 * inv))
-                x1 = int(round(x1 * inv)); y1 = int(round(y1 * inv))
-                x0 = max(0, min(x0, width - 1))
-                y0 = max(0, min(y0, height - 1))
-                x1 = max(x0 + 1, min(x1, width))
-                y1 = max(y0 + 1, min(y1, height))
-                w = max(1, x1 - x0)
-                h = max(1, y1 - y0)
-                return [(x0, y0, w, h, float(angle))]
-
-            return []
+                x0, y0, w, h = to_original(f.bbox)
+                result.append((x0, y0, w, h, float(angle), float(score)))
+            return result
         except Exception:
             # 任意异常回退到普通检测
-            pass
+            _LAST_DECISIONS.clear()
+            _LAST_MATCHES.clear()
 
     # 普通全人脸检测：沿用 Haar，角度置 0.0
     rects = detect_faces_with_config(
@@ -437,7 +661,16 @@ def detect_targets_or_all_faces_with_angle(
         min_neighbors=int(min_neighbors),
         confidence_threshold=float(confidence_threshold),
     )
-    return [(x, y, w, h, 0.0) for (x, y, w, h) in rects]
+    _LAST_DECISIONS.extend((x, y, w, h, None, True) for (x, y, w, h) in rects)
+    return [(x, y, w, h, 0.0, None) for (x, y, w, h) in rects]
+
+
+def get_last_decisions() -> list:
+    return list(_LAST_DECISIONS)
+
+
+def get_last_matches() -> list:
+    return list(_LAST_MATCHES)
 
 def _candidate_faces_dirs() -> list[str]:
     import sys, os
@@ -489,19 +722,8 @@ def _filter_outliers(embs: list[np.ndarray], thr: float = 0.3, max_iter: int = 2
     return kept
 
 
-def preload_targets_from_faces_dir(
-    outlier_threshold: float | None = None,
-    outlier_iter: int | None = None,
-) -> dict:
-    """从候选 faces 目录加载每人均值特征，存入全局 _TARGETS。返回已加载人员计数。"""
-    try:
-        _ensure_model()
-    except Exception:
-        # 无法初始化识别模型，清空并返回
-        _TARGETS.clear()
-        return {"loaded": 0}
-
-    loaded = 0
+def _iter_person_dirs():
+    """遍历候选 faces 目录下的人员子目录，产出 (人名, 目录, [图片文件名])。"""
     for root in _candidate_faces_dirs():
         if not os.path.isdir(root):
             continue
@@ -509,26 +731,309 @@ def preload_targets_from_faces_dir(
             person_dir = os.path.join(root, name)
             if not os.path.isdir(person_dir):
                 continue
-            embs = []
-            for fname in os.listdir(person_dir):
-                if not fname.lower().split('.')[-1] in { 'jpg','jpeg','png','webp','bmp' }:
+            files = [f for f in os.listdir(person_dir) if f.lower().split('.')[-1] in _IMAGE_EXTS]
+            yield name, person_dir, files
+
+
+def list_faces_dir() -> dict:
+    """列出 faces 目录内容：人名 -> [(文件名, 字节数, 修改时间 ns)]，供 Rust 侧计算嵌入缓存指纹。"""
+    out = {}
+    for name, person_dir, files in _iter_person_dirs():
+        entries = []
+        for fname in files:
+            try:
+                st = os.stat(os.path.join(person_dir, fname))
+                entries.append((fname, int(st.st_size), int(st.st_mtime_ns)))
+            except OSError:
+                pass
+        # 同名人员出现在多个候选目录时与加载一致：后者覆盖前者
+        out[name] = entries
+    return out
+
+
+def preload_targets_from_faces_dir(
+    outlier_threshold: float | None = None,
+    outlier_iter: int | None = None,
+    cached: dict | None = None,
+) -> dict:
+    """
+    从候选 faces 目录加载每人均值特征，整体替换全局 _TARGETS。返回 人名 -> 参与均值的样本数。
+    cached 为 人名 -> (样本数, 均值特征)，由 Rust 侧按文件指纹校验后传入，命中的人员不再读图计算。
+    """
+    cached = cached or {}
+
+    def groups():
+        for name, person_dir, files in _iter_person_dirs():
+            datas = []
+            if name not in cached:
+                for fname in files:
+                    try:
+                        with open(os.path.join(person_dir, fname), 'rb') as f:
+                            datas.append(f.read())
+                    except Exception:
+                        pass
+            yield name, datas
+
+    return _replace_targets(groups(), outlier_threshold, outlier_iter, cached)
+
+
+def _person_mean(datas: list, outlier_threshold: float | None, outlier_iter: int | None) -> Tuple[Optional[np.ndarray], int]:
+    """由某人的若干编码图像计算均值特征（剔除离群样本），返回 (均值, 参与均值的样本数)。"""
+    embs = []
+    for data in datas:
+        try:
+            emb = compute_embedding(data)
+            if emb is not None:
+                embs.append(np.asarray(emb, dtype=np.float32))
+        except Exception:
+            pass
+    # 剔除离群样本后再求均值
+    thr = float(outlier_threshold) if outlier_threshold is not None else 0.3
+    iters = int(outlier_iter) if outlier_iter is not None else 2
+    embs = _filter_outliers(embs, thr=thr, max_iter=iters)
+    return _mean_embedding(embs), len(embs)
+
+
+def _replace_targets(groups, outlier_threshold: float | None, outlier_iter: int | None, cached: dict | None = None) -> dict:
+    """
+    计算全部人员的均值特征后一次性替换 _TARGETS（检测只会看到旧库或新库，删除的人员随之移除）。
+    识别推理期间 onnxruntime 会释放 GIL，故重载之间以 _TARGETS_LOCK 串行。
+    """
+    global _TARGETS
+    with _TARGETS_LOCK:
+        try:
+            _ensure_model()
+        except Exception:
+            # 无法初始化识别模型，清空并返回
+            _TARGETS = {}
+            return {}
+        targets = {}
+        counts = {}
+        for name, datas in groups:
+            if cached and name in cached:
+                n, emb = cached[name]
+                mean = _l2_normalize(np.asarray(emb, dtype=np.float32))
+            else:
+                mean, n = _person_mean(list(datas), outlier_threshold, outlier_iter)
+            if mean is not None:
+                targets[name] = mean
+                counts[name] = n
+        _TARGETS = targets
+        return counts
+
+
+def preload_targets_from_images(
+    groups: dict,
+    outlier_threshold: float | None = None,
+    outlier_iter: int | None = None,
+) -> dict:
+    """由内存中的图像（人名 -> 编码图像字节列表，如从加密压缩包解出）加载目标特征，不落盘。返回 人名 -> 样本数。"""
+    return _replace_targets(groups.items(), outlier_threshold, outlier_iter)
+
+
+def export_targets() -> dict:
+    """导出当前目标库：人名 -> 均值特征（float 列表），供 Rust 侧写入嵌入缓存。"""
+    with _TARGETS_LOCK:
+        return {name: [float(x) for x in emb] for name, emb in _TARGETS.items()}
+
+
+_IMAGE_EXTS = {'jpg', 'jpeg', 'png', 'webp', 'bmp'}
+
+
+def validate_face_folder(path: str, outlier_threshold: float | None = None) -> str:
+    """
+    检查单人照片目录（不修改 _TARGETS）：逐张统计人脸数，并按同一人内部相似度估计录入质量。
+    返回 JSON 字符串，由 Rust 侧反序列化为 FolderReport。
+    """
+    import json
+    _ensure_model()
+    if not os.path.isdir(path):
+        raise ValueError(f'not a directory: {path}')
+    thr = float(outlier_threshold) if outlier_threshold is not None else 0.3
+
+    files = []
+    embs = []
+    for fname in sorted(os.listdir(path)):
+        if not fname.lower().split('.')[-1] in _IMAGE_EXTS:
+            continue
+        entry = {"file": fname, "face_count": 0, "similarity": None, "outlier": False, "error": None}
+        try:
+            with open(os.path.join(path, fname), 'rb') as f:
+                img = _decode_image_bytes(f.read())
+            if img is None:
+                entry["error"] = "failed to decode image"
+            else:
+                faces = _APP.get(img)
+                entry["face_count"] = len(faces)
+                emb = _largest_face_embedding(faces)
+                if emb is not None:
+                    embs.append((len(files), np.asarray(emb, dtype=np.float32)))
+        except Exception as e:
+            entry["error"] = str(e)
+        files.append(entry)
+
+    # 与录入时相同的思路：每张照片与全体均值的余弦相似度，低于离群阈值视为离群样本
+    mean = _mean_embedding([e for _, e in embs])
+    sims = []
+    if mean is not None:
+        for idx, e in embs:
+            score = float(np.dot(e, mean))
+            files[idx]["similarity"] = score
+            files[idx]["outlier"] = score < thr
+            sims.append(score)
+
+    usable = sum(1 for f in files if f["face_count"] == 1 and not f["outlier"])
+    mean_sim = float(np.mean(sims)) if sims else None
+    if usable < 3 or mean_sim is None:
+        quality = "insufficient"
+    elif mean_sim >= 0.6:
+        quality = "good"
+    elif mean_sim >= 0.45:
+        quality = "fair"
+    else:
+        quality = "poor"
+
+    return json.dumps({
+        "path": path,
+        "files": files,
+        "usable_count": usable,
+        "mean_similarity": mean_sim,
+        "quality": quality,
+    })
+
+
+_SYNC_STATE_FILE = '.sync_state.json'
+
+
+def _sync_target_dir() -> str:
+    """同步目标：第一个已存在的候选 faces 目录，都不存在时使用第一个候选并创建。"""
+    cands = _candidate_faces_dirs()
+    for p in cands:
+        if os.path.isdir(p):
+            return p
+    os.makedirs(cands[0], exist_ok=True)
+    return cands[0]
+
+
+def _safe_rel_path(name: str) -> Optional[str]:
+    """只接受 人名/图片 两级的相对路径，拒绝绝对路径与 .. 等越界写入。"""
+    parts = [p for p in name.replace('\\', '/').split('/') if p]
+    if len(parts) != 2 or any(p in ('.', '..') or ':' in p for p in parts):
+        return None
+    if parts[1].lower().split('.')[-1] not in _IMAGE_EXTS:
+        return None
+    return os.path.join(parts[0], parts[1])
+
+
+def _write_if_changed(path: str, data: bytes) -> bool:
+    try:
+        if os.path.getsize(path) == len(data):
+            with open(path, 'rb') as f:
+                if f.read() == data:
+                    return False
+    except OSError:
+        pass
+    os.makedirs(os.path.dirname(path), exist_ok=True)
+    tmp = path + '.tmp'
+    with open(tmp, 'wb') as f:
+        f.write(data)
+    os.replace(tmp, path)
+    return True
+
+
+def sync_faces_library(source: str, timeout_secs: float = 30.0) -> Tuple[bool, int, int]:
+    """
+    从集中位置同步人脸库到本地 faces 目录，返回 (是否有变化, 更新的文件数, 删除的文件数)。
+    source 为 http(s) 地址时下载人脸库压缩包（人名/图片 两级结构），以 ETag / Last-Modified 做条件请求，未变化时不下载；
+    否则视为目录（可为网络共享），按文件大小与修改时间增量复制。
+    只删除上次同步写入、而源中已不存在的文件，本地手动添加的照片保留；网络或共享不可用时抛出异常，本地目录保持原样。
+    """
+    import json
+    import shutil
+    dest = _sync_target_dir()
+    state_path = os.path.join(dest, _SYNC_STATE_FILE)
+    try:
+        with open(state_path, 'r', encoding='utf-8') as f:
+            state = json.load(f)
+        if state.get('source') != source:
+            state = {}
+    except (OSError, ValueError):
+        state = {}
+    prev_files = set(state.get('files', {}).keys())
+    files = {}
+    updated = 0
+
+    if source.lower().startswith(('http://', 'https://')):
+        import urllib.request
+        import urllib.error
+        import zipfile
+        req = urllib.request.Request(source)
+        if state.get('etag'):
+            req.add_header('If-None-Match', state['etag'])
+        if state.get('last_modified'):
+            req.add_header('If-Modified-Since', state['last_modified'])
+        try:
+            with urllib.request.urlopen(req, timeout=timeout_secs) as resp:
+                body = resp.read()
+                etag = resp.headers.get('ETag')
+                last_modified = resp.headers.get('Last-Modified')
+        except urllib.error.HTTPError as e:
+            if e.code == 304:
+                return (False, 0, 0)
+            raise
+        with zipfile.ZipFile(io.BytesIO(body)) as zf:
+            for info in zf.infolist():
+                if info.is_dir():
+                    continue
+                rel = _safe_rel_path(info.filename)
+                if rel is None:
+                    continue
+                if _write_if_changed(os.path.join(dest, rel), zf.read(info)):
+                    updated += 1
+                files[rel.replace('\\', '/')] = [info.file_size, 0]
+        state = {'source': source, 'etag': etag, 'last_modified': last_modified}
+    else:
+        if not os.path.isdir(source):
+            raise FileNotFoundError(f'faces sync source not found: {source}')
+        for name in os.listdir(source):
+            src_dir = os.path.join(source, name)
+            if not os.path.isdir(src_dir):
+                continue
+            for fname in os.listdir(src_dir):
+                rel = _safe_rel_path(f'{name}/{fname}')
+                if rel is None:
                     continue
-                fpath = os.path.join(person_dir, fname)
+                src = os.path.join(src_dir, fname)
+                dst = os.path.join(dest, rel)
+                st = os.stat(src)
+                key = rel.replace('\\', '/')
+                files[key] = [int(st.st_size), int(st.st_mtime_ns)]
                 try:
-                    with open(fpath, 'rb') as f:
-                        data = f.read()
-                    emb = compute_embedding(data)
-                    if emb is not None:
-                        embs.append(np.asarray(emb, dtype=np.float32))
-                except Exception:
+                    dst_st = os.stat(dst)
+                    if dst_st.st_size == st.st_size and int(dst_st.st_mtime_ns) == int(st.st_mtime_ns):
+                        continue
+                except OSError:
                     pass
-            # 剔除离群样本后再求均值
-            thr = float(outlier_threshold) if outlier_threshold is not None else 0.3
-            iters = int(outlier_iter) if outlier_iter is not None else 2
-            embs = _filter_outliers(embs, thr=thr, max_iter=iters)
-            mean = _mean_embedding(embs)
-            if mean is not None:
-                _TARGETS[name] = mean
-                loaded += 1
-    return {"loaded": loaded}
+                os.makedirs(os.path.dirname(dst), exist_ok=True)
+                shutil.copy2(src, dst)
+                updated += 1
+        state = {'source': source}
+
+    removed = 0
+    for key in prev_files - set(files.keys()):
+        path = os.path.join(dest, *key.split('/'))
+        try:
+            os.remove(path)
+            removed += 1
+            parent = os.path.dirname(path)
+            if not os.listdir(parent):
+                os.rmdir(parent)
+        except OSError:
+            pass
 
+    state['files'] = files
+    tmp = state_path + '.tmp'
+    with open(tmp, 'w', encoding='utf-8') as f:
+        json.dump(state, f)
+    os.replace(tmp, state_path)
+    return (updated > 0 or removed > 0, updated, removed)
@@ -0,0 +1,104 @@
+import importlib.util
+import sys
+import unittest
+from unittest import mock
+
+# cv2 / numpy 缺失时（如未安装依赖的 CI）以桩模块导入 faces，只测试不依赖它们的逻辑
+HAS_NUMPY = importlib.util.find_spec('numpy') is not None
+for _name in ('cv2', 'numpy'):
+    if importlib.util.find_spec(_name) is None:
+        sys.modules[_name] = mock.MagicMock()
+
+import faces  # noqa: E402
+
+
+class PresenceGateTest(unittest.TestCase):
+    THR = 0.35
+
+    def test_all_recognized_covers_nobody(self):
+        self.assertEqual(faces._presence_gate([0.8, 0.6, 0.35], self.THR), [False, False, False])
+
+    def test_one_stranger_covers_only_that_face(self):
+        self.assertEqual(faces._presence_gate([0.8, 0.1, 0.9], self.THR), [False, True, False])
+
+    def test_missing_score_is_covered(self):
+        self.assertEqual(faces._presence_gate([0.8, None], self.THR), [False, True])
+
+    def test_no_faces(self):
+        self.assertEqual(faces._presence_gate([], self.THR), [])
+
+
+class PresenceMatchesTest(unittest.TestCase):
+    def setUp(self):
+        self._targets = dict(faces._TARGETS)
+        faces._TARGETS.clear()
+
+    def tearDown(self):
+        faces._TARGETS.clear()
+        faces._TARGETS.update(self._targets)
+
+    def test_empty_library_covers_everyone(self):
+        matches = faces._presence_matches([[1.0, 0.0], [0.0, 1.0]])
+        self.assertEqual(matches, [(None, None), (None, None)])
+        scores = [s for _, s in matches]
+        self.assertEqual(faces._presence_gate(scores, 0.35), [True, True])
+
+    def test_face_without_embedding_is_covered(self):
+        faces._TARGETS['alice'] = [1.0, 0.0]
+        matches = faces._presence_matches([None])
+        self.assertEqual(matches, [(None, None)])
+        self.assertEqual(faces._presence_gate([s for _, s in matches], 0.35), [True])
+
+    @unittest.skipUnless(HAS_NUMPY, 'numpy not installed')
+    def test_stranger_among_known_faces(self):
+        import numpy as np
+        faces._TARGETS['alice'] = np.array([1.0, 0.0], dtype=np.float32)
+        matches = faces._presence_matches([[1.0, 0.0], [0.0, 1.0]])
+        self.assertEqual([n for n, _ in matches], ['alice', 'alice'])
+        scores = [s for _, s in matches]
+        self.assertEqual(faces._presence_gate(scores, 0.35), [False, True])
+
+
+class UnknownPresentRecordsTest(unittest.TestCase):
+    W, H = 4, 4
+
+    def setUp(self):
+        self._targets = dict(faces._TARGETS)
+        faces._TARGETS.clear()
+        faces._LAST_DECISIONS.clear()
+        faces._LAST_MATCHES.clear()
+
+    def tearDown(self):
+        faces._TARGETS.clear()
+        faces._TARGETS.update(self._targets)
+        faces._LAST_DECISIONS.clear()
+        faces._LAST_MATCHES.clear()
+
+    def _detect(self):
+        return faces.detect_targets_or_all_faces(
+            bytes(self.W * self.H * 4), self.W, self.H, False, 1.0, 1, 100, 1.1, 3, 0.5,
+            coverage_mode='unknown_present',
+        )
+
+    def test_repeated_frames_do_not_accumulate_decisions(self):
+        face = mock.Mock(bbox=[0.0, 0.0, 2.0, 2.0], normed_embedding=None, kps=None)
+        app = mock.Mock()
+        app.get.return_value = [face]
+        with mock.patch.object(faces, 'init_model', return_value=True), mock.patch.object(faces, '_APP', app):
+            for _ in range(3):
+                self.assertEqual(self._detect(), [(0, 0, 2, 2, None)])
+        self.assertEqual(faces.get_last_decisions(), [(0, 0, 2, 2, None, True)])
+        self.assertEqual(faces.get_last_matches(), [])
+
+    def test_fallback_clears_stale_records(self):
+        faces._LAST_DECISIONS.append((9, 9, 9, 9, 0.9, False))
+        faces._LAST_MATCHES.append('alice')
+        with mock.patch.object(faces, 'init_model', return_value=False), \
+                mock.patch.object(faces, 'detect_faces_with_config', return_value=[(1, 1, 2, 2)]):
+            self.assertEqual(self._detect(), [(1, 1, 2, 2, None)])
+        self.assertEqual(faces.get_last_decisions(), [])
+        self.assertEqual(faces.get_last_matches(), [])
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::ai::python_env;
+
+// 目标人脸均值特征的磁盘缓存：避免每次启动都对 faces/ 下全部照片重新推理。
+// 每人以「文件名/大小/修改时间 + 离群剔除参数」的指纹为键，文件夹变化时只重算变化的人员。
+// 缓存记录所用模型包，与当前配置不符时整体失效；特征计算方式变化时递增 CACHE_VERSION。
+const CACHE_VERSION: u32 = 1;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EmbeddingCache {
+    version: u32,
+    model: String,
+    people: HashMap<String, CachedPerson>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedPerson {
+    fingerprint: String,
+    samples: i32,
+    embedding: Vec<f32>,
+}
+
+// 与 faces.py 中 FaceAnalysis(name=...) 保持一致：不同模型包的特征互不兼容
+fn model_id() -> String {
+    crate::config::get_config()
+        .and_then(|c| c.face)
+        .map(|f| f.recognition.model())
+        .unwrap_or_else(|| crate::config::DEFAULT_RECOGNITION_MODEL.to_string())
+}
+
+fn cache_path() -> Result<PathBuf, String> {
+    Ok(python_env::get_app_data_dir()?.join("embeddings.json"))
+}
+
+// 读取缓存；文件不存在、损坏或版本/模型不匹配时返回空缓存
+pub fn load() -> EmbeddingCache {
+    let path = match cache_path() {
+        Ok(p) => p,
+        Err(e) => {
+            warn!("[embedding_cache] cache path unavailable: {}", e);
+            return EmbeddingCache::default();
+        }
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(t) => t,
+        Err(_) => return EmbeddingCache::default(),
+    };
+    let model = model_id();
+    match serde_json::from_str::<EmbeddingCache>(&text) {
+        Ok(cache) if cache.version == CACHE_VERSION && cache.model == model => cache,
+        Ok(cache) => {
+            info!(
+                "[embedding_cache] discard cache (version {} model {}, expected {} {})",
+                cache.version, cache.model, CACHE_VERSION, model
+            );
+            EmbeddingCache::default()
+        }
+        Err(e) => {
+            warn!("[embedding_cache] discard unreadable cache {:?}: {}", path, e);
+            EmbeddingCache::default()
+        }
+    }
+}
+
+impl EmbeddingCache {
+    // 指纹一致的人员：人名 -> (样本数, 均值特征)，直接交给 faces.py 复用
+    pub fn hits(&self, fingerprints: &HashMap<String, String>) -> HashMap<String, (i32, Vec<f32>)> {
+        fingerprints
+            .iter()
+            .filter_map(|(name, fp)| {
+                let p = self.people.get(name)?;
+                (&p.fingerprint == fp && !p.embedding.is_empty()).then(|| (name.clone(), (p.samples, p.embedding.clone())))
+            })
+            .collect()
+    }
+
+    // 缓存内容是否与当前文件夹完全一致（无需重写）
+    pub fn is_current(&self, fingerprints: &HashMap<String, String>, hits: usize) -> bool {
+        hits == fingerprints.len() && self.people.len() == fingerprints.len()
+    }
+}
+
+// 按当前加载结果重写缓存：只保留仍存在且成功计算出特征的人员
+pub fn store(
+    fingerprints: &HashMap<String, String>,
+    samples: &HashMap<String, i32>,
+    embeddings: HashMap<String, Vec<f32>>,
+) -> Result<(), String> {
+    let people: HashMap<String, CachedPerson> = embeddings
+        .into_iter()
+        .filter_map(|(name, embedding)| {
+            let fingerprint = fingerprints.get(&name)?.clone();
+            let samples = *samples.get(&name)?;
+            Some((name, CachedPerson { fingerprint, samples, embedding }))
+        })
+        .collect();
+    let count = people.len();
+    let cache = EmbeddingCache { version: CACHE_VERSION, model: model_id(), people };
+    let path = cache_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("create {:?} failed: {}", dir, e))?;
+    }
+    let text = serde_json::to_string(&cache).map_err(|e| format!("serialize embedding cache failed: {}", e))?;
+    // 先写临时文件再替换，避免中途退出留下半个文件
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, text).map_err(|e| format!("write {:?} failed: {}", tmp, e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("replace {:?} failed: {}", path, e))?;
+    debug!("[embedding_cache] stored {} people to {:?}", count, path);
+    Ok(())
+}
+
+// 每人的指纹：排序后的 (文件名, 大小, 修改时间) 列表与离群剔除参数一起做 FNV-1a 哈希（跨版本稳定，不依赖 std 的 DefaultHasher）
+pub fn fingerprints(listing: &HashMap<String, Vec<(String, u64, i64)>>, outlier: (f32, i32)) -> HashMap<String, String> {
+    listing
+        .iter()
+        .map(|(name, files)| {
+            let mut files: Vec<&(String, u64, i64)> = files.iter().collect();
+            files.sort();
+            let mut h = Fnv1a::new();
+            h.write(&outlier.0.to_le_bytes());
+            h.write(&outlier.1.to_le_bytes());
+            for (fname, size, mtime) in files {
+                h.write(fname.as_bytes());
+                h.write(&[0]);
+                h.write(&size.to_le_bytes());
+                h.write(&mtime.to_le_bytes());
+            }
+            (name.clone(), format!("{:016x}", h.0))
+        })
+        .collect()
+}
+
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
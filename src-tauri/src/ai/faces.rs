@@ -1,11 +1,13 @@
 use crate::monitor::Image;
 use crate::utils::rect::Rect;
-use crate::ai::python_env;
-use log::info;
+use crate::ai::{embedding_cache, python_env};
+use log::{debug, error, info, warn};
 use pyo3::prelude::*;
-use pyo3::types::PyBytes;
+use pyo3::types::{PyBytes, PyDict, PyList, PyTuple};
+use serde::{Deserialize, Serialize};
 use std::sync::OnceLock;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, AtomicU64, Ordering};
+use std::time::Instant;
 
 static FACE_MODEL_READY: OnceLock<AtomicBool> = OnceLock::new();
 
@@ -17,10 +19,176 @@ pub fn is_face_model_ready() -> bool {
     face_model_flag().load(Ordering::SeqCst)
 }
 
+// 模型初始化失败或超时：应用以静态遮罩模式运行（不检测、遮住整个显示器）；初始化稍后成功时自动清除
+static FACE_MODEL_FAILED: AtomicBool = AtomicBool::new(false);
+
+pub fn is_face_model_failed() -> bool {
+    FACE_MODEL_FAILED.load(Ordering::SeqCst)
+}
+
+const DEFAULT_MODEL_INIT_TIMEOUT_SECS: u64 = 180;
+
+// 运行时覆盖的识别阈值（f32 位模式），每帧读取；u32::MAX 表示未覆盖，沿用配置
+static RECOGNITION_THRESHOLD_OVERRIDE: AtomicU32 = AtomicU32::new(u32::MAX);
+
+// 实时调整识别阈值（余弦相似度），限制在 0~1；返回实际生效值
+pub fn set_recognition_threshold(threshold: f32) -> Result<f32, String> {
+    if !threshold.is_finite() {
+        return Err(format!("invalid recognition threshold: {}", threshold));
+    }
+    let clamped = threshold.clamp(0.0, 1.0);
+    RECOGNITION_THRESHOLD_OVERRIDE.store(clamped.to_bits(), Ordering::Relaxed);
+    info!("[set_recognition_threshold] recognition threshold set to {}", clamped);
+    Ok(clamped)
+}
+
+pub fn get_recognition_threshold() -> f32 {
+    let configured = crate::config::get_config()
+        .and_then(|c| c.face)
+        .map(|f| f.recognition.threshold)
+        .unwrap_or(0.0);
+    effective_recognition_threshold(configured)
+}
+
+fn effective_recognition_threshold(configured: f32) -> f32 {
+    recognition_threshold_override().unwrap_or(configured)
+}
+
+// 运行时覆盖的识别阈值，未覆盖时为 None
+pub fn recognition_threshold_override() -> Option<f32> {
+    match RECOGNITION_THRESHOLD_OVERRIDE.load(Ordering::Relaxed) {
+        u32::MAX => None,
+        bits => Some(f32::from_bits(bits)),
+    }
+}
+
+// 覆盖策略：auto 沿用“目标库是否为空”的自动判断；all 强制遮罩所有人脸；targets 仅遮罩命中目标；
+// unknown_present 在场门控：只有出现未识别（不在目标库）的人时才遮罩这些人，全员识别时不遮罩
+const COVERAGE_FROM_CONFIG: u8 = u8::MAX;
+const COVERAGE_AUTO: u8 = 0;
+const COVERAGE_ALL: u8 = 1;
+const COVERAGE_TARGETS: u8 = 2;
+const COVERAGE_UNKNOWN_PRESENT: u8 = 3;
+static COVERAGE_MODE: AtomicU8 = AtomicU8::new(COVERAGE_FROM_CONFIG);
+
+fn parse_coverage_mode(mode: &str) -> Result<u8, String> {
+    match mode {
+        "auto" => Ok(COVERAGE_AUTO),
+        "all" => Ok(COVERAGE_ALL),
+        "targets" => Ok(COVERAGE_TARGETS),
+        // protect_others（遮罩除已识别目标外的所有人）与 unknown_present 判定相同
+        "unknown_present" | "protect_others" => Ok(COVERAGE_UNKNOWN_PRESENT),
+        other => Err(format!("invalid coverage mode: {} (expected auto | all | targets | unknown_present)", other)),
+    }
+}
+
+// recognition.mode：target_only 仅遮罩命中的目标（即 auto），protect_others 遮罩除目标外的所有人
+fn parse_recognition_mode(mode: &str) -> Result<u8, String> {
+    match mode {
+        "target_only" => Ok(COVERAGE_AUTO),
+        "protect_others" => Ok(COVERAGE_UNKNOWN_PRESENT),
+        other => Err(format!("invalid recognition mode: {} (expected target_only | protect_others)", other)),
+    }
+}
+
+pub fn set_coverage_mode(mode: &str) -> Result<(), String> {
+    let value = parse_coverage_mode(mode)?;
+    COVERAGE_MODE.store(value, Ordering::Relaxed);
+    info!("[set_coverage_mode] coverage mode set to {}", mode);
+    Ok(())
+}
+
+pub fn get_coverage_mode() -> &'static str {
+    let value = match COVERAGE_MODE.load(Ordering::Relaxed) {
+        // 配置中 coverage_mode 优先于 mode
+        COVERAGE_FROM_CONFIG => {
+            let rec = crate::config::get_config().and_then(|c| c.face).map(|f| f.recognition).unwrap_or_default();
+            rec.coverage_mode
+                .and_then(|m| parse_coverage_mode(&m).map_err(|e| debug!("[get_coverage_mode] {}", e)).ok())
+                .or_else(|| rec.mode.and_then(|m| parse_recognition_mode(&m).map_err(|e| debug!("[get_coverage_mode] {}", e)).ok()))
+                .unwrap_or(COVERAGE_AUTO)
+        }
+        v => v,
+    };
+    match value {
+        COVERAGE_ALL => "all",
+        COVERAGE_TARGETS => "targets",
+        COVERAGE_UNKNOWN_PRESENT => "unknown_present",
+        _ => "auto",
+    }
+}
+
+// 识别开关：关闭时不再与目标库比对，无论覆盖策略与目标库内容如何都遮罩所有人脸（不修改人脸库）
+static RECOGNITION_ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_recognition_enabled(enabled: bool) {
+    RECOGNITION_ENABLED.store(enabled, Ordering::Relaxed);
+    info!("[set_recognition_enabled] recognition {}", if enabled { "enabled" } else { "disabled" });
+}
+
+pub fn is_recognition_enabled() -> bool {
+    RECOGNITION_ENABLED.load(Ordering::Relaxed)
+}
+
+// 本帧实际使用的覆盖策略：识别关闭时强制 all
+fn effective_coverage_mode() -> &'static str {
+    if is_recognition_enabled() { get_coverage_mode() } else { "all" }
+}
+
+// GIL 统计：获取 GIL 的等待时间与持有 GIL 执行的时间（微秒），用于判断是否存在 GIL 争用
+static GIL_CALLS: AtomicU64 = AtomicU64::new(0);
+static GIL_WAIT_US: AtomicU64 = AtomicU64::new(0);
+static GIL_HOLD_US: AtomicU64 = AtomicU64::new(0);
+static GIL_MAX_WAIT_US: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GilStats {
+    pub calls: u64,
+    pub total_wait_us: u64,
+    pub total_hold_us: u64,
+    pub max_wait_us: u64,
+    pub avg_wait_us: u64,
+    pub avg_hold_us: u64,
+}
+
+pub fn get_gil_stats() -> GilStats {
+    let calls = GIL_CALLS.load(Ordering::Relaxed);
+    let wait = GIL_WAIT_US.load(Ordering::Relaxed);
+    let hold = GIL_HOLD_US.load(Ordering::Relaxed);
+    GilStats {
+        calls,
+        total_wait_us: wait,
+        total_hold_us: hold,
+        max_wait_us: GIL_MAX_WAIT_US.load(Ordering::Relaxed),
+        avg_wait_us: if calls > 0 { wait / calls } else { 0 },
+        avg_hold_us: if calls > 0 { hold / calls } else { 0 },
+    }
+}
+
+// 包装 Python::with_gil：分别计量等待获取 GIL 与持有 GIL 执行的耗时
+fn with_gil_timed<F, R>(label: &str, f: F) -> R
+where
+    F: for<'py> FnOnce(Python<'py>) -> R,
+{
+    let requested = Instant::now();
+    Python::with_gil(|py| {
+        let wait_us = requested.elapsed().as_micros() as u64;
+        let acquired = Instant::now();
+        let result = f(py);
+        let hold_us = acquired.elapsed().as_micros() as u64;
+        GIL_CALLS.fetch_add(1, Ordering::Relaxed);
+        GIL_WAIT_US.fetch_add(wait_us, Ordering::Relaxed);
+        GIL_HOLD_US.fetch_add(hold_us, Ordering::Relaxed);
+        GIL_MAX_WAIT_US.fetch_max(wait_us, Ordering::Relaxed);
+        debug!("[perf] gil {} wait {} us, hold {} us", label, wait_us, hold_us);
+        result
+    })
+}
+
 // 统一入口：若存在目标人脸库，则返回命中的最佳目标；否则返回所有检测人脸
 pub fn detect_targets_or_all_faces(image: &Image) -> Result<Vec<Rect>, String> {
     // 统一委托给 Python faces.detect_targets_or_all_faces
-    Python::with_gil(|py| {
+    with_gil_timed("detect_targets_or_all_faces", |py| {
         let python_files_path = python_env::get_python_files_path()
             .map_err(|e| format!("Failed to get python files path: {}", e))?;
         let venv_site = python_env::get_venv_site_packages_path()
@@ -102,7 +270,7 @@ except Exception:
             (min_px, max_px)
         };
 
-        let res: Vec<(i32, i32, i32, i32)> = faces_mod
+        let res = faces_mod
             .call_method1(
                 "detect_targets_or_all_faces",
                 (
@@ -116,19 +284,37 @@ except Exception:
                     det.scale_factor,
                     det.min_neighbors,
                     det.confidence_threshold,
-                    rec.threshold,
+                    effective_recognition_threshold(rec.threshold),
+                    effective_coverage_mode(),
+                    rec.multi_target.unwrap_or(true),
                 ),
             )
-            .map_err(|e| format!("Failed to call detect_targets_or_all_faces: {}", e))?
-            .extract()
-            .map_err(|e| format!("Failed to extract faces result: {}", e))?;
-        Ok(res.into_iter().map(|(x,y,w,h)| Rect::new(x,y,w,h)).collect())
+            .map_err(|e| format!("Failed to call detect_targets_or_all_faces: {}", e))?;
+        Ok(extract_faces(res, false)?.into_iter().map(|(r, _)| r).collect())
     })
 }
 
+// 解析 Python 返回的检测框：(x, y, w, h[, angle][, score])，score 为识别相似度，无识别时为 None。
+// 兼容不带 score 的旧版 faces.py（score 缺省为 None）
+fn extract_faces(res: &PyAny, with_angle: bool) -> Result<Vec<(Rect, f32)>, String> {
+    let items: Vec<&PyTuple> = res.extract().map_err(|e| format!("Failed to extract faces result: {}", e))?;
+    let score_index = if with_angle { 5 } else { 4 };
+    items
+        .into_iter()
+        .map(|t| {
+            let get = |i: usize| t.get_item(i).and_then(|v| v.extract::<i32>());
+            let rect = Rect::new(get(0)?, get(1)?, get(2)?, get(3)?);
+            let angle = if with_angle { t.get_item(4)?.extract::<f32>()? } else { 0.0 };
+            let score = if t.len() > score_index { t.get_item(score_index)?.extract::<Option<f32>>()? } else { None };
+            Ok((rect.with_score(score), angle))
+        })
+        .collect::<PyResult<Vec<_>>>()
+        .map_err(|e| format!("Failed to extract faces result: {}", e))
+}
+
 /// 带角度的人脸检测：若存在识别目标，返回命中的目标框与 roll；否则返回所有检测框与 0.0 角度
 pub fn detect_faces_with_angle(image: &Image) -> Result<Vec<(Rect, f32)>, String> {
-    Python::with_gil(|py| {
+    with_gil_timed("detect_faces_with_angle", |py| {
         let python_files_path = python_env::get_python_files_path()
             .map_err(|e| format!("Failed to get python files path: {}", e))?;
         let venv_site = python_env::get_venv_site_packages_path()
@@ -207,7 +393,7 @@ except Exception:
             (min_px, max_px)
         };
 
-        let res: Vec<(i32, i32, i32, i32, f32)> = faces_mod
+        let res = faces_mod
             .call_method1(
                 "detect_targets_or_all_faces_with_angle",
                 (
@@ -221,22 +407,102 @@ except Exception:
                     det.scale_factor,
                     det.min_neighbors,
                     det.confidence_threshold,
-                    rec.threshold,
+                    effective_recognition_threshold(rec.threshold),
+                    effective_coverage_mode(),
+                    rec.multi_target.unwrap_or(true),
                 ),
             )
-            .map_err(|e| format!("Failed to call detect_targets_or_all_faces_with_angle: {}", e))?
-            .extract()
-            .map_err(|e| format!("Failed to extract faces result: {}", e))?;
-        Ok(res.into_iter().map(|(x,y,w,h,a)| (Rect::new(x,y,w,h), a)).collect())
+            .map_err(|e| format!("Failed to call detect_targets_or_all_faces_with_angle: {}", e))?;
+        extract_faces(res, true)
+    })
+}
+
+// 运行时切换推理 provider（cpu/cuda/dml）：Python 侧校验可用后用新 provider 重建模型并替换，失败时原模型保持不变；
+// 返回新模型实际使用的 provider 列表
+pub fn set_provider(provider: &str) -> Result<Vec<String>, String> {
+    with_gil_timed("set_provider", |py| {
+        let faces = py.import("faces").map_err(|e| format!("Failed to import faces: {}", e))?;
+        faces
+            .call_method1("set_provider", (provider,))
+            .and_then(|r| r.extract())
+            .map_err(|e| format!("set_provider({}) failed: {}", provider, e))
+    })
+}
+
+// 已加载模型实际使用的首选 provider（如 CUDAExecutionProvider），模型未加载时为空串
+pub fn get_active_provider() -> Result<String, String> {
+    with_gil_timed("get_active_provider", |py| {
+        let faces = match py.import("faces") {
+            Ok(m) => m,
+            Err(_) => return Ok(String::new()),
+        };
+        faces
+            .call_method0("get_active_provider")
+            .and_then(|r| r.extract())
+            .map_err(|e| format!("Failed to call get_active_provider: {}", e))
     })
 }
 
 // 检测与识别完全委托给 Python 端
+// 释放识别模型（ORT 会话与显存），返回此前是否已加载；目标特征保留，下次检测时按原 provider 惰性重建
+pub fn release_face_model() -> Result<bool, String> {
+    with_gil_timed("release_face_model", |py| {
+        // faces 模块尚未导入说明模型从未加载
+        let faces = match py.import("faces") {
+            Ok(m) => m,
+            Err(_) => return Ok(false),
+        };
+        faces
+            .call_method0("release_model")
+            .and_then(|r| r.extract())
+            .map_err(|e| format!("Failed to call release_model: {}", e))
+    })
+}
+
+// 带超时的模型初始化：init_model 可能因下载模型在受限网络下长时间挂起，超时或失败后提示离线模型配置，
+// 并标记为静态遮罩模式；超时后后台初始化仍继续，若最终成功则恢复正常检测
 pub fn initialize_face_recognition() -> Result<(), String> {
     if !python_env::is_python_ready() {
         return Err("Python environment is not ready".to_string());
     }
-    Python::with_gil(|py| {
+    let timeout_secs = crate::config::get_config()
+        .and_then(|c| c.face)
+        .and_then(|f| f.recognition.init_timeout_secs)
+        .unwrap_or(DEFAULT_MODEL_INIT_TIMEOUT_SECS)
+        .max(1);
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(init_face_model(timeout_secs));
+    });
+    let result = match rx.recv_timeout(std::time::Duration::from_secs(timeout_secs)) {
+        Ok(r) => r,
+        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+            Err(format!("face model initialization timed out after {} s", timeout_secs))
+        }
+        Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+            Err("face model initialization thread exited unexpectedly".to_string())
+        }
+    };
+    if let Err(e) = &result {
+        if !is_face_model_ready() {
+            FACE_MODEL_FAILED.store(true, Ordering::SeqCst);
+            error!("[faces] model unavailable, falling back to static mosaic mode: {}", e);
+            crate::api::emitter::emit_toast(
+                "人脸模型加载失败（可能无法下载模型），将以静态遮罩模式运行；离线环境请在 config.toml 的 [face.recognition] 中设置 model_root",
+            );
+        }
+    }
+    result
+}
+
+fn init_face_model(download_timeout_secs: u64) -> Result<(), String> {
+    with_gil_timed("initialize_face_recognition", |py| {
+        // 版本不符时尽早失败：此时尚未导入任何二进制扩展
+        let version = py.version_info();
+        if let Err(e) = python_env::check_embedded_python_version(version.major as u32, version.minor as u32, version.patch as u32) {
+            crate::api::emitter::emit_toast(&format!("Python 版本不受支持：{}", e));
+            return Err(e);
+        }
         let python_files_path = python_env::get_python_files_path()
             .map_err(|e| format!("Failed to get python files path: {}", e))?;
         // 优先把 venv 的 site-packages 放到 sys.path 前面，确保导入 venv 内的 onnxruntime 变体
@@ -295,25 +561,44 @@ if not loaded:
 
         let faces = py.import("faces").map_err(|e| format!("Failed to import faces: {}", e))?;
         // 读取配置中的 provider（cpu/cuda/dml），默认 cpu
-        let provider = crate::config::get_config()
-            .and_then(|c| c.face)
-            .map(|f| f.recognition.provider.unwrap_or_else(|| "cpu".to_string()))
+        let recognition = crate::config::get_config().and_then(|c| c.face).map(|f| f.recognition);
+        let provider = recognition
+            .as_ref()
+            .and_then(|r| r.provider.clone())
             .unwrap_or_else(|| "cpu".to_string());
+        let model = recognition
+            .as_ref()
+            .map(|r| r.model())
+            .unwrap_or_else(|| crate::config::DEFAULT_RECOGNITION_MODEL.to_string());
+        let model_root = recognition.and_then(|r| r.model_root).filter(|p| !p.trim().is_empty());
         let ok: bool = faces
-            .call_method1("init_model", (provider.as_str(),))
+            .call_method1("init_model", (provider.as_str(), model_root, download_timeout_secs as f64, model.as_str()))
             .map_err(|e| format!("Failed to call init_model: {}", e))?
             .extract()
             .map_err(|e| format!("Failed to extract init_model result: {}", e))?;
         if !ok { return Err("init_model returned false".to_string()); }
-        // 标记模型就绪
+        // 记录实际生效的 provider：自动选择或 CUDA/DML 初始化失败时可能已静默回退到 CPU
+        let active: String = faces
+            .call_method0("get_active_provider")
+            .and_then(|r| r.extract())
+            .unwrap_or_default();
+        info!("[faces] model {} initialized, requested provider {}, active provider {}", model, provider, active);
+        if !provider.eq_ignore_ascii_case("cpu") && active == "CPUExecutionProvider" {
+            warn!("[faces] requested provider {} but inference is running on CPU", provider);
+        }
+        // 标记模型就绪（超时后才完成时同时退出静态遮罩模式）
         face_model_flag().store(true, Ordering::SeqCst);
+        if FACE_MODEL_FAILED.swap(false, Ordering::SeqCst) {
+            info!("[faces] model became ready after fallback, detection resumed");
+        }
         Ok(())
     })
 }
 
-pub fn preload_targets_from_faces_dir(_app_handle: &tauri::AppHandle) -> Result<(), String> {
+// 加载（或重新加载）目标人脸库，返回 人名 -> 参与均值的样本数；Python 侧计算完成后整体替换目标库，监控中调用也安全
+pub fn preload_targets_from_faces_dir(_app_handle: &tauri::AppHandle) -> Result<std::collections::HashMap<String, i32>, String> {
     // 交给 Python 侧 faces.py 进行加载与均值特征的计算（带离群点配置）
-    Python::with_gil(|py| {
+    with_gil_timed("preload_targets_from_faces_dir", |py| {
         let python_files_path = python_env::get_python_files_path()
             .map_err(|e| format!("Failed to get python files path: {}", e))?;
         let venv_site = python_env::get_venv_site_packages_path()
@@ -374,18 +659,190 @@ except Exception:
             .map_err(|e| format!("Failed to load faces module: {}", e))?;
         let faces = py.import("faces").map_err(|e| format!("Failed to import faces: {}", e))?;
         let rec = crate::config::get_config().and_then(|c| c.face).map(|f| f.recognition).unwrap_or_default();
-        let stats: std::collections::HashMap<String, i32> = faces
-            .call_method1(
-                "preload_targets_from_faces_dir",
-                (rec.outlier_threshold.unwrap_or(0.3), rec.outlier_iter.unwrap_or(2)),
-            )
-            .map_err(|e| format!("Failed to call preload_targets_from_faces_dir: {}", e))?
+        let outlier = (rec.outlier_threshold.unwrap_or(0.3), rec.outlier_iter.unwrap_or(2));
+        // 配置了加密人脸库时只从压缩包加载，不再读取明文文件夹
+        let result = match rec.faces_archive.as_deref().filter(|p| !p.trim().is_empty()) {
+            Some(archive) => {
+                let password = faces_archive_password(&rec);
+                let groups = read_faces_archive(py, archive, password.as_deref())?;
+                info!("[preload_targets] read {} people from faces archive", groups.len());
+                faces
+                    .call_method1("preload_targets_from_images", (groups, outlier.0, outlier.1))
+                    .map_err(|e| format!("Failed to call preload_targets_from_images: {}", e))?
+            }
+            // 明文文件夹：按文件指纹复用磁盘缓存中的均值特征，只重算有变化的人员（加密压缩包不落盘缓存）
+            None => return preload_targets_with_cache(faces, outlier),
+        };
+        let stats: std::collections::HashMap<String, i32> = result
             .extract()
             .map_err(|e| format!("Failed to extract preload result: {}", e))?;
         info!("[preload_targets] loaded {:?}", stats);
+        Ok(stats)
+    })
+}
+
+fn preload_targets_with_cache(faces: &PyModule, outlier: (f32, i32)) -> Result<std::collections::HashMap<String, i32>, String> {
+    let listing: std::collections::HashMap<String, Vec<(String, u64, i64)>> = faces
+        .call_method0("list_faces_dir")
+        .and_then(|r| r.extract())
+        .map_err(|e| format!("Failed to list faces dir: {}", e))?;
+    let fingerprints = embedding_cache::fingerprints(&listing, outlier);
+    let cache = embedding_cache::load();
+    let hits = cache.hits(&fingerprints);
+    let hit_count = hits.len();
+    let up_to_date = cache.is_current(&fingerprints, hit_count);
+    info!("[preload_targets] {} of {} people reuse cached embeddings", hit_count, fingerprints.len());
+    let stats: std::collections::HashMap<String, i32> = faces
+        .call_method1("preload_targets_from_faces_dir", (outlier.0, outlier.1, hits))
+        .and_then(|r| r.extract())
+        .map_err(|e| format!("Failed to call preload_targets_from_faces_dir: {}", e))?;
+    info!("[preload_targets] loaded {:?}", stats);
+    if !up_to_date {
+        // 缓存写入失败不影响本次加载，下次启动重新计算即可
+        let stored = faces
+            .call_method0("export_targets")
+            .and_then(|r| r.extract())
+            .map_err(|e| format!("Failed to export targets: {}", e))
+            .and_then(|embeddings| embedding_cache::store(&fingerprints, &stats, embeddings));
+        if let Err(e) = stored {
+            warn!("[preload_targets] update embedding cache failed: {}", e);
+        }
+    }
+    Ok(stats)
+}
+
+// 人脸库压缩包口令：优先取环境变量（faces_archive_password_env 指定的变量名），其次取配置中的明文口令
+fn faces_archive_password(rec: &crate::config::RecognitionConfig) -> Option<String> {
+    rec.faces_archive_password_env
+        .as_deref()
+        .and_then(|name| std::env::var(name).ok())
+        .or_else(|| rec.faces_archive_password.clone())
+        .filter(|p| !p.is_empty())
+}
+
+// 读取人脸库压缩包（目录结构同 faces 文件夹：<人名>/<图片>），返回 人名 -> [图片字节] 的字典。
+// 借助 Python 标准库 zipfile 在内存中解密解压（仅支持 ZipCrypto 传统加密，不支持 AES），解出的图片不写入磁盘。
+fn read_faces_archive<'py>(py: Python<'py>, path: &str, password: Option<&str>) -> Result<&'py PyDict, String> {
+    let zipfile = py.import("zipfile").map_err(|e| format!("Failed to import zipfile: {}", e))?;
+    let archive = zipfile
+        .call_method1("ZipFile", (path,))
+        .map_err(|e| format!("Failed to open faces archive {}: {}", path, e))?;
+    let pwd = password.map(|p| PyBytes::new(py, p.as_bytes()));
+    let groups = PyDict::new(py);
+    let result = (|| -> Result<(), String> {
+        let names: Vec<String> = archive
+            .call_method0("namelist")
+            .and_then(|n| n.extract())
+            .map_err(|e| format!("Failed to list faces archive: {}", e))?;
+        for name in names {
+            let mut parts = name.split('/').filter(|s| !s.is_empty());
+            let (Some(person), Some(file), None) = (parts.next(), parts.next(), parts.next()) else {
+                continue;
+            };
+            let ext = file.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+            if !matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "webp" | "bmp") {
+                continue;
+            }
+            // 口令错误时 zipfile 抛出 RuntimeError，直接报错而非静默跳过，避免误以为人脸库为空
+            let data = archive
+                .call_method1("read", (name.as_str(), pwd))
+                .map_err(|e| format!("Failed to read {} from faces archive: {}", name, e))?;
+            let list = match groups.get_item(person).map_err(|e| e.to_string())? {
+                Some(list) => list.downcast::<PyList>().map_err(|e| e.to_string())?,
+                None => {
+                    let list = PyList::empty(py);
+                    groups.set_item(person, list).map_err(|e| e.to_string())?;
+                    list
+                }
+            };
+            list.append(data).map_err(|e| e.to_string())?;
+        }
         Ok(())
+    })();
+    let _ = archive.call_method0("close");
+    result.map(|_| groups)
+}
+
+// 最近一次检测的逐脸判定（坐标为传入检测图的坐标系），用于审计缩略图
+#[derive(Debug, Clone)]
+pub struct FaceDecision {
+    pub rect: Rect,
+    pub score: Option<f32>,
+    pub covered: bool,
+}
+
+// 从 HTTP 地址或目录同步人脸库到本地 faces 目录，返回 (是否有变化, 更新数, 删除数)；需在 faces 模块已导入（模型初始化）后调用
+pub fn sync_faces_library(source: &str, timeout_secs: u64) -> Result<(bool, usize, usize), String> {
+    with_gil_timed("sync_faces_library", |py| {
+        let faces = py.import("faces").map_err(|e| format!("Failed to import faces: {}", e))?;
+        faces
+            .call_method1("sync_faces_library", (source, timeout_secs as f64))
+            .map_err(|e| format!("Failed to sync faces library from {}: {}", source, e))?
+            .extract()
+            .map_err(|e| format!("Failed to extract sync result: {}", e))
     })
 }
+
+pub fn last_decisions() -> Result<Vec<FaceDecision>, String> {
+    with_gil_timed("last_decisions", |py| {
+        let faces = py.import("faces").map_err(|e| format!("Failed to import faces: {}", e))?;
+        let res: Vec<(i32, i32, i32, i32, Option<f32>, bool)> = faces
+            .call_method0("get_last_decisions")
+            .map_err(|e| format!("Failed to call get_last_decisions: {}", e))?
+            .extract()
+            .map_err(|e| format!("Failed to extract decisions: {}", e))?;
+        Ok(res
+            .into_iter()
+            .map(|(x, y, w, h, score, covered)| FaceDecision { rect: Rect::new(x, y, w, h), score, covered })
+            .collect())
+    })
+}
+
+// 最近一次检测中达到阈值的目标人名（去重）
+pub fn last_matches() -> Result<Vec<String>, String> {
+    with_gil_timed("last_matches", |py| {
+        let faces = py.import("faces").map_err(|e| format!("Failed to import faces: {}", e))?;
+        faces
+            .call_method0("get_last_matches")
+            .map_err(|e| format!("Failed to call get_last_matches: {}", e))?
+            .extract()
+            .map_err(|e| format!("Failed to extract matches: {}", e))
+    })
+}
+
+// 录入前检查：单张照片的人脸数与同一人内部相似度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderFileReport {
+    pub file: String,
+    pub face_count: u32,
+    pub similarity: Option<f32>,
+    pub outlier: bool,
+    pub error: Option<String>,
+}
+
+// quality: good | fair | poor | insufficient（可用照片不足 3 张）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderReport {
+    pub path: String,
+    pub files: Vec<FolderFileReport>,
+    pub usable_count: u32,
+    pub mean_similarity: Option<f32>,
+    pub quality: String,
+}
+
+// 只读检查某人的照片目录，不修改已加载的目标库；要求模型已初始化（faces 模块已加载）
+pub fn validate_face_folder(path: &str) -> Result<FolderReport, String> {
+    let rec = crate::config::get_config().and_then(|c| c.face).map(|f| f.recognition).unwrap_or_default();
+    let json: String = with_gil_timed("validate_face_folder", |py| {
+        let faces = py.import("faces").map_err(|e| format!("Failed to import faces: {}", e))?;
+        faces
+            .call_method1("validate_face_folder", (path, rec.outlier_threshold.unwrap_or(0.3)))
+            .map_err(|e| format!("Failed to call validate_face_folder: {}", e))?
+            .extract()
+            .map_err(|e| format!("Failed to extract validate_face_folder result: {}", e))
+    })?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse folder report: {}", e))
+}
 // Rust 不再实现本地 embedding 与匹配，全部交给 Python
 
 
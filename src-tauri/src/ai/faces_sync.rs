@@ -0,0 +1,73 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use log::{info, warn};
+
+use crate::ai::faces;
+use crate::api::emitter;
+use crate::config;
+
+// 定期同步的最小间隔，避免误配置为很小的值时频繁请求
+const MIN_INTERVAL_SECS: u64 = 60;
+
+static PERIODIC: OnceLock<()> = OnceLock::new();
+
+struct SyncSettings {
+    source: String,
+    interval_secs: u64,
+    timeout_secs: u64,
+}
+
+// 未配置来源或配置了 faces_archive（压缩包替代 faces 文件夹）时不同步
+fn settings() -> Option<SyncSettings> {
+    let rec = config::get_config()?.face?.recognition;
+    if rec.faces_archive.as_deref().is_some_and(|p| !p.trim().is_empty()) {
+        return None;
+    }
+    let source = rec.faces_sync_source.filter(|s| !s.trim().is_empty())?;
+    Some(SyncSettings {
+        source: source.trim().to_string(),
+        interval_secs: rec.faces_sync_interval_secs.unwrap_or(0),
+        timeout_secs: rec.faces_sync_timeout_secs.unwrap_or(30).max(1),
+    })
+}
+
+// 同步一次；未配置时返回 Ok(false)，否则返回本地人脸库是否有变化。失败时本地目录保持原样
+pub fn sync_once() -> Result<bool, String> {
+    let Some(s) = settings() else {
+        return Ok(false);
+    };
+    let (changed, updated, removed) = faces::sync_faces_library(&s.source, s.timeout_secs)?;
+    if changed {
+        info!("[faces_sync] synced from {}: {} updated, {} removed", s.source, updated, removed);
+    } else {
+        info!("[faces_sync] {} unchanged", s.source);
+    }
+    Ok(changed)
+}
+
+// 启动定期同步线程（仅一次）；每轮重新读取配置，有变化且模型就绪时重新加载人脸库
+pub fn start_periodic(app_handle: tauri::AppHandle) {
+    PERIODIC.get_or_init(|| {
+        std::thread::spawn(move || loop {
+            let interval = settings().map(|s| s.interval_secs).unwrap_or(0);
+            if interval == 0 {
+                // 未开启定期同步：稍后再看配置（可能经热加载开启）
+                std::thread::sleep(Duration::from_secs(MIN_INTERVAL_SECS));
+                continue;
+            }
+            std::thread::sleep(Duration::from_secs(interval.max(MIN_INTERVAL_SECS)));
+            match sync_once() {
+                Ok(true) if faces::is_face_model_ready() => match faces::preload_targets_from_faces_dir(&app_handle) {
+                    Ok(stats) => {
+                        crate::system::monitoring::reset_pipeline();
+                        emitter::emit_toast(&format!("人脸库已同步并重新加载：{} 人", stats.len()));
+                    }
+                    Err(e) => warn!("[faces_sync] reload after sync failed: {}", e),
+                },
+                Ok(_) => {}
+                Err(e) => warn!("[faces_sync] sync failed, keep local faces: {}", e),
+            }
+        });
+    });
+}
@@ -1,2 +1,5 @@
 pub mod python_env;
-pub mod faces;
\ No newline at end of file
+pub mod faces;
+pub mod embedding_cache;
+pub mod faces_sync;
+pub mod tracker;
\ No newline at end of file
@@ -3,7 +3,7 @@ use std::path::{Path, PathBuf};
 use std::fs;
 // removed unused io imports
 use std::env;
-use log::{info, warn, error};
+use log::{debug, info, warn, error};
 use once_cell::sync::OnceCell;
 use tauri::Emitter;
 
@@ -29,10 +29,255 @@ fn new_cmd<S: AsRef<std::ffi::OsStr>>(program: S) -> Command {
     c
 }
 
+// 离线安装：exe 同级或应用数据目录下的 wheels/ 中备齐依赖时，pip 只从该目录安装（--no-index --find-links），
+// 缺包时回退联网；设置 [python].offline = true 或环境变量 SCREEN_GHOST_OFFLINE=1 后不再联网
+const WHEELS_DIR_NAME: &str = "wheels";
+const OFFLINE_ENV: &str = "SCREEN_GHOST_OFFLINE";
+
+fn offline_forced() -> bool {
+    if matches!(env::var(OFFLINE_ENV).as_deref(), Ok("1") | Ok("true")) {
+        return true;
+    }
+    crate::config::get_config()
+        .and_then(|c| c.python)
+        .and_then(|p| p.offline)
+        .unwrap_or(false)
+}
+
+fn bundled_wheels_dir() -> Option<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(dir) = std::env::current_exe().ok().and_then(|e| e.parent().map(|d| d.join(WHEELS_DIR_NAME))) {
+        candidates.push(dir);
+    }
+    if let Ok(dir) = get_app_data_dir() {
+        candidates.push(dir.join(WHEELS_DIR_NAME));
+    }
+    candidates.into_iter().find(|d| !wheel_dists(d).is_empty())
+}
+
+// 目录内 wheel/源码包的分发名（小写，'-'/'.' 统一为 '_'），如 opencv_python-4.9.0-cp39-...whl -> opencv_python
+fn wheel_dists(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    entries
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_lowercase();
+            let stem = [".whl", ".tar.gz", ".zip"].iter().find_map(|ext| name.strip_suffix(ext))?;
+            // 分发名止于第一个后接数字的 '-'（版本号开头）
+            let end = stem
+                .char_indices()
+                .find(|&(i, c)| c == '-' && stem[i + 1..].starts_with(|n: char| n.is_ascii_digit()))
+                .map(|(i, _)| i)
+                .unwrap_or(stem.len());
+            Some(stem[..end].replace(['-', '.'], "_"))
+        })
+        .collect()
+}
+
+// 本次安装使用的 wheels 目录：依赖齐全时返回 Some；缺包时强制离线则报错列出缺失包，否则返回 None 走联网安装
+fn select_wheels_dir(provider_pref: &str) -> Result<Option<PathBuf>, String> {
+    let offline = offline_forced();
+    let Some(dir) = bundled_wheels_dir() else {
+        if offline {
+            return Err(format!(
+                "Offline mode is enabled but no {}/ directory with wheels was found next to the executable or in the app data directory",
+                WHEELS_DIR_NAME
+            ));
+        }
+        log_pip_index();
+        return Ok(None);
+    };
+    let dists = wheel_dists(&dir);
+    let has = |pkg: &str| dists.iter().any(|d| d == pkg);
+    let mut missing: Vec<&str> = ["numpy", "opencv_python", "insightface"].into_iter().filter(|p| !has(p)).collect();
+    let ort_variants: &[&str] = match provider_pref {
+        "cuda" => &["onnxruntime_gpu"],
+        "dml" => &["onnxruntime_directml"],
+        "cpu" => &["onnxruntime"],
+        _ => &["onnxruntime_gpu", "onnxruntime_directml", "onnxruntime"],
+    };
+    if !ort_variants.iter().any(|p| has(p)) {
+        missing.push(ort_variants[0]);
+    }
+    if missing.is_empty() {
+        info!("Installing Python packages from bundled wheels at {:?}", dir);
+        return Ok(Some(dir));
+    }
+    if offline {
+        return Err(format!("Offline mode is enabled but {:?} is missing wheels for: {}", dir, missing.join(", ")));
+    }
+    warn!("Bundled wheels at {:?} are missing {}; installing from the network", dir, missing.join(", "));
+    log_pip_index();
+    Ok(None)
+}
+
+// 联网安装时使用的镜像（[python].pip_index_url），缺省为 pip 自身配置的源
+fn pip_index_url() -> Option<String> {
+    crate::config::get_config()
+        .and_then(|c| c.python)
+        .and_then(|p| p.pip_index_url)
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty())
+}
+
+fn log_pip_index() {
+    match pip_index_url() {
+        Some(url) => info!("pip index: {}", url),
+        None => info!("pip index: default (pip configuration / PyPI)"),
+    }
+}
+
+fn pip_index_args() -> Vec<String> {
+    let Some(url) = pip_index_url() else { return Vec::new() };
+    let trust_https = crate::config::get_config()
+        .and_then(|c| c.python)
+        .and_then(|p| p.pip_trusted_host)
+        .unwrap_or(false);
+    index_args(&url, trust_https)
+}
+
+// --index-url，以及 http 镜像（或显式开启 pip_trusted_host 时）对应主机的 --trusted-host；
+// https 镜像默认保留证书校验
+fn index_args(url: &str, trust_https: bool) -> Vec<String> {
+    let mut args = vec!["--index-url".to_string(), url.to_string()];
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    if scheme.eq_ignore_ascii_case("http") || trust_https {
+        let authority = rest.split('/').next().unwrap_or(rest);
+        let host = authority.rsplit('@').next().unwrap_or(authority);
+        args.push("--trusted-host".to_string());
+        args.push(host.to_string());
+    }
+    args
+}
+
+// pip install 命令：给定 wheels 目录时只从该目录查找，否则使用配置的镜像
+fn pip_install(python_path: &Path, wheels: Option<&Path>) -> Command {
+    let mut cmd = new_cmd(python_path);
+    cmd.arg("-m").arg("pip").arg("install");
+    match wheels {
+        Some(dir) => {
+            cmd.arg("--no-index").arg("--find-links").arg(dir);
+        }
+        None => {
+            cmd.args(pip_index_args());
+        }
+    }
+    cmd
+}
+
+// 子进程输出只保留末尾 OUTPUT_TAIL_BYTES 字节（仅用于错误信息），逐行写入日志；
+// 避免 pip 从源码编译时的超长构建日志全部驻留内存
+const OUTPUT_TAIL_BYTES: usize = 64 * 1024;
+
+// 只保留末尾 cap 字节的缓冲，被丢弃的字节数记入 dropped
+struct TailBuffer {
+    buf: Vec<u8>,
+    cap: usize,
+    dropped: usize,
+}
+
+impl TailBuffer {
+    fn new(cap: usize) -> Self {
+        Self { buf: Vec::new(), cap, dropped: 0 }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+        // 超过两倍上限时再整体前移，摊还拷贝开销
+        if self.buf.len() > self.cap * 2 {
+            let start = tail_start(&self.buf, self.cap);
+            self.buf.drain(..start);
+            self.dropped += start;
+        }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        capped_tail(self.buf, self.cap, self.dropped)
+    }
+}
+
+// 保留末尾不超过 cap 字节时的起始下标；起点落在多字节 UTF-8 字符中间时后移到下一个字符边界，
+// 避免错误信息开头出现半个字符
+fn tail_start(bytes: &[u8], cap: usize) -> usize {
+    let mut start = bytes.len().saturating_sub(cap);
+    while start > 0 && start < bytes.len() && (bytes[start] & 0xC0) == 0x80 {
+        start += 1;
+    }
+    start
+}
+
+// 截取 buf 末尾 cap 字节；连同此前已丢弃的 dropped 字节，有丢弃时在开头加上截断提示
+fn capped_tail(mut buf: Vec<u8>, cap: usize, dropped: usize) -> Vec<u8> {
+    let start = tail_start(&buf, cap);
+    buf.drain(..start);
+    let dropped = dropped + start;
+    if dropped == 0 {
+        return buf;
+    }
+    let mut out = format!("...[truncated {} bytes]\n", dropped).into_bytes();
+    out.extend_from_slice(&buf);
+    out
+}
+
+fn read_capped<R: std::io::Read + Send + 'static>(reader: R, label: String) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        use std::io::BufRead;
+        let mut reader = std::io::BufReader::new(reader);
+        let mut tail = TailBuffer::new(OUTPUT_TAIL_BYTES);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => break,
+                Ok(_) => {
+                    debug!("[{}] {}", label, String::from_utf8_lossy(&line).trim_end());
+                    tail.push(&line);
+                }
+                Err(e) => {
+                    warn!("[{}] read output failed: {}", label, e);
+                    break;
+                }
+            }
+        }
+        tail.into_bytes()
+    })
+}
+
+// 替代 Command::output()：流式读取 stdout/stderr，逐行以 debug 级别记录，内存中只保留末尾；
+// 进程失败时以 warn 级别输出 stderr 末尾
+trait CappedOutput {
+    fn output_capped(&mut self) -> std::io::Result<std::process::Output>;
+}
+
+impl CappedOutput for Command {
+    fn output_capped(&mut self) -> std::io::Result<std::process::Output> {
+        let label = Path::new(self.get_program())
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "subprocess".to_string());
+        let mut child = self
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take().map(|o| read_capped(o, label.clone()));
+        let stderr = child.stderr.take().map(|e| read_capped(e, label));
+        let status = child.wait()?;
+        let stdout = stdout.and_then(|h| h.join().ok()).unwrap_or_default();
+        let stderr = stderr.and_then(|h| h.join().ok()).unwrap_or_default();
+        if !status.success() {
+            warn!("[{}] exited with {}: {}", label, status, String::from_utf8_lossy(&stderr).trim_end());
+        }
+        Ok(std::process::Output { status, stdout, stderr })
+    }
+}
+
 #[derive(Debug)]
 pub struct PythonEnvManager {
     python_path: Option<PathBuf>,
     virtual_env_path: Option<PathBuf>,
+    // 使用自定义解释器时，其 site-packages 路径（供嵌入式 Python 注入 sys.path）
+    site_packages_path: Option<PathBuf>,
     is_initialized: bool,
     app_handle: Option<tauri::AppHandle>,
 }
@@ -42,6 +287,7 @@ impl PythonEnvManager {
         Self {
             python_path: None,
             virtual_env_path: None,
+            site_packages_path: None,
             is_initialized: false,
             app_handle: None,
         }
@@ -71,6 +317,27 @@ impl PythonEnvManager {
         let python_files_path = self.extract_python_files()?;
         info!("Python files extracted to: {:?}", python_files_path);
 
+        // 1.5 若配置了自定义 Python 可执行文件且校验通过，直接使用，跳过系统检测与 venv
+        if let Some(custom) = crate::config::get_config()
+            .and_then(|c| c.python)
+            .and_then(|p| p.python_executable)
+            .filter(|p| !p.trim().is_empty())
+        {
+            emitter::emit_toast("正在校验自定义 Python…");
+            match self.try_use_custom_python(Path::new(custom.trim())) {
+                Ok(()) => {
+                    self.is_initialized = true;
+                    info!("Using custom Python executable: {}", custom);
+                    emitter::emit_toast("自定义 Python 就绪（即将加载人脸模型）");
+                    return Ok(());
+                }
+                Err(e) => {
+                    warn!("Custom Python executable '{}' rejected: {}", custom, e);
+                    emitter::emit_toast("自定义 Python 不可用，回退到自动配置环境…");
+                }
+            }
+        }
+
         // 2. 检测系统Python
         emitter::emit_toast("正在检测系统 Python…");
         if let Some(python_path) = self.detect_system_python()? {
@@ -159,12 +426,12 @@ impl PythonEnvManager {
     }
 
     // 在虚拟环境内自动安装最优 ORT 变体（CUDA→DML→CPU）
-    fn auto_install_onnxruntime_in_venv(&self, venv_path: &Path) -> Result<(), String> {
+    fn auto_install_onnxruntime_in_venv(&self, venv_path: &Path, wheels: Option<&Path>) -> Result<(), String> {
         let python_path = self.get_python_executable_from_venv(venv_path)?;
         self.ensure_pip_in_venv(venv_path)?;
 
         // 尝试 CUDA 版（优先），若安装后即可识别 provider，且 CUDA 运行库齐备（含 cuDNN 9），则直接使用
-        let _ = new_cmd(&python_path).arg("-m").arg("pip").arg("install").arg("-U").arg("onnxruntime-gpu>=1.16.3").stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+        let _ = pip_install(&python_path, wheels).arg("-U").arg("onnxruntime-gpu>=1.16.3").output_capped();
         if self.python_has_provider(&python_path, "CUDAExecutionProvider")? {
             if self.python_can_use_cuda(&python_path)? {
                 info!("Using CUDAExecutionProvider in venv");
@@ -178,22 +445,22 @@ impl PythonEnvManager {
         }
 
         // 回退到 DML 版（Windows 下可用）。此处不强制卸载 GPU 包，以便你装好 cuDNN 后下次启动仍可直接切回 CUDA
-        let _ = new_cmd(&python_path).arg("-m").arg("pip").arg("install").arg("-U").arg("onnxruntime-directml>=1.16.3").stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+        let _ = pip_install(&python_path, wheels).arg("-U").arg("onnxruntime-directml>=1.16.3").output_capped();
         if self.python_has_provider(&python_path, "DmlExecutionProvider")? {
             info!("Using DmlExecutionProvider in venv (temporary fallback)");
             return Ok(());
         }
 
         // 最后回退到 CPU 版
-        let _ = new_cmd(&python_path).arg("-m").arg("pip").arg("uninstall").arg("-y").arg("onnxruntime-directml").stdout(Stdio::piped()).stderr(Stdio::piped()).output();
-        let out = new_cmd(&python_path).arg("-m").arg("pip").arg("install").arg("-U").arg("onnxruntime>=1.16.3").stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+        let _ = new_cmd(&python_path).arg("-m").arg("pip").arg("uninstall").arg("-y").arg("onnxruntime-directml").output_capped();
+        let out = pip_install(&python_path, wheels).arg("-U").arg("onnxruntime>=1.16.3").output_capped();
         match out { Ok(o) if o.status.success() => Ok(()), _ => Err("Failed to install onnxruntime (CPU)".to_string()) }
     }
 
     // 在系统 Python 内自动安装最优 ORT 变体（CUDA→DML→CPU）
-    fn auto_install_onnxruntime_in_system_python(&self, python_path: &Path) -> Result<(), String> {
+    fn auto_install_onnxruntime_in_system_python(&self, python_path: &Path, wheels: Option<&Path>) -> Result<(), String> {
         // CUDA 版（优先），若安装后即可识别 provider，且 CUDA 运行库齐备（含 cuDNN 9），则直接使用
-        let _ = new_cmd(python_path).arg("-m").arg("pip").arg("install").arg("-U").arg("onnxruntime-gpu>=1.16.3").stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+        let _ = pip_install(python_path, wheels).arg("-U").arg("onnxruntime-gpu>=1.16.3").output_capped();
         if self.python_has_provider(python_path, "CUDAExecutionProvider")? {
             if self.python_can_use_cuda(python_path)? {
                 info!("Using CUDAExecutionProvider in system python");
@@ -206,14 +473,14 @@ impl PythonEnvManager {
             }
         }
         // DML 版（不卸载 GPU 包，便于后续自动切回 CUDA）
-        let _ = new_cmd(python_path).arg("-m").arg("pip").arg("install").arg("-U").arg("onnxruntime-directml>=1.16.3").stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+        let _ = pip_install(python_path, wheels).arg("-U").arg("onnxruntime-directml>=1.16.3").output_capped();
         if self.python_has_provider(python_path, "DmlExecutionProvider")? {
             info!("Using DmlExecutionProvider in system python (temporary fallback)");
             return Ok(());
         }
         // CPU 版
-        let _ = new_cmd(python_path).arg("-m").arg("pip").arg("uninstall").arg("-y").arg("onnxruntime-directml").stdout(Stdio::piped()).stderr(Stdio::piped()).output();
-        let out = new_cmd(python_path).arg("-m").arg("pip").arg("install").arg("-U").arg("onnxruntime>=1.16.3").stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+        let _ = new_cmd(python_path).arg("-m").arg("pip").arg("uninstall").arg("-y").arg("onnxruntime-directml").output_capped();
+        let out = pip_install(python_path, wheels).arg("-U").arg("onnxruntime>=1.16.3").output_capped();
         match out { Ok(o) if o.status.success() => Ok(()), _ => Err("Failed to install onnxruntime (CPU) in system python".to_string()) }
     }
 
@@ -222,7 +489,7 @@ impl PythonEnvManager {
         let code = format!("import onnxruntime as ort; print('{}' in ort.get_available_providers())", provider);
         let out = new_cmd(python_path)
             .arg("-c").arg(code)
-            .stdout(Stdio::piped()).stderr(Stdio::piped()).output()
+            .output_capped()
             .map_err(|e| format!("execute python failed: {}", e))?;
         if !out.status.success() { return Ok(false); }
         let s = String::from_utf8_lossy(&out.stdout).trim().to_string();
@@ -247,7 +514,7 @@ def ok(names):
 print('True' if ok(names_12) or ok(names_11) else 'False')"#;
             let out = new_cmd(python_path)
                 .arg("-c").arg(code)
-                .stdout(Stdio::piped()).stderr(Stdio::piped()).output()
+                .output_capped()
                 .map_err(|e| format!("execute python failed: {}", e))?;
             if !out.status.success() { return Ok(false); }
             let s = String::from_utf8_lossy(&out.stdout).trim().to_string();
@@ -265,18 +532,14 @@ print('True' if ok(names_12) or ok(names_11) else 'False')"#;
         for cmd in &python_commands {
             if let Ok(output) = new_cmd(cmd)
                 .arg("--version")
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .output()
+                .output_capped()
             {
                 if output.status.success() {
                     // 获取Python可执行文件的完整路径
                     if let Ok(output) = new_cmd(cmd)
                         .arg("-c")
                         .arg("import sys; print(sys.executable)")
-                        .stdout(Stdio::piped())
-                        .stderr(Stdio::piped())
-                        .output()
+                        .output_capped()
                     {
                         if output.status.success() {
                             let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -290,6 +553,31 @@ print('True' if ok(names_12) or ok(names_11) else 'False')"#;
         Ok(None)
     }
 
+    // 校验自定义解释器：存在、依赖齐全，并记录其 site-packages
+    fn try_use_custom_python(&mut self, python_path: &Path) -> Result<(), String> {
+        if !python_path.exists() {
+            return Err(format!("{:?} does not exist", python_path));
+        }
+        if !self.check_system_python_requirements(python_path)? {
+            return Err("missing required packages (cv2/numpy/onnxruntime/insightface)".to_string());
+        }
+        let out = new_cmd(python_path)
+            .arg("-c")
+            .arg("import sysconfig; print(sysconfig.get_paths()['purelib'])")
+            .output_capped()
+            .map_err(|e| format!("execute python failed: {}", e))?;
+        if !out.status.success() {
+            return Err("failed to query site-packages".to_string());
+        }
+        let site = PathBuf::from(String::from_utf8_lossy(&out.stdout).trim().to_string());
+        if !site.exists() {
+            return Err(format!("site-packages {:?} not found", site));
+        }
+        self.python_path = Some(python_path.to_path_buf());
+        self.site_packages_path = Some(site);
+        Ok(())
+    }
+
     #[cfg(target_os = "windows")]
     fn get_local_python_install_dir(&self) -> Result<PathBuf, String> {
         let app_dir = self.get_app_data_dir()?;
@@ -337,9 +625,7 @@ print('True' if ok(names_12) or ok(names_11) else 'False')"#;
                     url,
                     installer_path.display()
                 ))
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .output();
+                .output_capped();
 
             match download {
                 Ok(out) if out.status.success() => info!("Python installer downloaded to: {:?}", installer_path),
@@ -359,9 +645,8 @@ print('True' if ok(names_12) or ok(names_11) else 'False')"#;
             .arg("PrependPath=0")
             .arg("Include_pip=1")
             .arg(format!("TargetDir={}", target_dir.display()))
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .status()
+            .output_capped()
+            .map(|o| o.status)
             .map_err(|e| format!("Failed to start installer: {}", e))?;
 
         if !status.success() {
@@ -384,9 +669,7 @@ print('True' if ok(names_12) or ok(names_11) else 'False')"#;
             let result = new_cmd(python_path)
                 .arg("-c")
                 .arg(&format!("import {}", package))
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .output();
+                .output_capped();
             
             if result.is_err() || !result.unwrap().status.success() {
                 warn!("Required package '{}' not found in system Python", package);
@@ -415,9 +698,7 @@ print('True' if ok(names_12) or ok(names_11) else 'False')"#;
             .arg("-m")
             .arg("venv")
             .arg(&venv_path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output();
+            .output_capped();
         
         match result {
             Ok(output) if output.status.success() => {
@@ -444,16 +725,17 @@ print('True' if ok(names_12) or ok(names_11) else 'False')"#;
             info!("Venv dependencies already satisfied. Skipping installation.");
             return Ok(());
         }
-        // 先升级 pip/setuptools/wheel 提高兼容性
-        let _ = new_cmd(&python_path)
-            .arg("-m").arg("pip").arg("install").arg("-U").arg("pip").arg("setuptools").arg("wheel")
-            .stdout(Stdio::piped()).stderr(Stdio::piped()).output();
         // 识别依赖安装策略：provider=auto 时启用自动探测（CUDA→DML→CPU），否则按固定 provider 安装
         let provider_pref = crate::config::get_config()
             .and_then(|c| c.face)
             .and_then(|f| f.recognition.provider)
             .unwrap_or_else(|| "auto".to_string())
             .to_lowercase();
+        let wheels = select_wheels_dir(&provider_pref)?;
+        // 先升级 pip/setuptools/wheel 提高兼容性
+        let _ = pip_install(&python_path, wheels.as_deref())
+            .arg("-U").arg("pip").arg("setuptools").arg("wheel")
+            .output_capped();
         let app_handle = self.app_handle.clone();
 
         // 发送开始安装事件
@@ -471,11 +753,9 @@ print('True' if ok(names_12) or ok(names_11) else 'False')"#;
                         "正在安装 {}... ({:.1}%)", package, progress
                     ));
                 }
-                let result = new_cmd(&python_path)
-                    .arg("-m").arg("pip").arg("install").arg(package)
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .output();
+                let result = pip_install(&python_path, wheels.as_deref())
+                    .arg(package)
+                    .output_capped();
                 match result {
                     Ok(output) if output.status.success() => {
                         if let Some(ref handle) = app_handle {
@@ -491,17 +771,15 @@ print('True' if ok(names_12) or ok(names_11) else 'False')"#;
             }
 
             // 自动选择并安装最佳 ORT 变体
-            self.auto_install_onnxruntime_in_venv(venv_path)?;
+            self.auto_install_onnxruntime_in_venv(venv_path, wheels.as_deref())?;
 
             // 安装 insightface（放在 ORT 选择之后，避免间接拉取冲突变体）
             let package = "insightface";
             info!("Installing package: {}", package);
             if let Some(ref handle) = app_handle { let _ = handle.emit("python-installation-progress", "正在安装 insightface... (75.0%)"); }
-            let result = new_cmd(&python_path)
-                .arg("-m").arg("pip").arg("install").arg(package)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .output();
+            let result = pip_install(&python_path, wheels.as_deref())
+                .arg(package)
+                .output_capped();
             match result {
                 Ok(output) if output.status.success() => {
                     if let Some(ref handle) = app_handle { let _ = handle.emit("python-installation-success", "成功安装 insightface"); }
@@ -533,11 +811,9 @@ print('True' if ok(names_12) or ok(names_11) else 'False')"#;
                         "正在安装 {}... ({:.1}%)", package, progress
                     ));
                 }
-                let result = new_cmd(&python_path)
-                    .arg("-m").arg("pip").arg("install").arg(package)
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .output();
+                let result = pip_install(&python_path, wheels.as_deref())
+                    .arg(package)
+                    .output_capped();
                 match result {
                     Ok(output) if output.status.success() => {
                         if let Some(ref handle) = app_handle { let _ = handle.emit("python-installation-success", format!("成功安装 {}", package)); }
@@ -578,9 +854,7 @@ print('True' if ok(names_12) or ok(names_11) else 'False')"#;
             let result = new_cmd(&python_path)
                 .arg("-c")
                 .arg(&format!("import {}", package))
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .output();
+                .output_capped();
             
             if result.is_err() || !result.unwrap().status.success() {
                 warn!("Package verification failed for '{}'", package);
@@ -631,7 +905,7 @@ except Exception:
 "#;
             let out = new_cmd(&python_path)
                 .arg("-c").arg(code)
-                .stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+                .output_capped();
             let gpu_installed = match out {
                 Ok(o) if o.status.success() => {
                     let s = String::from_utf8_lossy(&o.stdout).trim().to_string();
@@ -681,16 +955,17 @@ except Exception:
     }
 
     fn install_packages_in_system_python(&self, python_path: &Path) -> Result<bool, String> {
-        // 先升级 pip/setuptools/wheel
-        let _ = new_cmd(python_path)
-            .arg("-m").arg("pip").arg("install").arg("-U").arg("pip").arg("setuptools").arg("wheel")
-            .stdout(Stdio::piped()).stderr(Stdio::piped()).output();
         // provider=auto 时：在系统 Python 中也尝试选择最优 ORT 变体；否则按固定 provider 安装
         let provider_pref = crate::config::get_config()
             .and_then(|c| c.face)
             .and_then(|f| f.recognition.provider)
             .unwrap_or_else(|| "auto".to_string())
             .to_lowercase();
+        let wheels = select_wheels_dir(&provider_pref)?;
+        // 先升级 pip/setuptools/wheel
+        let _ = pip_install(python_path, wheels.as_deref())
+            .arg("-U").arg("pip").arg("setuptools").arg("wheel")
+            .output_capped();
         let app_handle = self.app_handle.clone();
 
         if let Some(ref handle) = app_handle {
@@ -706,30 +981,30 @@ except Exception:
                     "正在安装 {}... ({:.1}%)", package, progress
                 ));
             }
-            let result = new_cmd(python_path)
-                .arg("-m").arg("pip").arg("install").arg(package)
-                .stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+            let result = pip_install(python_path, wheels.as_deref())
+                .arg(package)
+                .output_capped();
             if !matches!(result, Ok(ref o) if o.status.success()) {
                 return Ok(false);
             }
         }
 
         if provider_pref == "auto" {
-            if let Err(e) = self.auto_install_onnxruntime_in_system_python(python_path) {
+            if let Err(e) = self.auto_install_onnxruntime_in_system_python(python_path, wheels.as_deref()) {
                 warn!("auto onnxruntime in system python failed: {}", e);
                 return Ok(false);
             }
             // 安装 insightface
-            let result = new_cmd(python_path)
-                .arg("-m").arg("pip").arg("install").arg("insightface")
-                .stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+            let result = pip_install(python_path, wheels.as_deref())
+                .arg("insightface")
+                .output_capped();
             if !matches!(result, Ok(ref o) if o.status.success()) { return Ok(false); }
         } else {
             let ort_pkg = match provider_pref.as_str() { "cuda" => "onnxruntime-gpu", "dml" => "onnxruntime-directml", _ => "onnxruntime" };
             for package in [ort_pkg, "insightface"] {
-                let result = new_cmd(python_path)
-                    .arg("-m").arg("pip").arg("install").arg(package)
-                    .stdout(Stdio::piped()).stderr(Stdio::piped()).output();
+                let result = pip_install(python_path, wheels.as_deref())
+                    .arg(package)
+                    .output_capped();
                 if !matches!(result, Ok(ref o) if o.status.success()) { return Ok(false); }
             }
         }
@@ -799,8 +1074,11 @@ except Exception:
         // 1) 尝试启用 ensurepip
         let status = new_cmd(&py)
             .arg("-m").arg("ensurepip").arg("--upgrade")
-            .stdout(Stdio::piped()).stderr(Stdio::piped()).status();
+            .output_capped().map(|o| o.status);
         if !matches!(status, Ok(s) if s.success()) {
+            if offline_forced() {
+                return Err("pip is unavailable (ensurepip failed) and get-pip.py cannot be downloaded in offline mode".to_string());
+            }
             // 2) ensurepip 不可用，下载官方 get-pip.py 引导
             #[cfg(target_os = "windows")]
             {
@@ -810,13 +1088,13 @@ except Exception:
                     .arg("-NoProfile").arg("-ExecutionPolicy").arg("Bypass")
                     .arg("-Command")
                     .arg(format!("[Net.ServicePointManager]::SecurityProtocol = [Net.SecurityProtocolType]::Tls12; Invoke-WebRequest -UseBasicParsing -Uri '{}' -OutFile '{}'", url, tmp.display()))
-                    .stdout(Stdio::piped()).stderr(Stdio::piped()).status();
+                    .output_capped().map(|o| o.status);
                 if !matches!(dl, Ok(s) if s.success()) {
                     return Err("Failed to download get-pip.py".to_string());
                 }
                 let run = new_cmd(&py)
-                    .arg(tmp)
-                    .stdout(Stdio::piped()).stderr(Stdio::piped()).status();
+                    .arg(tmp).args(pip_index_args())
+                    .output_capped().map(|o| o.status);
                 if !matches!(run, Ok(s) if s.success()) {
                     return Err("Failed to bootstrap pip via get-pip.py".to_string());
                 }
@@ -827,13 +1105,13 @@ except Exception:
                 let tmp = std::env::temp_dir().join("get-pip.py");
                 let dl = new_cmd("curl")
                     .arg("-fsSL").arg(url).arg("-o").arg(&tmp)
-                    .stdout(Stdio::piped()).stderr(Stdio::piped()).status();
+                    .output_capped().map(|o| o.status);
                 if !matches!(dl, Ok(s) if s.success()) {
                     return Err("Failed to download get-pip.py (curl)".to_string());
                 }
                 let run = new_cmd(&py)
-                    .arg(tmp)
-                    .stdout(Stdio::piped()).stderr(Stdio::piped()).status();
+                    .arg(tmp).args(pip_index_args())
+                    .output_capped().map(|o| o.status);
                 if !matches!(run, Ok(s) if s.success()) {
                     return Err("Failed to bootstrap pip via get-pip.py".to_string());
                 }
@@ -952,24 +1230,7 @@ except Exception:
     }
 
     fn get_app_data_dir(&self) -> Result<PathBuf, String> {
-        #[cfg(target_os = "windows")]
-        {
-            let app_data = std::env::var("APPDATA")
-                .map_err(|_| "Could not get APPDATA environment variable".to_string())?;
-            Ok(PathBuf::from(app_data).join("screen-ghost"))
-        }
-        #[cfg(target_os = "macos")]
-        {
-            let home = std::env::var("HOME")
-                .map_err(|_| "Could not get HOME environment variable".to_string())?;
-            Ok(PathBuf::from(home).join("Library/Application Support/screen-ghost"))
-        }
-        #[cfg(target_os = "linux")]
-        {
-            let home = std::env::var("HOME")
-                .map_err(|_| "Could not get HOME environment variable".to_string())?;
-            Ok(PathBuf::from(home).join(".config/screen-ghost"))
-        }
+        get_app_data_dir()
     }
 
     // 移除未使用的 get_python_executable（对外提供全局函数即可）
@@ -994,6 +1255,28 @@ except Exception:
     }
 }
 
+/// 应用数据目录（Windows 下为 %APPDATA%/screen-ghost）
+pub fn get_app_data_dir() -> Result<PathBuf, String> {
+    #[cfg(target_os = "windows")]
+    {
+        let app_data = std::env::var("APPDATA")
+            .map_err(|_| "Could not get APPDATA environment variable".to_string())?;
+        Ok(PathBuf::from(app_data).join("screen-ghost"))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let home = std::env::var("HOME")
+            .map_err(|_| "Could not get HOME environment variable".to_string())?;
+        Ok(PathBuf::from(home).join("Library/Application Support/screen-ghost"))
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let home = std::env::var("HOME")
+            .map_err(|_| "Could not get HOME environment variable".to_string())?;
+        Ok(PathBuf::from(home).join(".config/screen-ghost"))
+    }
+}
+
 // 移除：initialize_python_environment 旧空实现（未被调用）
 
 pub fn initialize_python_environment_with_app_handle(app_handle: &tauri::AppHandle) -> Result<(), String> {
@@ -1026,6 +1309,89 @@ pub fn get_python_files_path() -> Result<PathBuf, String> {
     }
 }
 
+// 内置支持的 Python 版本范围（含两端）：超出范围时 numpy/onnxruntime 等二进制包可能无法加载
+const SUPPORTED_PYTHON_MIN: (u32, u32) = (3, 8);
+const SUPPORTED_PYTHON_MAX: (u32, u32) = (3, 12);
+
+// 解析 "3.11.9" / "Python 3.11.9" / "3.12" 为 (major, minor, patch)，缺失的 patch 记为 0
+pub fn parse_python_version(s: &str) -> Option<(u32, u32, u32)> {
+    let s = s.trim();
+    let s = s.strip_prefix("Python").map(str::trim).unwrap_or(s);
+    let mut parts = s.split('.');
+    let major = parts.next()?.trim().parse().ok()?;
+    let minor = parts.next()?.trim().parse().ok()?;
+    // patch 可能带后缀（如 "0rc1"），只取前导数字
+    let patch = parts
+        .next()
+        .map(|p| p.chars().take_while(|c| c.is_ascii_digit()).collect::<String>())
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+fn supported_python_range() -> ((u32, u32), (u32, u32)) {
+    let cfg = crate::config::get_config().and_then(|c| c.python);
+    let bound = |v: Option<String>, default: (u32, u32)| {
+        v.as_deref()
+            .and_then(parse_python_version)
+            .map(|(major, minor, _)| (major, minor))
+            .unwrap_or(default)
+    };
+    let min = bound(cfg.as_ref().and_then(|c| c.min_version.clone()), SUPPORTED_PYTHON_MIN);
+    let max = bound(cfg.as_ref().and_then(|c| c.max_version.clone()), SUPPORTED_PYTHON_MAX);
+    (min, max)
+}
+
+// venv 根目录的 pyvenv.cfg 记录了创建它的解释器版本
+fn venv_python_version() -> Option<(u32, u32, u32)> {
+    let venv = PYTHON_ENV_MANAGER.get()?.virtual_env_path.clone()?;
+    let cfg = fs::read_to_string(venv.join("pyvenv.cfg")).ok()?;
+    parse_pyvenv_version(&cfg)
+}
+
+// 从 pyvenv.cfg 内容中取 version / version_info 行
+fn parse_pyvenv_version(cfg: &str) -> Option<(u32, u32, u32)> {
+    cfg.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        match key.trim() {
+            "version" | "version_info" => parse_python_version(value),
+            _ => None,
+        }
+    })
+}
+
+/// 校验嵌入解释器的版本：需在支持范围内，且与 venv 的创建版本一致（二进制包按 major.minor 编译）。
+/// 在导入任何第三方包之前调用，不满足时返回可操作的错误，而不是在后续导入中崩溃
+pub fn check_embedded_python_version(major: u32, minor: u32, patch: u32) -> Result<(), String> {
+    check_python_version((major, minor, patch), supported_python_range(), venv_python_version())?;
+    info!("[check_embedded_python_version] Python {}.{}.{} ok", major, minor, patch);
+    Ok(())
+}
+
+// 版本校验本体：range 为支持的 (major, minor) 闭区间，venv 为 venv 的创建版本（无 venv 时为 None）
+fn check_python_version(
+    (major, minor, patch): (u32, u32, u32),
+    (min, max): ((u32, u32), (u32, u32)),
+    venv: Option<(u32, u32, u32)>,
+) -> Result<(), String> {
+    let v = (major, minor);
+    if v < min || v > max {
+        return Err(format!(
+            "Python {}.{}.{} is not supported (supported {}.{} - {}.{}); install a supported Python or adjust [python] min_version/max_version",
+            major, minor, patch, min.0, min.1, max.0, max.1
+        ));
+    }
+    if let Some((vm, vn, vp)) = venv {
+        if (vm, vn) != v {
+            return Err(format!(
+                "Python version mismatch: embedded interpreter is {}.{}.{} but the virtual environment was created with {}.{}.{}; delete the venv in the app data directory to recreate it",
+                major, minor, patch, vm, vn, vp
+            ));
+        }
+    }
+    Ok(())
+}
+
 /// 获取虚拟环境的 site-packages 路径，供嵌入式 Python 注入 sys.path 使用
 pub fn get_venv_site_packages_path() -> Result<PathBuf, String> {
     if let Some(m) = PYTHON_ENV_MANAGER.get() {
@@ -1053,10 +1419,143 @@ pub fn get_venv_site_packages_path() -> Result<PathBuf, String> {
                 }
                 Err("site-packages not found in venv (Unix)".to_string())
             }
+        } else if let Some(site) = &m.site_packages_path {
+            Ok(site.clone())
         } else {
             Err("Virtual environment path not set".to_string())
         }
     } else {
         Err("Python environment not initialized".to_string())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(dropped: usize) -> Vec<u8> {
+        format!("...[truncated {} bytes]\n", dropped).into_bytes()
+    }
+
+    #[test]
+    fn capped_tail_keeps_output_below_the_cap() {
+        let buf = vec![b'a'; OUTPUT_TAIL_BYTES - 1];
+        assert_eq!(capped_tail(buf.clone(), OUTPUT_TAIL_BYTES, 0), buf);
+    }
+
+    #[test]
+    fn capped_tail_keeps_output_exactly_at_the_cap() {
+        let buf = vec![b'a'; OUTPUT_TAIL_BYTES];
+        assert_eq!(capped_tail(buf.clone(), OUTPUT_TAIL_BYTES, 0), buf);
+    }
+
+    #[test]
+    fn capped_tail_truncates_output_above_the_cap() {
+        let mut buf = vec![b'a'; 10];
+        buf.extend(vec![b'b'; OUTPUT_TAIL_BYTES]);
+        let mut expected = marker(10);
+        expected.extend(vec![b'b'; OUTPUT_TAIL_BYTES]);
+        assert_eq!(capped_tail(buf, OUTPUT_TAIL_BYTES, 0), expected);
+    }
+
+    #[test]
+    fn capped_tail_counts_previously_dropped_bytes() {
+        let out = capped_tail(b"tail".to_vec(), OUTPUT_TAIL_BYTES, 123);
+        let mut expected = marker(123);
+        expected.extend_from_slice(b"tail");
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn capped_tail_does_not_split_a_multibyte_char() {
+        // 30000 个 3 字节字符：按字节截取的起点 90000 - 65536 = 24464 落在字符中间，应后移到 24465
+        let text = "中".repeat(30000);
+        let out = capped_tail(text.clone().into_bytes(), OUTPUT_TAIL_BYTES, 0);
+        let head = marker(24465);
+        assert!(out.starts_with(&head));
+        let tail = String::from_utf8(out[head.len()..].to_vec()).expect("tail is valid UTF-8");
+        assert_eq!(tail.len(), OUTPUT_TAIL_BYTES - 1);
+        assert!(text.ends_with(&tail));
+    }
+
+    #[test]
+    fn tail_buffer_matches_capped_tail_across_pushes() {
+        let mut tail = TailBuffer::new(OUTPUT_TAIL_BYTES);
+        let line = "行 line\n".repeat(100);
+        let mut all = Vec::new();
+        for _ in 0..200 {
+            tail.push(line.as_bytes());
+            all.extend_from_slice(line.as_bytes());
+        }
+        let out = tail.into_bytes();
+        assert_eq!(out, capped_tail(all, OUTPUT_TAIL_BYTES, 0));
+        assert!(String::from_utf8(out).is_ok());
+    }
+
+    #[test]
+    fn parse_python_version_accepts_common_forms() {
+        assert_eq!(parse_python_version("3.11.9"), Some((3, 11, 9)));
+        assert_eq!(parse_python_version("Python 3.12"), Some((3, 12, 0)));
+        assert_eq!(parse_python_version("3.13.0rc1"), Some((3, 13, 0)));
+        assert_eq!(parse_python_version(" Python 3.10.4\n"), Some((3, 10, 4)));
+    }
+
+    #[test]
+    fn parse_python_version_rejects_garbage() {
+        assert_eq!(parse_python_version(""), None);
+        assert_eq!(parse_python_version("3"), None);
+        assert_eq!(parse_python_version("Python"), None);
+        assert_eq!(parse_python_version("three.eleven"), None);
+    }
+
+    #[test]
+    fn parse_pyvenv_version_reads_version_info() {
+        let cfg = "home = C:\\Python311\ninclude-system-site-packages = false\nversion_info = 3.11.9.final.0\n";
+        assert_eq!(parse_pyvenv_version(cfg), Some((3, 11, 9)));
+        assert_eq!(parse_pyvenv_version("version = 3.10.2\n"), Some((3, 10, 2)));
+        assert_eq!(parse_pyvenv_version("home = /usr/bin\n"), None);
+    }
+
+    const RANGE: ((u32, u32), (u32, u32)) = (SUPPORTED_PYTHON_MIN, SUPPORTED_PYTHON_MAX);
+
+    #[test]
+    fn check_python_version_accepts_supported_versions() {
+        assert!(check_python_version((3, 8, 0), RANGE, None).is_ok());
+        assert!(check_python_version((3, 12, 4), RANGE, None).is_ok());
+        assert!(check_python_version((3, 11, 9), RANGE, Some((3, 11, 2))).is_ok());
+    }
+
+    #[test]
+    fn check_python_version_rejects_out_of_range() {
+        let err = check_python_version((3, 13, 0), RANGE, None).unwrap_err();
+        assert!(err.contains("3.13.0 is not supported"), "{}", err);
+        assert!(check_python_version((3, 7, 9), RANGE, None).is_err());
+        assert!(check_python_version((2, 7, 18), RANGE, None).is_err());
+    }
+
+    #[test]
+    fn check_python_version_rejects_venv_mismatch() {
+        let err = check_python_version((3, 11, 9), RANGE, Some((3, 10, 4))).unwrap_err();
+        assert!(err.contains("version mismatch"), "{}", err);
+        assert!(err.contains("3.10.4"), "{}", err);
+    }
+    #[test]
+    fn index_args_trust_only_http_mirrors_by_default() {
+        assert_eq!(
+            index_args("http://mirror.local:8080/simple", false),
+            ["--index-url", "http://mirror.local:8080/simple", "--trusted-host", "mirror.local:8080"]
+        );
+        assert_eq!(
+            index_args("https://pypi.tuna.tsinghua.edu.cn/simple", false),
+            ["--index-url", "https://pypi.tuna.tsinghua.edu.cn/simple"]
+        );
+    }
+
+    #[test]
+    fn index_args_trust_https_host_when_opted_in() {
+        assert_eq!(
+            index_args("https://user:pw@mirror.local/simple", true),
+            ["--index-url", "https://user:pw@mirror.local/simple", "--trusted-host", "mirror.local"]
+        );
+    }
+}
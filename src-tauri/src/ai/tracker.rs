@@ -0,0 +1,120 @@
+use std::sync::Mutex;
+use std::time::Instant;
+
+use log::debug;
+
+use crate::config;
+use crate::utils::rect::Rect;
+use crate::utils::sync::lock_or_recover;
+
+// 检测间隔（detection_interval）内的轻量跟踪：每次检测后按 IoU 把检测框与已有轨迹贪心关联，
+// 由相邻两次检测的中心位移估计速度；不检测的帧按匀速把每条轨迹向前推，使遮罩跟随运动而不是在下一次检测时跳变。
+// 检测结果始终为准：未关联上的检测框新建轨迹（速度为 0），本次未检出的轨迹直接丢弃（漏检保持交给 overlay 的 mosaic_hold_ms）
+
+// 检测框与轨迹关联所需的最小 IoU
+const MATCH_IOU: f32 = 0.3;
+// 速度的指数平滑系数：新速度所占比例
+const VELOCITY_ALPHA: f32 = 0.5;
+// 最长外推时长/ms：检测迟迟未返回时不再继续外推，避免遮罩越跑越远
+const MAX_PREDICT_MS: f32 = 500.0;
+
+struct Track {
+    rect: Rect,
+    angle: f32,
+    color: Option<String>,
+    // 中心点速度（物理像素/ms）
+    vx: f32,
+    vy: f32,
+    updated_at: Instant,
+}
+
+static TRACKS: Mutex<Vec<Track>> = Mutex::new(Vec::new());
+
+pub fn is_enabled() -> bool {
+    config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.enable_tracking)
+        .unwrap_or(false)
+}
+
+fn center(r: &Rect) -> (f32, f32) {
+    (r.x as f32 + r.width as f32 / 2.0, r.y as f32 + r.height as f32 / 2.0)
+}
+
+// 用一次检测结果更新轨迹；colors 与 items 一一对应（纯色 auto 样式的采样色，外推时沿用）
+pub fn update(items: &[(Rect, f32)], colors: Option<&[String]>) {
+    let now = Instant::now();
+    let mut tracks = lock_or_recover(&TRACKS, "tracks");
+    let mut previous: Vec<Option<Track>> = tracks.drain(..).map(Some).collect();
+
+    // 按 IoU 从高到低贪心关联
+    let mut pairs: Vec<(f32, usize, usize)> = Vec::new();
+    for (di, (rect, _)) in items.iter().enumerate() {
+        for (ti, t) in previous.iter().enumerate() {
+            let iou = t.as_ref().map(|t| t.rect.iou(rect)).unwrap_or(0.0);
+            if iou >= MATCH_IOU {
+                pairs.push((iou, di, ti));
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut matched: Vec<Option<Track>> = (0..items.len()).map(|_| None).collect();
+    for (_, di, ti) in pairs {
+        if matched[di].is_none() {
+            if let Some(t) = previous[ti].take() {
+                matched[di] = Some(t);
+            }
+        }
+    }
+
+    let mut associated = 0;
+    for (i, ((rect, angle), prev)) in items.iter().zip(matched).enumerate() {
+        let color = colors.and_then(|c| c.get(i).cloned());
+        let (vx, vy) = match prev {
+            Some(p) => {
+                associated += 1;
+                let dt = now.duration_since(p.updated_at).as_secs_f32() * 1000.0;
+                if dt >= 1.0 {
+                    let (px, py) = center(&p.rect);
+                    let (nx, ny) = center(rect);
+                    (
+                        VELOCITY_ALPHA * (nx - px) / dt + (1.0 - VELOCITY_ALPHA) * p.vx,
+                        VELOCITY_ALPHA * (ny - py) / dt + (1.0 - VELOCITY_ALPHA) * p.vy,
+                    )
+                } else {
+                    (p.vx, p.vy)
+                }
+            }
+            None => (0.0, 0.0),
+        };
+        tracks.push(Track { rect: rect.clone(), angle: *angle, color, vx, vy, updated_at: now });
+    }
+    debug!("[tracker] {} detections, {} associated with existing tracks", items.len(), associated);
+}
+
+// 按匀速外推当前各轨迹，结果裁剪到 bounds（显示器范围）内；第二个返回值为对应的颜色（全部轨迹都带颜色时）
+pub fn predict(bounds: &Rect) -> (Vec<(Rect, f32)>, Option<Vec<String>>) {
+    let now = Instant::now();
+    let tracks = lock_or_recover(&TRACKS, "tracks");
+    let mut items = Vec::with_capacity(tracks.len());
+    let mut colors = Vec::with_capacity(tracks.len());
+    for t in tracks.iter() {
+        let dt = (now.duration_since(t.updated_at).as_secs_f32() * 1000.0).min(MAX_PREDICT_MS);
+        let moved = Rect::new(
+            t.rect.x + (t.vx * dt).round() as i32,
+            t.rect.y + (t.vy * dt).round() as i32,
+            t.rect.width,
+            t.rect.height,
+        );
+        if let Some(r) = moved.intersection(bounds) {
+            items.push((r.with_score(t.rect.score), t.angle));
+            colors.push(t.color.clone());
+        }
+    }
+    let colors = colors.into_iter().collect::<Option<Vec<String>>>();
+    (items, colors)
+}
+
+pub fn reset() {
+    lock_or_recover(&TRACKS, "tracks").clear();
+}
@@ -1,34 +1,61 @@
-use crate::{monitor::{monitor, MonitorInfo}, system::monitoring};
+use crate::{monitor::{monitor, screen_shot, MonitorInfo}, system::monitoring};
 use crate::config;
 use crate::ai;
+use crate::utils::rect::Rect;
+use crate::mosaic::MosaicStyle;
 use crate::api::emitter as app_emitter;
-use crate::overlay::overlay::get_latest_mosaic_payload;
+use crate::overlay::overlay::{self as overlay_ops, get_latest_mosaic_payload, MosaicEmitStats};
 
 #[tauri::command]
 pub fn get_monitors() -> Vec<MonitorInfo> {
     monitor::list_monitors().unwrap()
 }
 
-#[tauri::command]
-pub async fn set_working_monitor(monitor: MonitorInfo) -> Result<(), String> {
-    // 就绪保护：Python 环境与人脸模型均需就绪
+// 就绪保护：Python 环境与人脸模型均需就绪
+fn ensure_ready(ctx: &str) -> Result<(), String> {
     let py_ready = ai::python_env::is_python_ready();
     let face_ready = crate::ai::faces::is_face_model_ready();
     if !py_ready {
         app_emitter::emit_toast("正在完成初始化，请稍候…");
+        app_emitter::emit_error("python_not_ready", "Python environment is not ready", Some(ctx));
         return Err("python_not_ready".to_string());
     }
+    // 模型加载失败时允许以静态遮罩模式运行（遮住整个显示器）
+    if !face_ready && crate::ai::faces::is_face_model_failed() {
+        app_emitter::emit_toast("人脸模型不可用，以静态遮罩模式运行");
+        return Ok(());
+    }
     if !face_ready {
         app_emitter::emit_toast("人脸模型未就绪，请重启应用后重试");
+        app_emitter::emit_error("face_model_not_ready", "Face model is not ready", Some(ctx));
         return Err("face_model_not_ready".to_string());
     }
-    monitoring::set_working_monitor(monitor).await;
     Ok(())
 }
 
+#[tauri::command]
+pub async fn set_working_monitor(monitor: MonitorInfo) -> Result<(), String> {
+    ensure_ready("set_working_monitor")?;
+    monitoring::set_working_monitor(monitor).await
+}
+
+#[tauri::command]
+pub async fn set_working_monitor_by_name(name: String) -> Result<MonitorInfo, String> {
+    ensure_ready("set_working_monitor_by_name")?;
+    let monitor = monitoring::set_working_monitor_by_name(&name).await?;
+    Ok(monitor)
+}
+
+#[tauri::command]
+pub async fn prewarm_capture(monitor: MonitorInfo) -> Result<(), String> {
+    crate::monitor::screen_shot::prewarm_capture(&monitor)
+}
+
 #[tauri::command]
 pub fn is_ready() -> bool {
-    crate::ai::python_env::is_python_ready() && crate::ai::faces::is_face_model_ready()
+    // 模型失败时也视为可用（静态遮罩模式）
+    crate::ai::python_env::is_python_ready()
+        && (crate::ai::faces::is_face_model_ready() || crate::ai::faces::is_face_model_failed())
 }
 
 #[tauri::command]
@@ -37,12 +64,327 @@ pub fn stop_monitoring() {
     monitoring::stop_monitoring();
 }
 
+// 指定显示器（缺省为当前工作显示器）实际生效的配置，含显示器覆盖项与运行时调整的识别阈值
+#[tauri::command]
+pub fn get_effective_config(monitor_name: Option<String>) -> Option<config::Config> {
+    let name = monitor_name.or_else(|| monitoring::working_monitor().and_then(|m| m.name));
+    config::get_effective_config(name.as_deref())
+}
+
+// CSS 声明块字符串（旧格式）或结构化样式 { kind, color, block_size, blur_radius, css }
 #[tauri::command]
-pub fn get_mosaic_style() -> String {
-    config::get_config().unwrap().monitoring.unwrap().mosaic_style
+pub fn get_mosaic_style() -> config::MosaicStyleSetting {
+    config::get_config()
+        .and_then(|c| c.monitoring)
+        .map(|m| m.mosaic_style)
+        .unwrap_or_default()
+}
+
+// 当前生效的结构化遮罩样式（pixelate / sticker / solid）
+#[tauri::command]
+pub fn get_cover_style() -> MosaicStyle {
+    overlay_ops::cover_style()
+}
+
+// 校验并实时应用遮罩样式，立即按新样式重发最新一帧；persist 为 true 时同时写回 config.toml
+#[tauri::command]
+pub fn set_cover_style(style: MosaicStyle, persist: Option<bool>) -> Result<(), String> {
+    style.validate()?;
+    let mut applied = false;
+    config::update_config(|c| {
+        if let Some(m) = c.monitoring.as_mut() {
+            m.cover_style = Some(style.clone());
+            applied = true;
+        }
+    })?;
+    if !applied {
+        return Err("config has no [monitoring] section".to_string());
+    }
+    overlay_ops::reemit_with_current_style();
+    if persist.unwrap_or(false) {
+        let value = toml::Value::try_from(&style).map_err(|e| format!("serialize cover_style failed: {}", e))?;
+        config::persist_config_value("monitoring", "cover_style", &value)?;
+    }
+    Ok(())
 }
 
 #[tauri::command]
 pub fn get_latest_mosaic() -> Option<serde_json::Value> {
     get_latest_mosaic_payload()
+}
+
+// 暂停监控：保留 overlay 窗口，只停止截图与检测并清空遮罩，返回是否为本次新暂停
+#[tauri::command]
+pub fn pause_monitoring() -> Result<bool, String> {
+    monitoring::pause_monitoring()
+}
+
+// 恢复暂停的监控，返回此前是否处于暂停
+#[tauri::command]
+pub fn resume_monitoring() -> Result<bool, String> {
+    monitoring::resume_monitoring()
+}
+
+// 临时遮住整个工作显示器 duration_ms 毫秒，到期恢复检测驱动的遮罩；0 表示取消
+#[tauri::command]
+pub fn cover_all_for(duration_ms: u64) -> Result<(), String> {
+    monitoring::cover_all_for(duration_ms)
+}
+
+// 锁定当前遮罩 duration_ms 毫秒（检测照常进行但不更新遮罩），到期自动解锁；0 表示立即解锁
+#[tauri::command]
+pub fn lock_mosaics(duration_ms: u64) -> Result<(), String> {
+    monitoring::lock_mosaics(duration_ms)
+}
+
+// 测试用：在工作显示器的指定物理像素坐标注入一个遮罩框，绕过截图与检测
+#[tauri::command]
+pub fn inject_test_box(rect: Rect) -> Result<(), String> {
+    let monitor = monitoring::working_monitor().ok_or("monitoring is not running")?;
+    overlay_ops::inject_test_box(rect, monitor.scale_factor)
+}
+
+// 隐私自检：当前遮罩是否完全覆盖工作显示器内的物理像素坐标 (x, y)，可配合 inject_test_box 断言框中心受保护
+#[tauri::command]
+pub fn assert_coverage(x: i32, y: i32) -> bool {
+    overlay_ops::covers_point(x, y)
+}
+
+#[tauri::command]
+pub fn clear_mosaics() {
+    if let Some(monitor) = monitoring::working_monitor() {
+        overlay_ops::clear_mosaics(monitor.scale_factor);
+    } else {
+        overlay_ops::clear_test_boxes();
+    }
+}
+
+#[tauri::command]
+pub fn set_recognition_threshold(threshold: f32) -> Result<f32, String> {
+    let applied = ai::faces::set_recognition_threshold(threshold)?;
+    monitoring::reset_pipeline();
+    Ok(applied)
+}
+
+#[tauri::command]
+pub fn get_recognition_threshold() -> f32 {
+    ai::faces::get_recognition_threshold()
+}
+
+#[tauri::command]
+pub fn set_coverage_mode(mode: String) -> Result<(), String> {
+    ai::faces::set_coverage_mode(&mode)?;
+    monitoring::reset_pipeline();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_coverage_mode() -> String {
+    ai::faces::get_coverage_mode().to_string()
+}
+
+#[tauri::command]
+pub fn release_idle_resources(release_model: Option<bool>) -> Result<(), String> {
+    monitoring::release_idle_resources(release_model.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn set_recognition_enabled(enabled: bool) {
+    ai::faces::set_recognition_enabled(enabled);
+    monitoring::reset_pipeline();
+}
+
+#[tauri::command]
+pub fn get_recognition_enabled() -> bool {
+    ai::faces::is_recognition_enabled()
+}
+
+#[tauri::command]
+pub fn set_follow_active_window(enabled: bool) {
+    monitoring::set_follow_active_window(enabled);
+    monitoring::reset_pipeline();
+}
+
+#[tauri::command]
+pub fn get_follow_active_window() -> bool {
+    monitoring::is_following_active_window()
+}
+
+#[tauri::command]
+pub fn set_exclude_taskbar(enabled: bool) {
+    monitoring::set_exclude_taskbar(enabled);
+    monitoring::reset_pipeline();
+}
+
+#[tauri::command]
+pub fn get_exclude_taskbar() -> bool {
+    monitoring::is_excluding_taskbar()
+}
+
+// 遮罩不可见（overlay 隐藏或显示器关闭）时是否暂停检测
+#[tauri::command]
+pub fn set_pause_when_hidden(enabled: bool) {
+    monitoring::set_pause_when_hidden(enabled);
+}
+
+#[tauri::command]
+pub fn get_pause_when_hidden() -> bool {
+    monitoring::is_pausing_when_hidden()
+}
+
+#[tauri::command]
+pub fn overlay_status() -> crate::overlay::OverlayStatus {
+    crate::overlay::overlay_status()
+}
+
+#[tauri::command]
+pub fn get_mosaic_emit_stats() -> MosaicEmitStats {
+    overlay_ops::get_mosaic_emit_stats()
+}
+
+// 汇总性能统计：最近 frames 帧（缺省 120）的截图/检测耗时、截图方法与实际帧率，mosaic 投递 seq 统计与 GIL 等待/持有耗时
+#[tauri::command]
+pub fn get_perf_stats(frames: Option<usize>) -> serde_json::Value {
+    serde_json::json!({
+        "frames": monitoring::perf::stats(frames.unwrap_or(120)),
+        "mosaic_emit": overlay_ops::get_mosaic_emit_stats(),
+        "gil": crate::ai::faces::get_gil_stats(),
+        "detection_queue": monitoring::detection_queue::stats(),
+    })
+}
+
+// 合并配置补丁（与 config.toml 结构相同的 JSON，可只含部分字段），校验范围后写回配置文件并立即生效
+#[tauri::command]
+pub fn update_config(patch: serde_json::Value) -> Result<(), String> {
+    config::apply_patch(patch)?;
+    monitoring::reset_pipeline();
+    monitoring::sync_extra_monitors();
+    app_emitter::emit_config_changed();
+    Ok(())
+}
+
+// 当前配置来自配置文件还是默认值（文件缺失或解析失败时），供界面提示
+#[tauri::command]
+pub fn get_config_status() -> crate::config::ConfigStatus {
+    crate::config::get_config_status()
+}
+
+// 最近（性能环形缓冲内，至多 1000 帧）每帧检测到的人脸数分布：[(人脸数, 帧数)]，用于了解画面中通常有几张脸及误检情况
+#[tauri::command]
+pub fn get_detection_histogram() -> Vec<(usize, u32)> {
+    monitoring::perf::face_count_histogram()
+}
+
+// 本机截图能力（DXGI/WARP/WGC/排除截图），启动时探测一次并缓存
+#[tauri::command]
+pub fn get_capture_capabilities() -> crate::monitor::capabilities::CaptureCapabilities {
+    crate::monitor::capabilities::get().clone()
+}
+
+// 查看各显示器自适应截图方法的统计（连续成功次数与首选方法）
+#[tauri::command]
+pub fn get_capture_stats() -> Vec<(usize, screen_shot::CaptureStatsInfo)> {
+    screen_shot::capture_stats()
+}
+
+// 重置截图方法统计：指定显示器或全部（monitor_id 缺省），返回清除的条目数
+#[tauri::command]
+pub fn reset_capture_stats(monitor_id: Option<usize>) -> usize {
+    screen_shot::reset_capture_stats(monitor_id)
+}
+
+// 导出最近的逐帧性能样本（.csv 或 .json），返回写入的样本数
+#[tauri::command]
+pub fn export_perf_report(path: String) -> Result<usize, String> {
+    monitoring::perf::export_report(&path)
+}
+
+// 开始录制遮罩后的画面（依赖 ffmpeg），仅在监控进行中可用
+#[tauri::command]
+pub fn start_recording(path: String) -> Result<(), String> {
+    let monitor = monitoring::working_monitor().ok_or("monitoring is not running")?;
+    let (width, height) = monitoring::capture_size(&monitor);
+    monitoring::recording::start(&path, width, height)
+}
+
+#[tauri::command]
+pub fn stop_recording() -> Option<String> {
+    monitoring::recording::stop()
+}
+
+// 支持排查：导出最近一次截图的原始帧（PNG），显式调用并记录日志
+#[tauri::command]
+pub fn get_last_frame_png() -> Result<Option<Vec<u8>>, String> {
+    monitoring::get_last_frame_png()
+}
+
+// 运行时切换推理 provider（cpu/cuda/dml），不重装 onnxruntime；provider 不可用时返回错误并保留原模型。
+// 成功后更新内存中的配置（不写回文件），便于现场对比 CUDA 与 CPU 的检测耗时
+#[tauri::command]
+pub async fn set_provider(provider: String) -> Result<(), String> {
+    ensure_ready("set_provider")?;
+    let provider = provider.trim().to_lowercase();
+    if !matches!(provider.as_str(), "cpu" | "cuda" | "dml") {
+        return Err(format!("unknown provider '{}', expected cpu/cuda/dml", provider));
+    }
+    let active = crate::ai::faces::set_provider(&provider)?;
+    log::info!("[set_provider] switched to {} (session providers {:?})", provider, active);
+    config::update_config(|c| {
+        if let Some(f) = c.face.as_mut() {
+            f.recognition.provider = Some(provider.clone());
+        }
+    })
+}
+
+// 识别模型实际使用的 execution provider（如 CUDAExecutionProvider），模型未加载时返回错误
+#[tauri::command]
+pub async fn get_active_provider() -> Result<String, String> {
+    ensure_ready("get_active_provider")?;
+    let active = crate::ai::faces::get_active_provider()?;
+    if active.is_empty() {
+        return Err("face model is not loaded".to_string());
+    }
+    Ok(active)
+}
+
+// 支持排查：截取指定显示器当前画面，返回 Base64 编码的 PNG
+#[tauri::command]
+pub async fn capture_still(monitor_id: usize) -> Result<String, String> {
+    monitoring::capture_still_png(monitor_id).map(|png| crate::utils::base64::encode(&png))
+}
+
+// 用同一帧按多个缩放倍率检测并计时，辅助选择 capture_scale；每完成一个倍率发送 benchmark-progress
+#[tauri::command]
+pub async fn benchmark_detection(monitor: MonitorInfo, scales: Vec<f32>) -> Result<Vec<monitoring::ScaleBench>, String> {
+    ensure_ready("benchmark_detection")?;
+    monitoring::benchmark_detection(&monitor, &scales)
+}
+
+#[tauri::command]
+pub async fn validate_face_folder(path: String) -> Result<crate::ai::faces::FolderReport, String> {
+    ensure_ready("validate_face_folder")?;
+    crate::ai::faces::validate_face_folder(&path)
+}
+
+// 重新加载人脸库（新增/删除人员后无需重启），返回 人名 -> 参与均值的样本数
+#[tauri::command]
+pub async fn reload_faces(app_handle: tauri::AppHandle) -> Result<std::collections::HashMap<String, i32>, String> {
+    if !ai::faces::is_face_model_ready() {
+        return Err("face model is not ready".to_string());
+    }
+    let stats = ai::faces::preload_targets_from_faces_dir(&app_handle)?;
+    // 旧目标库下进行中的检测结果作废
+    monitoring::reset_pipeline();
+    app_emitter::emit_toast(&format!("人脸库已重新加载：{} 人", stats.len()));
+    Ok(stats)
+}
+
+#[tauri::command]
+pub fn get_error_history() -> Vec<app_emitter::ErrorEvent> {
+    app_emitter::get_error_history()
+}
+
+#[tauri::command]
+pub fn clear_error_history() {
+    app_emitter::clear_error_history();
 }
\ No newline at end of file
@@ -1,13 +1,50 @@
 use tauri::Emitter;
 use serde::Serialize;
+use std::collections::VecDeque;
 use std::sync::{OnceLock, Mutex, Condvar};
-use crate::{app::AppState, monitor::Image, utils::rect::Rect};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use super::preview::AdaptivePreview;
+use crate::{app::AppState, monitor::{Image, MonitorInfo}, utils::rect::Rect};
+use crate::utils::sync::lock_or_recover;
 
 struct ImageEmitQueue {
-	buf: Mutex<Option<Image>>, // 仅保留最新一帧
+	buf: Mutex<Option<(Image, bool)>>, // 仅保留最新一帧；bool 表示是否为自适应码率的调试预览帧
 	cv: Condvar,
 }
 
+// 调试预览帧：按自适应质量编码为 JPEG（base64）发送
+#[derive(Serialize)]
+struct PreviewPayload {
+	width: i32,
+	height: i32,
+	jpeg: String,
+}
+
+const DEFAULT_PREVIEW_BUDGET_KBPS: u32 = 2000;
+
+// 预览带宽预算（字节/秒），0 表示不限
+fn preview_budget() -> Option<f64> {
+	let kbps = crate::config::get_config()
+		.and_then(|c| c.monitoring)
+		.and_then(|m| m.preview_budget_kbps)
+		.unwrap_or(DEFAULT_PREVIEW_BUDGET_KBPS);
+	(kbps > 0).then(|| kbps as f64 * 1000.0 / 8.0)
+}
+
+fn emit_preview(handle: &tauri::AppHandle, img: &Image, adaptive: &mut AdaptivePreview) {
+	let budget = preview_budget();
+	let Some(quality) = adaptive.plan(img, budget, Instant::now()) else { return };
+	let jpeg = match img.to_jpeg(quality) {
+		Ok(jpeg) => crate::utils::base64::encode(&jpeg),
+		Err(e) => {
+			log::warn!("[emit_preview] encode failed: {}", e);
+			return;
+		}
+	};
+	adaptive.record(jpeg.len(), (img.width.max(0) * img.height.max(0)) as usize, budget, Instant::now());
+	let _ = handle.emit("image", PreviewPayload { width: img.width, height: img.height, jpeg });
+}
+
 static IMAGE_QUEUE: OnceLock<ImageEmitQueue> = OnceLock::new();
 static IMAGE_EMIT_THREAD: OnceLock<()> = OnceLock::new();
 
@@ -22,20 +59,22 @@ fn spawn_image_emit_thread_once() {
 	IMAGE_EMIT_THREAD.get_or_init(|| {
 		std::thread::spawn(|| {
 			let q = image_queue();
+			let mut adaptive = AdaptivePreview::default();
 			loop {
 				// 等待有最新一帧
-				let mut guard = q.buf.lock().unwrap();
+				let mut guard = lock_or_recover(&q.buf, "image_queue");
 				while guard.is_none() {
-					guard = q.cv.wait(guard).unwrap();
+					guard = q.cv.wait(guard).unwrap_or_else(|e| e.into_inner());
 				}
-				let img = guard.take().unwrap();
+				let (img, preview) = guard.take().unwrap();
 				drop(guard);
 
-				// 串行发送，确保不并行 emit；默认关闭，仅在 DEBUG_IMAGE_STREAM=1 时开启
-				let enable = std::env::var("DEBUG_IMAGE_STREAM").ok().as_deref() == Some("1");
-				if enable {
-					if let Ok(app) = AppState::get_global() {
-						let handle = app.handle;
+				// 串行发送，确保不并行 emit；是否入队由 emit_image / emit_processed_image 决定
+				if let Ok(app) = AppState::get_global() {
+					let handle = app.handle;
+					if preview {
+						emit_preview(&handle, &img, &mut adaptive);
+					} else {
 						let _ = handle.emit("image", img);
 					}
 				}
@@ -48,13 +87,20 @@ pub fn emit_image(image: &Image) {
 	// 仅在显式开启 DEBUG_IMAGE_STREAM=1 时才启用图像事件流
 	let enable = std::env::var("DEBUG_IMAGE_STREAM").ok().as_deref() == Some("1");
 	if !enable { return; }
-	// 后台串行线程发送：仅覆盖为最新帧
+	queue_image(image.clone(), true);
+}
+
+// 已在服务端烘焙遮罩的帧（bake_mosaic），不受 DEBUG_IMAGE_STREAM 限制，同样以 image 事件发送
+pub fn emit_processed_image(image: Image) {
+	queue_image(image, false);
+}
+
+// 后台串行线程发送：仅覆盖为最新帧
+fn queue_image(image: Image, preview: bool) {
 	spawn_image_emit_thread_once();
 	let q = image_queue();
-	if let Ok(mut guard) = q.buf.lock() {
-		*guard = Some(image.clone());
-		q.cv.notify_one();
-	}
+	*lock_or_recover(&q.buf, "image_queue") = Some((image, preview));
+	q.cv.notify_one();
 }
 
 pub fn emit_toast(message: &str) {
@@ -67,6 +113,27 @@ pub fn emit_toast_close() {
     emit_toast("close");
 }
 
+// 配置经 update_config 写回后通知所有窗口重新读取
+pub fn emit_config_changed() {
+    if let Ok(app) = AppState::get_global() {
+        let _ = app.handle.emit("config-changed", ());
+    }
+}
+
+// 后端主动开始监控（如按配置的默认显示器）时通知前端同步选中状态
+pub fn emit_working_monitor(monitor: &MonitorInfo) {
+    if let Ok(app) = AppState::get_global() {
+        let _ = app.handle.emit("working-monitor", monitor.clone());
+    }
+}
+
+// 分辨率变化或显示器插拔后发送重新枚举的显示器列表，前端据此刷新显示器选择
+pub fn emit_monitors_changed(monitors: &[MonitorInfo]) {
+    if let Ok(app) = AppState::get_global() {
+        let _ = app.handle.emit("monitors-changed", monitors.to_vec());
+    }
+}
+
 pub fn emit_frame_info(frame_info: Vec<Rect>) {
     let app = AppState::get_global().unwrap();
     let handle = app.handle;
@@ -80,10 +147,98 @@ pub struct FaceAngleEventItem {
     pub width: i32,
     pub height: i32,
     pub angle: f32,
+    // 识别相似度，无识别（普通检测）时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
 }
 
 pub fn emit_frame_info_with_angle(items: Vec<FaceAngleEventItem>) {
     let app = AppState::get_global().unwrap();
     let handle = app.handle;
     let _ = handle.emit("frame_info_angle", items);
+}
+
+// 每个检测周期结束时的摘要，供外部自动化订阅，无需轮询 get_latest_mosaic
+#[derive(Debug, Clone, Serialize)]
+pub struct DetectionCycleEvent {
+    pub monitor_id: usize,
+    pub timestamp: i64,
+    pub capture_ms: u64,
+    pub detection_ms: u64,
+    pub method: String,
+    pub face_count: usize,
+    // 命中的目标人名；redact_person_names 开启（默认）或多尺度检测时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_persons: Option<Vec<String>>,
+}
+
+pub fn emit_detection_cycle(event: DetectionCycleEvent) {
+    if let Ok(app) = AppState::get_global() {
+        let _ = app.handle.emit("detection-cycle", event);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkProgress {
+    pub done: usize,
+    pub total: usize,
+    pub scale: f32,
+}
+
+// 检测基准测试进度：每完成一个尺度发送一次
+pub fn emit_benchmark_progress(progress: BenchmarkProgress) {
+    if let Ok(app) = AppState::get_global() {
+        let _ = app.handle.emit("benchmark-progress", progress);
+    }
+}
+
+// 隐私保护已确认生效（overlay 可见、置顶、排除截图，且模型就绪）
+pub fn emit_protected(status: &crate::overlay::OverlayStatus) {
+    if let Ok(app) = AppState::get_global() {
+        let _ = app.handle.emit("protected", status);
+    }
+}
+
+// 错误事件：独立于 toast 的结构化错误通道，并保留有限条历史供前端查询
+const ERROR_HISTORY_CAPACITY: usize = 200;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorEvent {
+    pub code: String,
+    pub message: String,
+    pub context: Option<String>,
+    pub timestamp: i64,
+}
+
+static ERROR_HISTORY: OnceLock<Mutex<VecDeque<ErrorEvent>>> = OnceLock::new();
+
+fn error_history() -> &'static Mutex<VecDeque<ErrorEvent>> {
+    ERROR_HISTORY.get_or_init(|| Mutex::new(VecDeque::with_capacity(ERROR_HISTORY_CAPACITY)))
+}
+
+pub fn emit_error(code: &str, message: &str, context: Option<&str>) {
+    let event = ErrorEvent {
+        code: code.to_string(),
+        message: message.to_string(),
+        context: context.map(|c| c.to_string()),
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0),
+    };
+    {
+        let mut history = lock_or_recover(error_history(), "error_history");
+        if history.len() >= ERROR_HISTORY_CAPACITY {
+            history.pop_front();
+        }
+        history.push_back(event.clone());
+    }
+    if let Ok(app) = AppState::get_global() {
+        let _ = app.handle.emit("error", event);
+    }
+}
+
+pub fn get_error_history() -> Vec<ErrorEvent> {
+    lock_or_recover(error_history(), "error_history").iter().cloned().collect()
+}
+
+pub fn clear_error_history() {
+    lock_or_recover(error_history(), "error_history").clear();
 }
\ No newline at end of file
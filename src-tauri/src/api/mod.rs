@@ -1,2 +1,3 @@
 pub mod command;
-pub mod emitter;
\ No newline at end of file
+pub mod emitter;
+pub mod preview;
\ No newline at end of file
@@ -0,0 +1,171 @@
+use std::time::{Duration, Instant};
+
+use crate::monitor::Image;
+
+// 调试预览流（DEBUG_IMAGE_STREAM）的自适应码率：按与上一次发送帧的差异决定帧率与 JPEG 质量，
+// 静止画面低频发送高质量帧、运动画面高频发送低质量帧，并使平均发送量不超过 monitoring.preview_budget_kbps
+
+// 完全静止 / 剧烈运动时的目标帧率
+const STATIC_FPS: f64 = 1.0;
+const MOTION_FPS: f64 = 15.0;
+// JPEG 质量档位，从高到低
+const QUALITIES: [u8; 5] = [90, 75, 60, 45, 30];
+// 尚无编码统计时各档位假设的每像素字节数（JPEG + base64）
+const INITIAL_BYTES_PER_PX: [f64; 5] = [0.6, 0.35, 0.25, 0.18, 0.13];
+// 帧差采样网格步长（像素），以及视为剧烈运动的平均亮度差（0~255）
+const SAMPLE_STEP: usize = 16;
+const FULL_MOTION_DIFF: f64 = 24.0;
+
+pub struct AdaptivePreview {
+    // 上一次发送帧的采样亮度：(宽, 高, 采样值)
+    last_sample: Option<(i32, i32, Vec<u8>)>,
+    last_sent: Option<Instant>,
+    // 按预算折算的下一次最早发送时间
+    next_allowed: Option<Instant>,
+    // 各质量档位的每像素字节数估计，按实际编码结果滑动更新
+    bytes_per_px: [f64; 5],
+    // plan 选定的档位，record 据此更新对应的估计
+    level: usize,
+}
+
+impl Default for AdaptivePreview {
+    fn default() -> Self {
+        Self { last_sample: None, last_sent: None, next_allowed: None, bytes_per_px: INITIAL_BYTES_PER_PX, level: 0 }
+    }
+}
+
+impl AdaptivePreview {
+    // 决定本帧是否发送及 JPEG 质量；None 表示跳过本帧。budget 为每秒字节数，None 表示不限
+    pub fn plan(&mut self, image: &Image, budget: Option<f64>, now: Instant) -> Option<u8> {
+        let sample = luma_samples(image);
+        // 首帧或分辨率变化：当作静止画面，不受帧率间隔限制，立即发送一帧高质量图
+        let (motion, continued) = match &self.last_sample {
+            Some((w, h, prev)) if *w == image.width && *h == image.height => (motion_between(prev, &sample), true),
+            _ => (0.0, false),
+        };
+        let fps = STATIC_FPS + (MOTION_FPS - STATIC_FPS) * motion;
+        if let Some(last) = self.last_sent.filter(|_| continued) {
+            if now.duration_since(last) < Duration::from_secs_f64(1.0 / fps) {
+                return None;
+            }
+        }
+        if self.next_allowed.is_some_and(|t| now < t) {
+            return None;
+        }
+        // 运动越剧烈质量越低；超出每帧预算时继续降档，最低档仍超出时由 record 推迟下一帧（即降低帧率）
+        let mut level = (motion * (QUALITIES.len() - 1) as f64).round() as usize;
+        if let Some(budget) = budget {
+            let per_frame = budget / fps;
+            let pixels = image.width.max(0) as f64 * image.height.max(0) as f64;
+            while level + 1 < QUALITIES.len() && pixels * self.bytes_per_px[level] > per_frame {
+                level += 1;
+            }
+        }
+        self.last_sample = Some((image.width, image.height, sample));
+        self.level = level;
+        Some(QUALITIES[level])
+    }
+
+    // 记录实际发送的字节数与像素数：更新本档位的每像素字节估计，并按预算推迟下一次发送
+    pub fn record(&mut self, sent_bytes: usize, pixels: usize, budget: Option<f64>, now: Instant) {
+        if pixels > 0 {
+            let est = &mut self.bytes_per_px[self.level];
+            *est = *est * 0.7 + (sent_bytes as f64 / pixels as f64) * 0.3;
+        }
+        self.last_sent = Some(now);
+        self.next_allowed = budget.filter(|b| *b > 0.0).map(|b| now + Duration::from_secs_f64(sent_bytes as f64 / b));
+    }
+}
+
+// 稀疏网格上的亮度采样（BGRA），用于低成本估计帧差
+fn luma_samples(image: &Image) -> Vec<u8> {
+    let w = image.width.max(0) as usize;
+    let h = image.height.max(0) as usize;
+    let mut out = Vec::with_capacity((w / SAMPLE_STEP + 1) * (h / SAMPLE_STEP + 1));
+    for y in (0..h).step_by(SAMPLE_STEP) {
+        for x in (0..w).step_by(SAMPLE_STEP) {
+            let i = (y * w + x) * 4;
+            if let Some(px) = image.data.get(i..i + 3) {
+                out.push(((px[0] as u32 * 29 + px[1] as u32 * 150 + px[2] as u32 * 77) >> 8) as u8);
+            }
+        }
+    }
+    out
+}
+
+// 采样亮度的平均绝对差，归一化到 0~1
+fn motion_between(prev: &[u8], cur: &[u8]) -> f64 {
+    let n = prev.len().min(cur.len());
+    if n == 0 {
+        return 0.0;
+    }
+    let sum: u64 = prev.iter().zip(cur).map(|(a, b)| a.abs_diff(*b) as u64).sum();
+    (sum as f64 / n as f64 / FULL_MOTION_DIFF).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(width: i32, height: i32, value: u8) -> Image {
+        Image { width, height, data: vec![value; (width * height * 4) as usize] }
+    }
+
+    #[test]
+    fn static_frames_are_sent_rarely_at_high_quality() {
+        let mut p = AdaptivePreview::default();
+        let t0 = Instant::now();
+        let img = frame(320, 240, 100);
+        assert_eq!(p.plan(&img, None, t0), Some(90));
+        p.record(1000, 320 * 240, None, t0);
+        assert_eq!(p.plan(&img, None, t0 + Duration::from_millis(500)), None);
+        assert_eq!(p.plan(&img, None, t0 + Duration::from_millis(1000)), Some(90));
+    }
+
+    #[test]
+    fn motion_is_sent_often_at_low_quality() {
+        let mut p = AdaptivePreview::default();
+        let t0 = Instant::now();
+        assert_eq!(p.plan(&frame(320, 240, 0), None, t0), Some(90));
+        p.record(1000, 320 * 240, None, t0);
+        let t1 = t0 + Duration::from_millis(70);
+        assert_eq!(p.plan(&frame(320, 240, 255), None, t1), Some(30));
+        p.record(1000, 320 * 240, None, t1);
+        // 中等程度的变化落在中间档位
+        let t2 = t1 + Duration::from_millis(300);
+        assert_eq!(p.plan(&frame(320, 240, 249), None, t2), Some(75));
+    }
+
+    #[test]
+    fn budget_lowers_quality_and_delays_next_frame() {
+        let mut p = AdaptivePreview::default();
+        let t0 = Instant::now();
+        let img = frame(1000, 1000, 50);
+        // 静止帧每帧可用 200 KB：90 档估计 600 KB、75 档 350 KB、60 档 250 KB 均超出，落到 45 档（180 KB）
+        assert_eq!(p.plan(&img, Some(200_000.0), t0), Some(45));
+        p.record(300_000, 1000 * 1000, Some(200_000.0), t0);
+        // 发送 300 KB 后须等待 1.5 s，即使静止帧 1 s 的帧率间隔已到
+        assert_eq!(p.plan(&img, Some(200_000.0), t0 + Duration::from_millis(1200)), None);
+        // 45 档的估计已被实际结果抬高，改用最低档
+        assert_eq!(p.plan(&img, Some(200_000.0), t0 + Duration::from_millis(1500)), Some(30));
+    }
+
+    #[test]
+    fn resolution_change_sends_immediately() {
+        let mut p = AdaptivePreview::default();
+        let t0 = Instant::now();
+        assert!(p.plan(&frame(320, 240, 10), None, t0).is_some());
+        p.record(1000, 320 * 240, None, t0);
+        assert_eq!(p.plan(&frame(640, 480, 10), None, t0 + Duration::from_millis(10)), Some(90));
+    }
+
+    #[test]
+    fn lower_quality_encodes_smaller() {
+        let data = (0..64 * 64 * 4).map(|i| (i * 37 % 251) as u8).collect();
+        let img = Image { width: 64, height: 64, data };
+        let high = img.to_jpeg(QUALITIES[0]).unwrap();
+        let low = img.to_jpeg(QUALITIES[QUALITIES.len() - 1]).unwrap();
+        assert_eq!(&high[..2], &[0xFF, 0xD8]);
+        assert!(low.len() < high.len());
+    }
+}
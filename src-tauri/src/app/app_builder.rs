@@ -3,33 +3,91 @@ use tauri_plugin_autostart::MacosLauncher;
 
 use crate::api::command;
 
+fn close_to_tray() -> bool {
+    crate::config::get_config()
+        .and_then(|c| c.system)
+        .is_some_and(|s| s.close_to_tray())
+}
+
 pub fn create_app_builder() -> tauri::Builder<tauri::Wry> {
     tauri::Builder::default()
         .plugin(tauri_plugin_autostart::init(
             MacosLauncher::LaunchAgent,
-            None,
+            Some(vec![super::protection::AUTOSTART_ARG]),
         ))
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_positioner::init())
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            let _ = app
-                .get_webview_window("main")
-                .expect("no main window")
-                .set_focus();
+            let window = app.get_webview_window("main").expect("no main window");
+            // 主窗口可能已隐藏到托盘
+            let _ = window.show();
+            let _ = window.set_focus();
         }))
         .invoke_handler(tauri::generate_handler![
             command::get_monitors,
             command::set_working_monitor,
+            command::set_working_monitor_by_name,
+            command::prewarm_capture,
             command::stop_monitoring,
             command::get_mosaic_style,
+            command::get_cover_style,
+            command::set_cover_style,
             command::get_latest_mosaic,
+            command::cover_all_for,
+            command::pause_monitoring,
+            command::resume_monitoring,
+            command::lock_mosaics,
+            command::inject_test_box,
+            command::assert_coverage,
+            command::clear_mosaics,
+            command::get_mosaic_emit_stats,
+            command::get_perf_stats,
+            command::get_capture_capabilities,
+            command::get_detection_histogram,
+            command::get_config_status,
+            command::update_config,
+            command::get_capture_stats,
+            command::reset_capture_stats,
+            command::export_perf_report,
+            command::start_recording,
+            command::stop_recording,
+            command::get_last_frame_png,
+            command::capture_still,
+            command::set_provider,
+            command::get_active_provider,
+            command::get_error_history,
+            command::clear_error_history,
+            command::validate_face_folder,
+            command::benchmark_detection,
+            command::overlay_status,
+            command::set_recognition_threshold,
+            command::get_recognition_threshold,
+            command::get_effective_config,
+            command::set_follow_active_window,
+            command::get_follow_active_window,
+            command::set_exclude_taskbar,
+            command::get_exclude_taskbar,
+            command::set_pause_when_hidden,
+            command::get_pause_when_hidden,
+            command::set_coverage_mode,
+            command::get_coverage_mode,
+            command::set_recognition_enabled,
+            command::get_recognition_enabled,
+            command::release_idle_resources,
+            command::reload_faces,
         ])
         .on_window_event(|window, event| {
-            if let WindowEvent::CloseRequested { .. } = event {
+            if let WindowEvent::CloseRequested { api, .. } = event {
                 // 仅当主窗口关闭时退出整个应用；其他窗口（如 overlay）允许正常关闭
                 if window.label() == "main" {
+                    // close_to_tray：隐藏主窗口，监控与托盘保持运行
+                    if close_to_tray() {
+                        api.prevent_close();
+                        let _ = window.hide();
+                        return;
+                    }
                     let _ = std::panic::catch_unwind(|| {
-                        crate::system::monitoring::stop_monitoring();
+                        crate::system::monitoring::stop_monitoring_immediately();
                     });
                     let _ = window.app_handle().exit(0);
                 }
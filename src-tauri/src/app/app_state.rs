@@ -2,6 +2,7 @@ use std::sync::Mutex;
 
 use once_cell::sync::Lazy;
 use tauri::{AppHandle, WebviewWindow};
+use crate::utils::sync::lock_or_recover;
 
 
 static APP: Lazy<Mutex<Option<AppState>>> = Lazy::new(|| Mutex::new(None));
@@ -15,14 +16,14 @@ pub struct AppState {
 impl AppState {
     /// 设置全局App实例
     pub fn set_global(app: AppState) -> Result<(), Box<dyn std::error::Error>> {
-        let mut app_guard = APP.lock().map_err(|e| format!("Failed to lock app mutex: {}", e))?;
+        let mut app_guard = lock_or_recover(&APP, "app_state");
         *app_guard = Some(app);
         Ok(())
     }
 
     /// 获取全局App实例
     pub fn get_global() -> Result<AppState, Box<dyn std::error::Error>> {
-        let app_guard = APP.lock().map_err(|e| format!("Failed to lock app mutex: {}", e))?;
+        let app_guard = lock_or_recover(&APP, "app_state");
         app_guard.clone().ok_or_else(|| "App not initialized".into())
     }
 
@@ -38,7 +39,7 @@ impl AppState {
 
     /// 检查App是否已初始化
     pub fn is_initialized() -> bool {
-        APP.lock().map(|guard| guard.is_some()).unwrap_or(false)
+        lock_or_recover(&APP, "app_state").is_some()
     }
 }
 
@@ -1,12 +1,14 @@
-use log::{error, info};
+use log::{error, info, warn};
 use tauri::Manager;
 use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_APARTMENTTHREADED};
+use windows::Win32::UI::HiDpi::{SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2};
 
 mod tray;
 mod autostart;
 mod panic_handler;
 mod app_builder;
 mod app_state;
+mod protection;
 pub use app_state::AppState;
 
 use crate::utils::logger;
@@ -15,6 +17,14 @@ use crate::api::emitter;
 
 const LOG_LEVEL: &str = "debug";
 
+fn init_dpi_awareness() {
+    match unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) } {
+        Ok(()) => info!("DPI awareness set (per-monitor v2)"),
+        // 清单或宿主已设置过时返回 ERROR_ACCESS_DENIED，沿用已有设置即可
+        Err(e) => info!("DPI awareness not changed (already set by manifest?): {}", e.message()),
+    }
+}
+
 pub fn run() {
     // 自动切换到 exe 所在目录, 为了解决windows自动启动时workding directory读取不到配置文件的问题
     if !cfg!(debug_assertions) {
@@ -46,6 +56,10 @@ pub fn run() {
         }
     }
 
+    // DPI 感知每个进程只能设置一次，须在创建任何窗口（含 tauri 事件循环）之前完成；
+    // 截图按物理像素取显示器坐标，依赖于此
+    init_dpi_awareness();
+
     // Initialize app
     let mut builder = app_builder::create_app_builder();
     // Setup application
@@ -77,6 +91,25 @@ pub fn run() {
         autostart::set_auto_start(&app_handle).expect("Failed to setup auto start");
         info!("[✓] auto start setup");
 
+        // 配置文件热加载：保存 config.toml 后无需重启，进行中的检测按新配置重新开始
+        config::watch(|| {
+            crate::system::monitoring::reset_pipeline();
+            crate::system::monitoring::sync_extra_monitors();
+            emitter::emit_toast("配置已重新加载");
+        });
+
+        // 后台探测本机截图能力并缓存（创建设备与桌面复制需要一些时间，不阻塞启动）
+        std::thread::spawn(|| {
+            crate::monitor::capabilities::get();
+        });
+
+        // 开机自启且开启保护时，先于 Python 初始化拉起截图与 overlay
+        let protect_on_autostart = protection::is_enabled();
+        if protect_on_autostart {
+            info!("[protection] autostart launch, starting protection early");
+            protection::start_early();
+        }
+
 		// Initialize Python environment (run in background to avoid blocking UI)
 		let app_handle_clone = app_handle.clone();
 		tauri::async_runtime::spawn_blocking(move || {
@@ -84,24 +117,65 @@ pub fn run() {
 				Ok(()) => info!("[✓] Python environment initialized"),
 				Err(e) => {
 					error!("[✗] Failed to initialize Python environment: {}", e);
+					emitter::emit_error("python_init_failed", &e, Some("initialize_python_environment"));
 					return;
 				}
 			}
 
 			// 初始化识别模型并预加载 faces/ 目录的人脸目标向量
 			emitter::emit_toast("正在初始化人脸识别模型…");
-			match crate::ai::faces::initialize_face_recognition() {
-				Ok(()) => info!("[✓] face recognition model initialized"),
-				Err(e) => error!("[✗] face recognition model init failed: {}", e),
-			}
-			emitter::emit_toast("正在预加载人脸库与特征…");
-			match crate::ai::faces::preload_targets_from_faces_dir(&app_handle_clone) {
-				Ok(()) => info!("[✓] preloaded target face embeddings from faces/"),
-				Err(e) => error!("[✗] preload target embeddings failed: {}", e),
+			let model_ok = match crate::ai::faces::initialize_face_recognition() {
+				Ok(()) => {
+					info!("[✓] face recognition model initialized");
+					true
+				}
+				Err(e) => {
+					error!("[✗] face recognition model init failed: {}", e);
+					emitter::emit_error("model_init_failed", &e, Some("initialize_face_recognition"));
+					false
+				}
+			};
+			// 模型不可用时跳过预加载（超时的初始化可能仍占用 GIL）
+			if model_ok {
+				// 配置了集中人脸库时先同步到本地 faces 目录；离线或失败时沿用本地目录
+				if let Err(e) = crate::ai::faces_sync::sync_once() {
+					warn!("[faces_sync] sync failed, using local faces: {}", e);
+				}
+				crate::ai::faces_sync::start_periodic(app_handle_clone.clone());
+				emitter::emit_toast("正在预加载人脸库与特征…");
+				match crate::ai::faces::preload_targets_from_faces_dir(&app_handle_clone) {
+					Ok(stats) => info!("[✓] preloaded target face embeddings for {} people", stats.len()),
+					Err(e) => {
+						error!("[✗] preload target embeddings failed: {}", e);
+						emitter::emit_error("preload_targets_failed", &e, Some("preload_targets_from_faces_dir"));
+					}
+				}
 			}
 			// 至此后端完全就绪，再发完成事件与关闭 toast，确保前端可操作
-			emitter::emit_toast("全部初始化完成，可开始使用");
+			if model_ok {
+				emitter::emit_toast("全部初始化完成，可开始使用");
+			} else {
+				emitter::emit_toast("初始化完成：人脸模型不可用，开始监控将以静态遮罩模式运行");
+			}
 			emitter::emit_toast_close();
+			crate::system::monitoring::ensure_idle_watcher();
+
+			if protect_on_autostart {
+				protection::verify_and_report();
+				return;
+			}
+
+			// 若配置了默认显示器，按设备名自动开始监控
+			let default_name = config::get_config()
+				.and_then(|c| c.monitoring)
+				.and_then(|m| m.default_monitor_name)
+				.filter(|n| !n.trim().is_empty());
+			if let Some(name) = default_name.filter(|_| crate::ai::faces::is_face_model_ready()) {
+				match tauri::async_runtime::block_on(crate::system::monitoring::set_working_monitor_by_name(&name)) {
+					Ok(m) => info!("[✓] auto-started monitoring on {:?} (monitor {})", m.name, m.id),
+					Err(e) => error!("[✗] auto-start monitoring on {} failed: {}", name, e),
+				}
+			}
 		});
 
         info!("=== application initialized ===");
@@ -122,7 +196,7 @@ pub fn run() {
                 info!("[✓] COM uninitialized");
             }
             // 确保监控线程退出
-            crate::system::monitoring::stop_monitoring();
+            crate::system::monitoring::stop_monitoring_immediately();
         }
     });
 }
@@ -0,0 +1,80 @@
+use log::{error, info};
+
+use crate::api::emitter;
+use crate::config;
+use crate::overlay;
+
+// 开机自启时的隐私保护启动契约：
+// 1. 在 Python/模型初始化之前即拉起截图与 overlay（模型就绪前 cal() 会跳过检测）；
+// 2. 初始化结束后确认 overlay 可见、置顶且已排除截图，且人脸模型就绪，才发出 protected 事件；
+// 3. 任一保证无法满足时发出错误事件并保留错误提示，不静默降级。
+
+pub const AUTOSTART_ARG: &str = "--autostart";
+
+const VERIFY_ATTEMPTS: u32 = 20;
+const VERIFY_INTERVAL_MS: u64 = 250;
+
+pub fn is_autostart_launch() -> bool {
+    std::env::args().any(|a| a == AUTOSTART_ARG)
+}
+
+pub fn is_enabled() -> bool {
+    is_autostart_launch()
+        && config::get_config()
+            .and_then(|c| c.system)
+            .and_then(|s| s.protect_on_autostart)
+            .unwrap_or(false)
+}
+
+// 尽早开始监控：使用 default_monitor_name，未配置时回退到主显示器
+pub fn start_early() {
+    let name = config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.default_monitor_name)
+        .unwrap_or_default();
+    tauri::async_runtime::spawn(async move {
+        match crate::system::monitoring::set_working_monitor_by_name(&name).await {
+            Ok(m) => info!("[protection] early monitoring started on monitor {}", m.id),
+            Err(e) => {
+                error!("[protection] early monitoring failed: {}", e);
+                emitter::emit_error("protection_start_failed", &e, Some("start_early"));
+            }
+        }
+    });
+}
+
+// 在初始化流程末尾调用（阻塞线程内）：轮询 overlay 真实窗口状态，确认保证后报告
+pub fn verify_and_report() {
+    let mut status = overlay::overlay_status();
+    for _ in 0..VERIFY_ATTEMPTS {
+        if status.exists && status.visible && status.topmost && status.excluded_from_capture {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(VERIFY_INTERVAL_MS));
+        status = overlay::overlay_status();
+    }
+    let model_ready = crate::ai::faces::is_face_model_ready();
+    let mut missing: Vec<&str> = Vec::new();
+    if !status.exists || !status.visible {
+        missing.push("overlay not visible");
+    }
+    if !status.topmost {
+        missing.push("overlay not topmost");
+    }
+    if !status.excluded_from_capture {
+        missing.push("overlay not excluded from capture");
+    }
+    if !model_ready {
+        missing.push("face model not ready");
+    }
+    if missing.is_empty() {
+        info!("[protection] protection verified: {:?}", status);
+        emitter::emit_protected(&status);
+    } else {
+        let message = missing.join(", ");
+        error!("[protection] protection guarantees not met: {}", message);
+        emitter::emit_error("protection_unverified", &message, Some("verify_and_report"));
+        // 不再发送 close，提示保持显示直到用户处理
+        emitter::emit_toast(&format!("隐私保护未生效：{}", message));
+    }
+}
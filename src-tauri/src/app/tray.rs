@@ -6,7 +6,11 @@ const SHOW_TRAY_ICON: bool = false;
 pub fn setup_tray(
     app_handle: &AppHandle,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    if !SHOW_TRAY_ICON {
+    // close_to_tray 时主窗口关闭后只能从托盘恢复或退出，必须显示托盘图标
+    let close_to_tray = crate::config::get_config()
+        .and_then(|c| c.system)
+        .is_some_and(|s| s.close_to_tray());
+    if !SHOW_TRAY_ICON && !close_to_tray {
         info!("[setup_tray] tray icon is not enabled");
         return Ok(());
     }
@@ -29,6 +33,7 @@ pub fn setup_tray(
             let app_handle = tray_handle.app_handle();
             match event.id.as_ref() {
                 "exit" => {
+                    crate::system::monitoring::stop_monitoring_immediately();
                     app_handle.exit(0);
                 }
                 "settings" => {
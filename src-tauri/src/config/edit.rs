@@ -0,0 +1,354 @@
+// 配置文件的按行编辑：只改写指定的键或节，其余行（注释、空行、未涉及的键）原样保留
+
+// 一次改动；section 为节的路径，如 ["face", "recognition"]
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Edit {
+    // 替换同名键，不存在时插入到节标题之后，节不存在时追加到文件末尾
+    Set { section: Vec<String>, key: String, value: toml::Value },
+    Remove { section: Vec<String>, key: String },
+    // 删除该节及其全部子节
+    RemoveSection(Vec<String>),
+}
+
+// 根据 JSON 补丁生成改动：merged 为合并补丁后的完整配置（toml 表）。
+// 子项全部为表的层级（顶层、face、monitor_overrides）视为节的容器，其余表为节，节内补丁涉及的键整体改写（内嵌表写成行内表）
+pub(super) fn patch_edits(merged: &toml::Table, patch: &serde_json::Map<String, serde_json::Value>) -> Result<Vec<Edit>, String> {
+    let mut edits = Vec::new();
+    collect_sections(&mut Vec::new(), merged, patch, &mut edits)?;
+    Ok(edits)
+}
+
+fn is_container(table: &toml::Table) -> bool {
+    !table.is_empty() && table.values().all(|v| v.is_table())
+}
+
+fn collect_sections(
+    path: &mut Vec<String>,
+    merged: &toml::Table,
+    patch: &serde_json::Map<String, serde_json::Value>,
+    edits: &mut Vec<Edit>,
+) -> Result<(), String> {
+    for (name, value) in patch {
+        path.push(name.clone());
+        match (merged.get(name), value) {
+            (None, _) => edits.push(Edit::RemoveSection(path.clone())),
+            (Some(toml::Value::Table(table)), serde_json::Value::Object(patch)) if is_container(table) => {
+                collect_sections(path, table, patch, edits)?;
+            }
+            (Some(toml::Value::Table(table)), serde_json::Value::Object(patch)) => {
+                for key in patch.keys() {
+                    edits.push(match table.get(key) {
+                        Some(value) => Edit::Set { section: path.clone(), key: key.clone(), value: tidy_floats(value.clone()) },
+                        None => Edit::Remove { section: path.clone(), key: key.clone() },
+                    });
+                }
+            }
+            _ => return Err(format!("{} is not a config section", path.join("."))),
+        }
+        path.pop();
+    }
+    Ok(())
+}
+
+// f32 字段序列化为 f64 后会带出 0.6000000238418579 这样的尾数，写回前还原为 f32 的最短表示
+fn tidy_floats(value: toml::Value) -> toml::Value {
+    match value {
+        toml::Value::Float(f) if (f as f32) as f64 == f => {
+            toml::Value::Float((f as f32).to_string().parse().unwrap_or(f))
+        }
+        toml::Value::Array(items) => toml::Value::Array(items.into_iter().map(tidy_floats).collect()),
+        toml::Value::Table(table) => toml::Value::Table(table.into_iter().map(|(k, v)| (k, tidy_floats(v))).collect()),
+        other => other,
+    }
+}
+
+pub(super) fn apply_edits(content: &str, edits: &[Edit]) -> String {
+    edits.iter().fold(content.to_string(), |text, edit| apply_edit(&text, edit))
+}
+
+fn apply_edit(content: &str, edit: &Edit) -> String {
+    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+    match edit {
+        Edit::Set { section, key, value } => {
+            let new_line = format!("{} = {}", key_repr(key), value);
+            match find_section(&lines, section) {
+                Some((start, end)) => match find_key(&lines, start + 1, end, key) {
+                    Some((i, j)) => {
+                        lines.splice(i..j, [new_line]);
+                    }
+                    None => lines.insert(start + 1, new_line),
+                },
+                None => {
+                    if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                        lines.push(String::new());
+                    }
+                    lines.push(header_repr(section));
+                    lines.push(new_line);
+                }
+            }
+        }
+        Edit::Remove { section, key } => {
+            if let Some((start, end)) = find_section(&lines, section) {
+                if let Some((i, j)) = find_key(&lines, start + 1, end, key) {
+                    lines.drain(i..j);
+                }
+            }
+        }
+        Edit::RemoveSection(section) => {
+            loop {
+                let starts = statement_starts(&lines);
+                let found = (0..lines.len())
+                    .find(|&i| starts[i] && header_path(&lines[i]).is_some_and(|p| p.starts_with(section)));
+                let Some(start) = found else { break };
+                let end = section_end(&lines, &starts, start);
+                lines.drain(start..end);
+            }
+        }
+    }
+    let mut out = lines.join("\n");
+    if content.ends_with('\n') || content.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+// 键名：能写成裸键时直接写，否则写成带转义的基本字符串
+fn key_repr(key: &str) -> String {
+    let bare = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        format!("\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+fn header_repr(section: &[String]) -> String {
+    let keys: Vec<String> = section.iter().map(|k| key_repr(k)).collect();
+    format!("[{}]", keys.join("."))
+}
+
+// 解析节标题行得到节的路径；按解析结果比较，键的引号写法（"..." / '...' / 裸键）不影响匹配
+fn header_path(line: &str) -> Option<Vec<String>> {
+    let line = line.trim();
+    if !line.starts_with('[') || line.starts_with("[[") {
+        return None;
+    }
+    let mut table: toml::Table = toml::from_str(line).ok()?;
+    let mut path = Vec::new();
+    while table.len() == 1 {
+        let (key, value) = table.into_iter().next()?;
+        path.push(key);
+        let toml::Value::Table(inner) = value else { return None };
+        table = inner;
+    }
+    Some(path)
+}
+
+// 节标题所在行与节的结束位置（下一个节标题或文件末尾）
+fn find_section(lines: &[String], section: &[String]) -> Option<(usize, usize)> {
+    let starts = statement_starts(lines);
+    let start = (0..lines.len()).find(|&i| starts[i] && header_path(&lines[i]).as_deref() == Some(section))?;
+    Some((start, section_end(lines, &starts, start)))
+}
+
+fn section_end(lines: &[String], starts: &[bool], start: usize) -> usize {
+    (start + 1..lines.len())
+        .find(|&i| starts[i] && lines[i].trim_start().starts_with('['))
+        .unwrap_or(lines.len())
+}
+
+// 节内键所在的行范围，多行字符串/数组的续行一并计入
+fn find_key(lines: &[String], from: usize, to: usize, key: &str) -> Option<(usize, usize)> {
+    let starts = statement_starts(lines);
+    let repr = key_repr(key);
+    let i = (from..to).find(|&i| {
+        starts[i]
+            && lines[i]
+                .trim_start()
+                .strip_prefix(repr.as_str())
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+    })?;
+    let j = (i + 1..lines.len()).find(|&j| starts[j]).unwrap_or(lines.len());
+    Some((i, j))
+}
+
+// 逐行标记是否为一条语句的起始行（即不处于多行字符串或跨行数组之内）
+fn statement_starts(lines: &[String]) -> Vec<bool> {
+    let mut starts = Vec::with_capacity(lines.len());
+    let mut open_delim: Option<&str> = None;
+    let mut depth = 0i32;
+    for line in lines {
+        starts.push(open_delim.is_none() && depth <= 0);
+        let mut rest = line.as_str();
+        loop {
+            if let Some(delim) = open_delim {
+                match rest.find(delim) {
+                    Some(i) => {
+                        rest = &rest[i + delim.len()..];
+                        open_delim = None;
+                    }
+                    None => break,
+                }
+                continue;
+            }
+            let Some(i) = rest.find(['"', '\'', '#', '[', ']', '{', '}']) else { break };
+            rest = &rest[i..];
+            match rest.as_bytes()[0] {
+                b'#' => break,
+                b'"' if rest.starts_with("\"\"\"") => {
+                    open_delim = Some("\"\"\"");
+                    rest = &rest[3..];
+                }
+                b'\'' if rest.starts_with("'''") => {
+                    open_delim = Some("'''");
+                    rest = &rest[3..];
+                }
+                q @ (b'"' | b'\'') => rest = skip_string(&rest[1..], q),
+                b'[' | b'{' => {
+                    depth += 1;
+                    rest = &rest[1..];
+                }
+                _ => {
+                    depth -= 1;
+                    rest = &rest[1..];
+                }
+            }
+        }
+    }
+    starts
+}
+
+// 跳过单行字符串，返回闭合引号之后的部分；基本字符串（双引号）支持反斜杠转义
+fn skip_string(s: &str, quote: u8) -> &str {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if quote == b'"' => i += 2,
+            b if b == quote => return &s[i + 1..],
+            _ => i += 1,
+        }
+    }
+    ""
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = r#"[face.detection]
+scale_factor = 1.1
+
+[face.recognition]
+# 识别阈值
+threshold = 0.4
+
+[monitoring]
+# 检测间隔/ms
+interval = 8
+mosaic_style = """
+background-color: transparent;
+[data-x] { opacity: 1; }
+"""
+multi_scales = [
+    1.0,
+    0.5,
+]
+capture_scale = 0.8 # 行尾注释
+
+[monitor_overrides."\\\\.\\DISPLAY2"]
+capture_scale = 0.5
+"#;
+
+    fn section(path: &[&str]) -> Vec<String> {
+        path.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn set_replaces_key_and_keeps_comments() {
+        let out = apply_edits(
+            CONTENT,
+            &[Edit::Set { section: section(&["monitoring"]), key: "capture_scale".into(), value: toml::Value::Float(0.5) }],
+        );
+        assert!(out.contains("# 检测间隔/ms\ninterval = 8\n"));
+        assert!(out.contains("capture_scale = 0.5\n\n[monitor_overrides"));
+        assert!(!out.contains("0.8"));
+        assert_eq!(out.lines().count(), CONTENT.lines().count());
+    }
+
+    #[test]
+    fn set_replaces_multiline_values_whole() {
+        let edits = [
+            Edit::Set { section: section(&["monitoring"]), key: "mosaic_style".into(), value: toml::Value::String("opacity: 1;".into()) },
+            Edit::Set {
+                section: section(&["monitoring"]),
+                key: "multi_scales".into(),
+                value: toml::Value::Array(vec![toml::Value::Float(1.0)]),
+            },
+        ];
+        let out = apply_edits(CONTENT, &edits);
+        assert!(out.contains("mosaic_style = \"opacity: 1;\"\nmulti_scales = [1.0]\ncapture_scale = 0.8"));
+        assert!(!out.contains("[data-x]"));
+        let parsed: toml::Table = toml::from_str(&out).unwrap();
+        assert_eq!(parsed["monitoring"]["interval"].as_integer(), Some(8));
+    }
+
+    #[test]
+    fn set_inserts_missing_key_and_appends_missing_section() {
+        let edits = [
+            Edit::Set { section: section(&["face", "recognition"]), key: "provider".into(), value: toml::Value::String("cpu".into()) },
+            Edit::Set { section: section(&["recording"]), key: "fps".into(), value: toml::Value::Integer(30) },
+        ];
+        let out = apply_edits(CONTENT, &edits);
+        assert!(out.contains("[face.recognition]\nprovider = \"cpu\"\n# 识别阈值\nthreshold = 0.4"));
+        assert!(out.ends_with("capture_scale = 0.5\n\n[recording]\nfps = 30\n"));
+    }
+
+    #[test]
+    fn remove_key_and_nested_sections() {
+        let edits = [
+            Edit::Remove { section: section(&["monitoring"]), key: "multi_scales".into() },
+            Edit::RemoveSection(section(&["face"])),
+            Edit::RemoveSection(section(&["monitor_overrides", r"\\.\DISPLAY2"])),
+        ];
+        let out = apply_edits(CONTENT, &edits);
+        assert!(out.starts_with("[monitoring]\n"));
+        assert!(!out.contains("multi_scales") && !out.contains("0.5,"));
+        assert!(!out.contains("monitor_overrides"));
+        let parsed: toml::Table = toml::from_str(&out).unwrap();
+        assert_eq!(parsed["monitoring"]["capture_scale"].as_float(), Some(0.8));
+    }
+
+    #[test]
+    fn patch_edits_write_f32_values_without_widening_noise() {
+        let mut merged = toml::Table::new();
+        let mut monitoring = toml::Table::new();
+        monitoring.insert("capture_scale".into(), toml::Value::Float(0.6f32 as f64));
+        monitoring.insert("region".into(), toml::Value::try_from(serde_json::json!({ "x": 0.3f32 })).unwrap());
+        merged.insert("monitoring".into(), toml::Value::Table(monitoring));
+        let patch = serde_json::json!({ "monitoring": { "capture_scale": 0.6, "region": { "x": 0.3 } } });
+        let out = apply_edits("[monitoring]\n", &patch_edits(&merged, patch.as_object().unwrap()).unwrap());
+        assert!(out.contains("capture_scale = 0.6\n"), "{}", out);
+        assert!(out.contains("region = { x = 0.3 }\n"), "{}", out);
+    }
+
+    #[test]
+    fn patch_edits_follow_section_layout() {
+        let mut merged: toml::Table = toml::from_str(CONTENT).unwrap();
+        merged.get_mut("monitoring").and_then(|m| m.as_table_mut()).unwrap().remove("multi_scales");
+        let patch = serde_json::json!({
+            "face": { "recognition": { "threshold": 0.4 } },
+            "monitoring": { "multi_scales": null },
+            "recording": null,
+        });
+        let edits = patch_edits(&merged, patch.as_object().unwrap()).unwrap();
+        assert_eq!(
+            edits,
+            vec![
+                Edit::Set { section: section(&["face", "recognition"]), key: "threshold".into(), value: toml::Value::Float(0.4) },
+                Edit::Remove { section: section(&["monitoring"]), key: "multi_scales".into() },
+                Edit::RemoveSection(section(&["recording"])),
+            ]
+        );
+    }
+}
@@ -18,6 +18,14 @@ pub struct DetectionConfig {
     pub confidence_threshold: f32,
     pub use_gray: bool,
     pub image_scale: f32,
+    // 可选：多尺度检测（相对于检测图的缩放倍率，如 [1.0, 0.5]），两个及以上时逐一检测并经 NMS 融合。
+    // 延迟约为各尺度检测耗时之和，默认关闭
+    pub multi_scales: Option<Vec<f32>>,
+    // 多尺度融合时的 NMS IoU 阈值，默认 0.4
+    pub multi_scale_iou: Option<f32>,
+    // 可选：检测图长边上限（px），超过时进一步缩小（框自动映射回原分辨率）。与 capture_scale 的比例不同，
+    // 这是绝对上限，限制每次传给 Python 的图像大小，与显示器分辨率无关；缺省不限制
+    pub max_dim: Option<u32>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
@@ -26,4 +34,42 @@ pub struct RecognitionConfig {
     pub provider: Option<String>,
     pub outlier_threshold: Option<f32>,
     pub outlier_iter: Option<i32>,
+    // 可选：离线模型根目录（其下为 models/<model>），缺省使用 insightface 默认目录，缺失时联网下载
+    pub model_root: Option<String>,
+    // 可选：insightface 模型包（buffalo_l / buffalo_s 等），缺省 buffalo_l；更换后人脸库特征会重新计算
+    pub model: Option<String>,
+    // 可选：模型初始化（含下载）超时秒数，超时后以静态遮罩模式运行，默认 180
+    pub init_timeout_secs: Option<u64>,
+    // 可选：启动时的覆盖策略（auto / all / targets / unknown_present），运行时可通过命令切换，缺省 auto
+    pub coverage_mode: Option<String>,
+    // 可选：识别模式（target_only 仅遮罩命中的目标 / protect_others 遮罩除目标外的所有人，目标库为空时遮罩所有人），
+    // 为 coverage_mode 的简化写法，二者同时配置时以 coverage_mode 为准
+    pub mode: Option<String>,
+    // 可选：目标路径下遮罩所有相似度达到 threshold 的人脸（多人同框时全部遮罩），false 时只遮罩最相似的一个，默认 true
+    pub multi_target: Option<bool>,
+    // 可选：加密人脸库压缩包（zip，目录结构同 faces 文件夹），配置后替代明文 faces 文件夹
+    pub faces_archive: Option<String>,
+    // 可选：压缩包口令（明文保存在配置中），建议改用 faces_archive_password_env
+    pub faces_archive_password: Option<String>,
+    // 可选：存放压缩包口令的环境变量名，优先于 faces_archive_password
+    pub faces_archive_password_env: Option<String>,
+    // 可选：集中管理的人脸库来源（http(s) 压缩包地址或目录/网络共享），加载前同步到本地 faces 目录；配置 faces_archive 时不同步
+    pub faces_sync_source: Option<String>,
+    // 可选：启动后定期同步的间隔（秒，至少 60），0 或缺省只在启动时同步一次
+    pub faces_sync_interval_secs: Option<u64>,
+    // 可选：HTTP 同步的超时（秒），缺省 30
+    pub faces_sync_timeout_secs: Option<u64>,
+}
+
+pub const DEFAULT_RECOGNITION_MODEL: &str = "buffalo_l";
+
+impl RecognitionConfig {
+    pub fn model(&self) -> String {
+        self.model
+            .as_deref()
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .unwrap_or(DEFAULT_RECOGNITION_MODEL)
+            .to_string()
+    }
 }
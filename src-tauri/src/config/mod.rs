@@ -1,13 +1,21 @@
+mod edit;
 mod face;
 mod monitoring;
+mod python;
+mod recording;
 mod system;
+mod watch;
 
 pub use face::*;
 pub use monitoring::*;
+pub use python::*;
+pub use recording::*;
 pub use system::*;
+pub use watch::watch;
 
-use log::info;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use toml;
@@ -16,7 +24,11 @@ use toml;
 pub struct Config {
     pub face: Option<FaceConfig>,
     pub monitoring: Option<MonitoringConfig>,
+    pub python: Option<PythonConfig>,
+    pub recording: Option<RecordingConfig>,
     pub system: Option<SystemConfig>,
+    // 可选：按显示器设备名覆盖 [monitoring] 中的部分参数
+    pub monitor_overrides: Option<HashMap<String, MonitorOverride>>,
 }
 
 
@@ -30,33 +42,393 @@ pub fn get_config_path() -> Option<String> {
     None
 }
 
+// 当前配置的来源，供界面提示用户配置文件缺失或有误
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigStatus {
+    // true 表示来自配置文件，false 表示使用默认值
+    pub from_file: bool,
+    pub path: Option<String>,
+    // 读取或解析失败的原因
+    pub error: Option<String>,
+}
+
+static CONFIG_STATUS: Lazy<Mutex<ConfigStatus>> =
+    Lazy::new(|| Mutex::new(ConfigStatus { from_file: false, path: None, error: None }));
+
+pub fn get_config_status() -> ConfigStatus {
+    lock_or_recover(&CONFIG_STATUS, "config_status").clone()
+}
+
+fn set_status(from_file: bool, path: Option<String>, error: Option<String>) {
+    *lock_or_recover(&CONFIG_STATUS, "config_status") = ConfigStatus { from_file, path, error };
+}
+
+// 配置文件缺失、无法读取或解析失败时使用默认配置（各字段均为 Option/Default），不中断启动
 pub fn load_config() -> Config {
-    if let Some(path) = get_config_path() {
-        let config_str = fs::read_to_string(&path)
-            .expect(format!("[load_config] Failed to read config file: {}", path).as_str());
-        let config: Config = toml::from_str(&config_str)
-            .expect(format!("[load_config] Failed to parse config file: {}", path).as_str());
-        info!("[load_config] load config from{} : {:?}", path, config);
-        return config;
+    let Some(path) = get_config_path() else {
+        warn!("[load_config] config.toml not found, using defaults");
+        set_status(false, None, Some("config.toml not found".to_string()));
+        return Config::default();
+    };
+    match load_config_from(&path) {
+        Ok(config) => {
+            info!("[load_config] load config from{} : {:?}", path, config);
+            set_status(true, Some(path), None);
+            config
+        }
+        Err(e) => {
+            warn!("[load_config] {}, using defaults", e);
+            set_status(false, Some(path), Some(e));
+            Config::default()
+        }
     }
-    panic!("please check the config file: config.toml exists");
+}
+
+// 从指定路径读取并解析配置文件
+pub fn load_config_from(path: &str) -> Result<Config, String> {
+    let config_str = fs::read_to_string(path).map_err(|e| format!("Failed to read config file {}: {}", path, e))?;
+    toml::from_str(&config_str).map_err(|e| format!("Failed to parse config file {}: {}", path, e))
 }
 
 // 全局配置实例
 use once_cell::sync::Lazy;
 use std::sync::Mutex;
+use crate::utils::sync::lock_or_recover;
 
 pub static CONFIG: Lazy<Mutex<Option<Config>>> = Lazy::new(|| Mutex::new(None));
 
+// 当前工作显示器的设备名，get_config() 据此合并 [monitor_overrides] 中的覆盖项
+static ACTIVE_MONITOR: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
 // 初始化配置
 pub fn init_config() -> Config {
     let config = load_config();
-    let mut config_guard = CONFIG.lock().unwrap();
+    let mut config_guard = lock_or_recover(&CONFIG, "config");
     *config_guard = Some(config.clone());
     config
 }
 
-// 获取配置
+// 获取配置（已合并当前工作显示器的覆盖项）
 pub fn get_config() -> Option<Config> {
-    CONFIG.lock().unwrap().clone()
+    let config = lock_or_recover(&CONFIG, "config").clone()?;
+    let monitor = lock_or_recover(&ACTIVE_MONITOR, "active_monitor").clone();
+    Some(effective_config(config, monitor.as_deref(), None))
+}
+
+pub fn set_active_monitor(name: Option<String>) {
+    *lock_or_recover(&ACTIVE_MONITOR, "active_monitor") = name;
+}
+
+// 指定显示器实际生效的配置：合并该显示器的覆盖项与运行时调整的识别阈值
+pub fn get_effective_config(monitor_name: Option<&str>) -> Option<Config> {
+    let config = lock_or_recover(&CONFIG, "config").clone()?;
+    let threshold = crate::ai::faces::recognition_threshold_override();
+    Some(effective_config(config, monitor_name, threshold))
+}
+
+fn effective_config(mut config: Config, monitor_name: Option<&str>, threshold: Option<f32>) -> Config {
+    let over = monitor_name.and_then(|name| config.monitor_overrides.as_ref()?.get(name)).cloned();
+    if let Some(over) = over {
+        config.monitoring = config.monitoring.map(|m| m.with_override(&over));
+    }
+    if let (Some(threshold), Some(face)) = (threshold, config.face.as_mut()) {
+        face.recognition.threshold = threshold;
+    }
+    config
+}
+
+// 从配置文件重新读取并替换全局配置；读取、解析或校验失败时保留当前配置。
+// 运行时经 update_config 修改但未写回文件的值会被文件内容覆盖
+pub fn reload() -> Result<(), String> {
+    let path = get_config_path().ok_or("config file not found")?;
+    let config = load_config_from(&path)?;
+    validate(&config).map_err(|e| format!("invalid {}: {}", path, e))?;
+    *lock_or_recover(&CONFIG, "config") = Some(config);
+    info!("[reload] config reloaded from {}", path);
+    set_status(true, Some(path), None);
+    Ok(())
+}
+
+// 修改内存中的配置，立即对后续读取生效（不写回文件）
+pub fn update_config<F: FnOnce(&mut Config)>(f: F) -> Result<(), String> {
+    let mut guard = lock_or_recover(&CONFIG, "config");
+    let config = guard.as_mut().ok_or("config is not initialized")?;
+    f(config);
+    Ok(())
+}
+
+// 将 JSON 补丁合并进当前配置（对象逐层合并，null 表示删除该可选项），校验后写回配置文件并替换全局配置。
+// 与 persist_config_value 一样按行改写补丁涉及的键，保留文件中的注释与其余内容；配置文件不存在时写到 config.toml
+pub fn apply_patch(patch: serde_json::Value) -> Result<Config, String> {
+    let mut guard = lock_or_recover(&CONFIG, "config");
+    let current = guard.clone().unwrap_or_default();
+    let path = get_config_path().unwrap_or_else(|| "config.toml".to_string());
+    let config = apply_patch_to_file(&path, &current, patch)?;
+    watch::ignore_own_write(&path);
+    *guard = Some(config.clone());
+    set_status(true, Some(path.clone()), None);
+    info!("[apply_patch] config saved to {}", path);
+    Ok(config)
+}
+
+// 合并、校验补丁并改写 path 指向的文件，返回合并后的配置；不读写全局配置
+fn apply_patch_to_file(path: &str, current: &Config, patch: serde_json::Value) -> Result<Config, String> {
+    let patch_map = patch.as_object().cloned().ok_or("config patch must be a JSON object")?;
+    let config = merge_patch(current, &patch)?;
+    validate(&config)?;
+
+    let content = if Path::new(path).exists() {
+        fs::read_to_string(path).map_err(|e| format!("read {} failed: {}", path, e))?
+    } else {
+        String::new()
+    };
+    let merged = toml::Value::try_from(&config).map_err(|e| format!("serialize config failed: {}", e))?;
+    let merged = merged.as_table().ok_or("serialize config failed: not a table")?;
+    let text = edit::apply_edits(&content, &edit::patch_edits(merged, &patch_map)?);
+
+    // 改写后的文件应等于“文件原内容合并补丁”，否则（如键的写法无法按行识别）拒绝写入，避免写坏配置文件
+    let before: Config = toml::from_str(&content).map_err(|e| format!("parse {} failed: {}", path, e))?;
+    let expected = merge_patch(&before, &patch)?;
+    let written: Config = toml::from_str(&text).map_err(|e| format!("cannot update {} in place: {}", path, e))?;
+    if serde_json::to_value(&written).ok() != serde_json::to_value(&expected).ok() {
+        return Err(format!("cannot update {} in place: edited file does not match the patch", path));
+    }
+    fs::write(path, text).map_err(|e| format!("write {} failed: {}", path, e))?;
+    Ok(config)
+}
+
+fn merge_patch(base: &Config, patch: &serde_json::Value) -> Result<Config, String> {
+    let mut merged = serde_json::to_value(base).map_err(|e| format!("serialize config failed: {}", e))?;
+    merge_json(&mut merged, patch.clone());
+    serde_json::from_value(merged).map_err(|e| format!("invalid config patch: {}", e))
+}
+
+fn merge_json(base: &mut serde_json::Value, patch: serde_json::Value) {
+    match (base, patch) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(patch)) => {
+            for (k, v) in patch {
+                match base.get_mut(&k) {
+                    Some(existing) if existing.is_object() && v.is_object() => merge_json(existing, v),
+                    _ => {
+                        base.insert(k, v);
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+fn check_range(name: &str, value: f32, min: f32, max: f32) -> Result<(), String> {
+    if value.is_finite() && (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("{} must be in {}..={}, got {}", name, min, max, value))
+    }
+}
+
+fn check_positive(name: &str, value: f32) -> Result<(), String> {
+    if value.is_finite() && value > 0.0 {
+        Ok(())
+    } else {
+        Err(format!("{} must be > 0, got {}", name, value))
+    }
+}
+
+// 校验取值范围，拒绝明显无效的配置（运行时各处仍会做各自的钳制）
+fn validate(config: &Config) -> Result<(), String> {
+    if let Some(m) = &config.monitoring {
+        check_positive("monitoring.mosaic_scale", m.mosaic_scale)?;
+        if m.interval == 0 {
+            return Err("monitoring.interval must be > 0".to_string());
+        }
+        if let Some(v) = m.capture_scale {
+            check_range("monitoring.capture_scale", v, 0.1, 1.0)?;
+        }
+        if let Some(r) = &m.region {
+            if r.width <= 0 || r.height <= 0 || r.x < 0 || r.y < 0 {
+                return Err(format!("monitoring.region must have x, y >= 0 and positive size, got {:?}", r));
+            }
+        }
+        if let Some(v) = m.pixelate_block_px.filter(|v| *v != 0) {
+            if !(PIXELATE_BLOCK_MIN..=PIXELATE_BLOCK_MAX).contains(&v) {
+                return Err(format!(
+                    "monitoring.pixelate_block_px must be 0 or in {}..={}, got {}",
+                    PIXELATE_BLOCK_MIN, PIXELATE_BLOCK_MAX, v
+                ));
+            }
+        }
+        if let Some(v) = m.redaction_merge_iou {
+            check_range("monitoring.redaction_merge_iou", v, 0.0, 1.0)?;
+        }
+    }
+    for (name, o) in config.monitor_overrides.iter().flatten() {
+        if o.interval == Some(0) {
+            return Err(format!("monitor_overrides.{}.interval must be > 0", name));
+        }
+        if let Some(v) = o.mosaic_scale {
+            check_positive(&format!("monitor_overrides.{}.mosaic_scale", name), v)?;
+        }
+        if let Some(v) = o.capture_scale {
+            check_range(&format!("monitor_overrides.{}.capture_scale", name), v, 0.1, 1.0)?;
+        }
+    }
+    if let Some(f) = &config.face {
+        check_range("face.recognition.threshold", f.recognition.threshold, 0.0, 1.0)?;
+        check_range("face.detection.confidence_threshold", f.detection.confidence_threshold, 0.0, 1.0)?;
+        check_positive("face.detection.image_scale", f.detection.image_scale)?;
+        if let Some(v) = f.detection.multi_scale_iou {
+            check_range("face.detection.multi_scale_iou", v, 0.0, 1.0)?;
+        }
+        if let Some(scales) = &f.detection.multi_scales {
+            for s in scales {
+                check_positive("face.detection.multi_scales", *s)?;
+            }
+        }
+    }
+    if let Some(r) = &config.recording {
+        if let Some(v) = r.scale {
+            check_range("recording.scale", v, 0.1, 1.0)?;
+        }
+        if r.fps == Some(0) {
+            return Err("recording.fps must be > 0".to_string());
+        }
+    }
+    Ok(())
+}
+
+// 将 [section] 下的单个键写回配置文件：替换已有的同名键（含多行值），否则插入到节标题之后，节不存在时追加到文件末尾；
+// 按行编辑以保留文件中的注释与其余内容
+pub fn persist_config_value(section: &str, key: &str, value: &toml::Value) -> Result<(), String> {
+    let path = get_config_path().ok_or("config file not found")?;
+    let content = fs::read_to_string(&path).map_err(|e| format!("read {} failed: {}", path, e))?;
+    let edit = edit::Edit::Set {
+        section: section.split('.').map(|s| s.to_string()).collect(),
+        key: key.to_string(),
+        value: value.clone(),
+    };
+    let out = edit::apply_edits(&content, &[edit]);
+    fs::write(&path, out).map_err(|e| format!("write {} failed: {}", path, e))?;
+    // 内存中的配置已由调用方更新，不需要监听线程再重新加载一次
+    watch::ignore_own_write(&path);
+    info!("[persist_config_value] {}.{} saved to {}", section, key, path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const DISPLAY1: &str = r"\\.\DISPLAY1";
+    const DISPLAY2: &str = r"\\.\DISPLAY2";
+
+    const CONFIG_TOML: &str = r#"
+[face.detection]
+scale_factor = 1.1
+min_neighbors = 3
+confidence_threshold = 0.5
+use_gray = false
+image_scale = 1.0
+
+[face.recognition]
+threshold = 0.4
+
+[monitoring]
+# 检测间隔/ms
+interval = 100
+screen_shot_while_detecting = true
+mosaic_scale = 1.2
+mosaic_style = ""
+capture_scale = 0.8
+
+[monitor_overrides."\\\\.\\DISPLAY2"]
+capture_scale = 0.5
+mosaic_scale = 1.5
+"#;
+
+    // 每个测试写各自的临时文件，不读写全局 CONFIG，可与其他测试并行
+    fn write_temp_config(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("screen-ghost-config-{}-{}.toml", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn load_temp_config(name: &str, content: &str) -> Result<Config, String> {
+        let path = write_temp_config(name, content);
+        let result = load_config_from(path.to_str().unwrap());
+        let _ = fs::remove_file(&path);
+        result
+    }
+
+    #[test]
+    fn monitor_override_applies_only_to_its_monitor() {
+        let config = load_temp_config("override", CONFIG_TOML).unwrap();
+
+        let plain = effective_config(config.clone(), Some(DISPLAY1), None).monitoring.unwrap();
+        assert_eq!(plain.capture_scale, Some(0.8));
+        assert_eq!(plain.mosaic_scale, 1.2);
+
+        let overridden = effective_config(config.clone(), Some(DISPLAY2), None).monitoring.unwrap();
+        assert_eq!(overridden.capture_scale, Some(0.5));
+        assert_eq!(overridden.mosaic_scale, 1.5);
+        // 未覆盖的字段沿用 [monitoring]
+        assert_eq!(overridden.interval, 100);
+
+        let none = effective_config(config, None, None).monitoring.unwrap();
+        assert_eq!(none.capture_scale, Some(0.8));
+    }
+
+    #[test]
+    fn effective_config_applies_threshold_override() {
+        let config = load_temp_config("threshold", CONFIG_TOML).unwrap();
+
+        let configured = effective_config(config.clone(), Some(DISPLAY2), None);
+        assert_eq!(configured.face.unwrap().recognition.threshold, 0.4);
+
+        let live = effective_config(config, Some(DISPLAY2), Some(0.25));
+        assert_eq!(live.face.unwrap().recognition.threshold, 0.25);
+        assert_eq!(live.monitoring.unwrap().capture_scale, Some(0.5));
+    }
+
+    #[test]
+    fn load_config_from_reports_path_on_error() {
+        let err = load_temp_config("broken", "[monitoring\ninterval = ").unwrap_err();
+        assert!(err.contains("Failed to parse config file"), "{}", err);
+
+        let missing = std::env::temp_dir().join("screen-ghost-config-missing.toml");
+        let err = load_config_from(missing.to_str().unwrap()).unwrap_err();
+        assert!(err.contains("Failed to read config file"), "{}", err);
+    }
+    #[test]
+    fn patch_is_validated_and_written_in_place() {
+        let path = write_temp_config("patch", CONFIG_TOML);
+        let path_str = path.to_str().unwrap();
+        let config = load_config_from(path_str).unwrap();
+        assert_eq!(validate(&config), Ok(()));
+
+        let patch = serde_json::json!({
+            "monitoring": { "capture_scale": 0.6 },
+            "monitor_overrides": { DISPLAY2: { "capture_scale": 0.4 } },
+        });
+        let patched = apply_patch_to_file(path_str, &config, patch).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        // 只改动补丁涉及的键，注释与其余行保留
+        assert!(written.contains("# 检测间隔/ms\ninterval = 100\n"), "{}", written);
+        assert!(written.contains("capture_scale = 0.6\n"), "{}", written);
+        assert_eq!(written.lines().count(), CONFIG_TOML.lines().count());
+
+        let reloaded = load_config_from(path_str).unwrap();
+        let effective = effective_config(reloaded, Some(DISPLAY2), Some(0.3));
+        assert_eq!(effective.monitoring.unwrap().capture_scale, Some(0.4));
+        assert_eq!(effective.face.unwrap().recognition.threshold, 0.3);
+        assert_eq!(effective_config(patched, Some(DISPLAY1), None).monitoring.unwrap().capture_scale, Some(0.6));
+
+        // 超出范围的覆盖项被拒绝，文件保持不变
+        let bad = serde_json::json!({ "monitor_overrides": { DISPLAY2: { "capture_scale": 3.0 } } });
+        let err = apply_patch_to_file(path_str, &config, bad).unwrap_err();
+        assert!(err.contains("capture_scale"), "{}", err);
+        assert_eq!(fs::read_to_string(&path).unwrap(), written);
+        let _ = fs::remove_file(&path);
+    }
 }
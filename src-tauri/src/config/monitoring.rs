@@ -1,11 +1,215 @@
 use serde::{Deserialize, Serialize};
 
+use crate::mosaic::MosaicStyle;
+use crate::utils::rect::Rect;
+
+// 会话中人脸检测调用出错时遮罩的处理方式
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionErrorPolicy {
+    // 保留上一帧遮罩
+    #[default]
+    HoldLast,
+    // 遮住整个显示器
+    FailClosed,
+    // 清空遮罩
+    Clear,
+}
+
+// 检测图下采样（capture_scale / 多尺度）的滤波方式
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DownscaleFilter {
+    // 最近邻：最快，低倍率下锯齿明显
+    Nearest,
+    // 区域平均（盒式滤波，配置中也可写作 box）：每个目标像素取其覆盖的源像素均值，低倍率下更好地保留人脸结构
+    #[default]
+    #[serde(alias = "box")]
+    Area,
+}
+
+// 结构化 mosaic_style 的遮罩效果
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MosaicKind {
+    Solid,
+    Pixelate,
+    Blur,
+}
+
+// 结构化 mosaic_style：kind 决定效果，其余字段只对相应效果生效
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct MosaicStyleConfig {
+    pub kind: MosaicKind,
+    // solid：CSS 颜色或 "auto"，缺省为黑色
+    pub color: Option<String>,
+    // pixelate：色块边长（物理像素），未设置 pixelate_block_px 时生效
+    pub block_size: Option<u32>,
+    // blur：模糊半径（物理像素），缺省 12
+    pub blur_radius: Option<u32>,
+    // pixelate / blur 使用的图案（CSS 声明块，同旧格式），缺省时为纯色底
+    pub css: Option<String>,
+}
+
+// mosaic_style 兼容两种写法：旧的 CSS 声明块字符串，或带 kind 的结构化表
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum MosaicStyleSetting {
+    Structured(MosaicStyleConfig),
+    Css(String),
+}
+
+impl Default for MosaicStyleSetting {
+    fn default() -> Self {
+        MosaicStyleSetting::Css(String::new())
+    }
+}
+
+impl MosaicStyleSetting {
+    pub fn structured(&self) -> Option<&MosaicStyleConfig> {
+        match self {
+            MosaicStyleSetting::Structured(s) => Some(s),
+            MosaicStyleSetting::Css(_) => None,
+        }
+    }
+}
+
+// mosaic_padding 的数值单位
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaddingUnit {
+    // 物理像素
+    #[default]
+    Px,
+    // 框尺寸的比例：上下按框高、左右按框宽
+    Ratio,
+}
+
+// 遮罩框各边外扩量（框的局部方向，旋转框随角度旋转），负值为内缩；在 mosaic_scale 缩放之前应用
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+pub struct MosaicPadding {
+    #[serde(default)]
+    pub top: f32,
+    #[serde(default)]
+    pub bottom: f32,
+    #[serde(default)]
+    pub left: f32,
+    #[serde(default)]
+    pub right: f32,
+    #[serde(default)]
+    pub unit: PaddingUnit,
+}
+
+impl MosaicPadding {
+    // 换算为像素：(左, 右, 上, 下)
+    pub fn edges_px(&self, width: f32, height: f32) -> (f32, f32, f32, f32) {
+        match self.unit {
+            PaddingUnit::Px => (self.left, self.right, self.top, self.bottom),
+            PaddingUnit::Ratio => (self.left * width, self.right * width, self.top * height, self.bottom * height),
+        }
+    }
+}
+
+// pixelate_block_px 的有效范围
+pub const PIXELATE_BLOCK_MIN: u32 = 2;
+pub const PIXELATE_BLOCK_MAX: u32 = 256;
+
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct MonitoringConfig {
     pub interval: u64,
+    // 可选：人脸检测的最小间隔（ms），截图仍按 interval 进行，期间沿用上次检测的遮罩；0 或缺省为每帧检测
+    pub detection_interval: Option<u64>,
+    // 可选：两次检测之间按各人脸的运动速度外推遮罩框（需配合 detection_interval），默认关闭
+    pub enable_tracking: Option<bool>,
     pub screen_shot_while_detecting: bool,
     pub mosaic_scale: f32,
-    pub mosaic_style: String,
+    pub mosaic_style: MosaicStyleSetting,
     // 可选：对截图做下采样（0.1~1.0），仅用于检测加速，遮罩坐标将自动还原到原分辨率
     pub capture_scale: Option<f32>,
+    // 可选：只截取并检测显示器的局部区域（显示器内物理像素坐标），遮罩坐标自动平移回整个显示器；缺省为整个显示器
+    pub region: Option<Rect>,
+    // 可选：DirectX 截图等待新帧（AcquireNextFrame）的超时/ms，三种 DirectX 方法统一使用；缺省按方法各用默认值
+    pub acquire_timeout_ms: Option<u32>,
+    // 可选：DXGI 报告画面没有新帧时跳过检测与推送，沿用当前遮罩（静止画面下节省 CPU/GPU），默认 true
+    pub skip_unchanged_frames: Option<bool>,
+    // 可选：停止监控时遮罩淡出的时长（ms），0 或缺省表示立即消失
+    pub stop_fade_ms: Option<u64>,
+    // 可选：向 overlay 推送遮罩的频率（fps，15~240），缺省 60；开始监控时读取
+    pub overlay_fps: Option<u32>,
+    // 可选：遮罩边缘羽化宽度（物理像素），0 或缺省为硬边
+    pub feather_px: Option<u32>,
+    // 可选：像素化遮罩的色块边长（物理像素，2~256），使大小人脸的像素化程度一致；0 或缺省时随框大小缩放
+    pub pixelate_block_px: Option<u32>,
+    // 可选：在截图上直接像素化人脸区域并以 image 事件发送处理后的帧，供抓取该帧的下游工具使用（此时不再发送原始调试帧），默认关闭
+    pub bake_mosaic: Option<bool>,
+    // 可选：在遮罩框外沿绘制一圈淡淡的脉动描边，提示遮罩正在实时跟踪，默认关闭
+    pub active_border: Option<bool>,
+    // 可选：人脸漏检后遮罩保留时长/ms，期间逐渐淡出（同一人脸重新出现时按 IoU 匹配刷新），0 或缺省为不保留
+    pub mosaic_hold_ms: Option<u64>,
+    // 可选：停止监控后空闲多少秒自动释放截图资源与缓存帧，0 或缺省为不自动释放
+    pub idle_release_secs: Option<u64>,
+    // 可选：空闲释放时是否同时释放识别模型（回收显存，下次检测时重新加载需数秒），默认 false
+    pub idle_release_model: Option<bool>,
+    // 可选：detection-cycle 事件的最小间隔（ms），高帧率时丢弃间隔内的周期，0 表示每个周期都发送，默认 200
+    pub detection_event_min_interval_ms: Option<u64>,
+    // 可选：detection-cycle 事件中隐去命中的人名，默认 true
+    pub redact_person_names: Option<bool>,
+    // 可选：按设备名指定默认监控的显示器，初始化完成后自动开始监控
+    pub default_monitor_name: Option<String>,
+    // 可选：工作显示器之外同时监控的显示器（设备名列表，"*" 表示其余全部），每个显示器独立截图线程与 overlay，缺省只监控工作显示器
+    pub extra_monitors: Option<Vec<String>>,
+    // 可选：遮罩样式（pixelate / sticker / solid），缺省为 pixelate
+    pub cover_style: Option<MosaicStyle>,
+    // 可选：仅在当前前台窗口区域内检测与遮罩（随窗口移动/缩放），默认关闭
+    pub follow_active_window: Option<bool>,
+    // 可选：需要遮罩的最小人脸面积（原分辨率物理像素²），检测后过滤，不影响检测器的 min_face_size
+    pub min_cover_area_px: Option<u64>,
+    // 可选：遮罩框四边外扩的像素数（原分辨率），在合并之前进行
+    pub redaction_pad_px: Option<i32>,
+    // 可选：遮罩框四边分别外扩（检测框常贴紧面部、漏掉额头与下巴），与 mosaic_scale 叠加，缺省不外扩
+    pub mosaic_padding: Option<MosaicPadding>,
+    // 可选：外扩后间隙不超过该值（px）的框合并为一个，0 表示仅合并相交/相接的框，缺省不合并
+    pub redaction_merge_gap_px: Option<i32>,
+    // 可选：外扩后 IoU 不低于该值的框合并为一个，缺省不按 IoU 合并
+    pub redaction_merge_iou: Option<f32>,
+    // 可选：检测出错时的遮罩策略（hold_last / fail_closed / clear），缺省为 hold_last
+    pub on_detection_error: Option<DetectionErrorPolicy>,
+    // 可选：忽略任务栏区域（显示器减去工作区）内的检测结果，缺省开启；自动隐藏的任务栏不排除
+    pub exclude_taskbar: Option<bool>,
+    // 可选：capture_scale < 1 时在 GPU 上降采样，只回读缩小后的图像（仅 DirectX 优化路径生效，录制期间不启用），默认关闭
+    pub gpu_downscale: Option<bool>,
+    // 可选：检测图下采样的滤波方式（nearest / area，area 也可写作 box），只影响送检图像，不影响导出/录制的画面，缺省 area
+    pub downscale_filter: Option<DownscaleFilter>,
+    // 可选：overlay 被隐藏或显示器关闭时暂停截图与检测（暂停期间全屏遮罩），缺省开启
+    pub pause_when_hidden: Option<bool>,
+    // 可选：调试预览流（DEBUG_IMAGE_STREAM=1）的带宽预算/kbps，按画面变化自适应 JPEG 质量与帧率，缺省 2000，0 为不限
+    pub preview_budget_kbps: Option<u32>,
+}
+
+// 按显示器覆盖的监控参数（[monitor_overrides."<设备名>"]），缺省的字段沿用 [monitoring]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct MonitorOverride {
+    pub interval: Option<u64>,
+    pub mosaic_scale: Option<f32>,
+    pub capture_scale: Option<f32>,
+    pub min_cover_area_px: Option<u64>,
+}
+
+impl MonitoringConfig {
+    // 合并某个显示器的覆盖项
+    pub fn with_override(mut self, o: &MonitorOverride) -> Self {
+        if let Some(interval) = o.interval {
+            self.interval = interval;
+        }
+        if let Some(mosaic_scale) = o.mosaic_scale {
+            self.mosaic_scale = mosaic_scale;
+        }
+        if o.capture_scale.is_some() {
+            self.capture_scale = o.capture_scale;
+        }
+        if o.min_cover_area_px.is_some() {
+            self.min_cover_area_px = o.min_cover_area_px;
+        }
+        self
+    }
 }
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct PythonConfig {
+    // 可选：指定已有的 Python 可执行文件（如 conda 环境），校验通过后直接使用，跳过 venv 创建
+    pub python_executable: Option<String>,
+    // 可选：覆盖支持的 Python 版本范围（如 "3.9" / "3.12"，含两端），缺省使用内置范围
+    pub min_version: Option<String>,
+    pub max_version: Option<String>,
+    // 可选：只从随附的 wheels/ 目录安装依赖，缺包时报错而不联网（等同环境变量 SCREEN_GHOST_OFFLINE=1）
+    pub offline: Option<bool>,
+    // 可选：pip 镜像地址（--index-url），用于 Python 依赖的联网安装；http:// 镜像的主机自动加入 --trusted-host
+    pub pip_index_url: Option<String>,
+    // 可选：https 镜像也加入 --trusted-host（自签证书等场景，会跳过证书校验），默认 false
+    pub pip_trusted_host: Option<bool>,
+}
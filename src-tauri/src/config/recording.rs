@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct RecordingConfig {
+    // 编码器可执行文件（ffmpeg），缺省从 PATH 查找
+    pub ffmpeg_path: Option<String>,
+    // 录制帧率，默认 10
+    pub fps: Option<u32>,
+    // 输出分辨率相对显示器物理分辨率的倍率（0.1~1.0），默认 1.0
+    pub scale: Option<f32>,
+    // 单次录制最长时长（秒），到达后自动停止，默认 3600
+    pub max_seconds: Option<u64>,
+    // 单个文件大小上限（MB），到达后自动停止，缺省不限制
+    pub max_mb: Option<u64>,
+}
@@ -3,4 +3,20 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct SystemConfig {
     pub log_level: Option<String>,
+    // 创建 overlay 时是否自动打开 devtools（默认 false，与日志级别无关）
+    pub overlay_devtools: Option<bool>,
+    // 调试：把每个识别判定的缩略图与分数写入应用数据目录下的 decisions/（默认关闭）
+    pub decision_audit: Option<bool>,
+    // 判定缩略图最多保留的文件数，超出后删除最旧的，默认 500
+    pub decision_audit_max_files: Option<usize>,
+    // 开机自启时尽早开始遮罩，并在确认 overlay 置顶且排除截图后才报告已保护（默认 false）
+    pub protect_on_autostart: Option<bool>,
+    // 关闭主窗口时隐藏到托盘（监控继续运行），只能从托盘菜单退出（默认 false：关闭即退出）
+    pub close_to_tray: Option<bool>,
+}
+
+impl SystemConfig {
+    pub fn close_to_tray(&self) -> bool {
+        self.close_to_tray.unwrap_or(false)
+    }
 }
\ No newline at end of file
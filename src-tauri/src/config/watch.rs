@@ -0,0 +1,51 @@
+use std::fs;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+use log::{info, warn};
+
+use super::{get_config_path, reload};
+use crate::utils::sync::lock_or_recover;
+
+// 配置文件热加载：轮询修改时间（与空闲释放等后台任务一致，不引入文件系统通知依赖），
+// 变化后重新加载全局配置并回调；监控循环每轮读取配置，interval / capture_scale 等随即生效
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+static WATCHER: OnceLock<()> = OnceLock::new();
+// 应用自身写回配置文件后的修改时间：内存配置已是最新，监视线程跳过这次变化
+static OWN_WRITE: Mutex<Option<SystemTime>> = Mutex::new(None);
+
+pub(super) fn ignore_own_write(path: &str) {
+    *lock_or_recover(&OWN_WRITE, "config_own_write") = fs::metadata(path).and_then(|m| m.modified()).ok();
+}
+
+fn modified_at() -> Option<SystemTime> {
+    let path = get_config_path()?;
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// 启动监视线程（仅一次）；on_reload 在每次成功重新加载后调用
+pub fn watch<F: Fn() + Send + 'static>(on_reload: F) {
+    WATCHER.get_or_init(|| {
+        std::thread::spawn(move || {
+            let mut last = modified_at();
+            info!("[config_watch] watching {:?}", get_config_path());
+            loop {
+                std::thread::sleep(POLL_INTERVAL);
+                let current = modified_at();
+                if current.is_none() || current == last {
+                    continue;
+                }
+                last = current;
+                if current == *lock_or_recover(&OWN_WRITE, "config_own_write") {
+                    continue;
+                }
+                match reload() {
+                    Ok(()) => on_reload(),
+                    // 编辑器保存到一半或写错语法时保留旧配置，下次保存再试
+                    Err(e) => warn!("[config_watch] keep current config: {}", e),
+                }
+            }
+        });
+    });
+}
@@ -0,0 +1,127 @@
+use std::sync::OnceLock;
+
+use log::{info, warn};
+use serde::Serialize;
+use windows::core::Interface;
+use windows::Win32::Graphics::Direct3D::{D3D_DRIVER_TYPE, D3D_DRIVER_TYPE_HARDWARE, D3D_DRIVER_TYPE_UNKNOWN, D3D_DRIVER_TYPE_WARP};
+use windows::Win32::Graphics::Direct3D11::{D3D11CreateDevice, ID3D11Device, D3D11_CREATE_DEVICE_BGRA_SUPPORT, D3D11_SDK_VERSION};
+use windows::Win32::Graphics::Dxgi::{CreateDXGIFactory1, IDXGIAdapter, IDXGIAdapter1, IDXGIFactory1, IDXGIOutput1};
+use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
+
+// 本机截图能力：启动时探测一次并缓存，供排查问题时先确认环境（驱动、系统版本、远程桌面等）
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureCapabilities {
+    // 可创建 D3D11 硬件设备（DXGI 截图的前提）
+    pub d3d11_hardware: bool,
+    // 可创建 WARP 软件设备（无可用显卡驱动时的软件渲染）
+    pub d3d11_warp: bool,
+    // 至少一个输出可创建桌面复制
+    pub dxgi_duplication: bool,
+    // 系统支持 Windows.Graphics.Capture
+    pub wgc: bool,
+    // 窗口可设置 WDA_EXCLUDEFROMCAPTURE（Windows 10 2004+），不支持时 overlay 会被截进画面
+    pub exclude_from_capture: bool,
+    // 适配器名称及其输出数
+    pub adapters: Vec<(String, u32)>,
+    // 各项探测失败的原因
+    pub errors: Vec<String>,
+}
+
+static CAPABILITIES: OnceLock<CaptureCapabilities> = OnceLock::new();
+
+// 首次调用时探测（涉及创建设备与桌面复制，约数十至数百毫秒），之后返回缓存结果
+pub fn get() -> &'static CaptureCapabilities {
+    CAPABILITIES.get_or_init(|| {
+        let caps = probe();
+        info!(
+            "[capture_capabilities] hardware={} warp={} duplication={} wgc={} exclude_from_capture={} adapters={:?}",
+            caps.d3d11_hardware, caps.d3d11_warp, caps.dxgi_duplication, caps.wgc, caps.exclude_from_capture, caps.adapters
+        );
+        for e in &caps.errors {
+            warn!("[capture_capabilities] {}", e);
+        }
+        caps
+    })
+}
+
+fn probe() -> CaptureCapabilities {
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+    }
+    let mut errors = Vec::new();
+    let mut record = |name: &str, r: Result<(), String>| match r {
+        Ok(()) => true,
+        Err(e) => {
+            errors.push(format!("{}: {}", name, e));
+            false
+        }
+    };
+    let d3d11_hardware = record("d3d11_hardware", create_device(None, D3D_DRIVER_TYPE_HARDWARE).map(|_| ()));
+    let d3d11_warp = record("d3d11_warp", create_device(None, D3D_DRIVER_TYPE_WARP).map(|_| ()));
+    let (adapters, duplication) = probe_duplication();
+    let dxgi_duplication = record("dxgi_duplication", duplication);
+    let wgc = record("wgc", super::wgc::check_supported());
+    let exclude_from_capture = record("exclude_from_capture", crate::overlay::probe_exclude_from_capture());
+    CaptureCapabilities { d3d11_hardware, d3d11_warp, dxgi_duplication, wgc, exclude_from_capture, adapters, errors }
+}
+
+fn create_device(adapter: Option<&IDXGIAdapter>, driver_type: D3D_DRIVER_TYPE) -> Result<ID3D11Device, String> {
+    let mut device: Option<ID3D11Device> = None;
+    unsafe {
+        D3D11CreateDevice(
+            adapter,
+            driver_type,
+            windows::Win32::Foundation::HMODULE::default(),
+            D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+            None,
+            D3D11_SDK_VERSION,
+            Some(&mut device),
+            None,
+            None,
+        )
+        .map_err(|e| format!("D3D11CreateDevice failed: {e}"))?;
+    }
+    device.ok_or_else(|| "D3D11 device not created".to_string())
+}
+
+// 枚举适配器与输出，逐个尝试创建桌面复制，任一成功即可用；同时收集适配器列表
+fn probe_duplication() -> (Vec<(String, u32)>, Result<(), String>) {
+    let factory: IDXGIFactory1 = match unsafe { CreateDXGIFactory1() } {
+        Ok(f) => f,
+        Err(e) => return (Vec::new(), Err(format!("CreateDXGIFactory1 failed: {e}"))),
+    };
+    let mut adapters = Vec::new();
+    let mut result = Err("no adapter output found".to_string());
+    let mut i = 0;
+    while let Ok(adapter) = unsafe { factory.EnumAdapters1(i) } {
+        i += 1;
+        let name = adapter_name(&adapter);
+        let mut outputs = 0;
+        while let Ok(output) = unsafe { adapter.EnumOutputs(outputs) } {
+            outputs += 1;
+            if result.is_ok() {
+                continue;
+            }
+            result = (|| {
+                let output1: IDXGIOutput1 = output.cast().map_err(|e| format!("IDXGIOutput1 cast failed: {e}"))?;
+                let base: IDXGIAdapter = adapter.cast().map_err(|e| format!("IDXGIAdapter cast failed: {e}"))?;
+                let device = create_device(Some(&base), D3D_DRIVER_TYPE_UNKNOWN)?;
+                unsafe { output1.DuplicateOutput(&device) }
+                    .map(|_| ())
+                    .map_err(|e| format!("DuplicateOutput failed on {}: {e}", name))
+            })();
+        }
+        adapters.push((name, outputs));
+    }
+    (adapters, result)
+}
+
+fn adapter_name(adapter: &IDXGIAdapter1) -> String {
+    match unsafe { adapter.GetDesc1() } {
+        Ok(desc) => {
+            let len = desc.Description.iter().position(|c| *c == 0).unwrap_or(desc.Description.len());
+            String::from_utf16_lossy(&desc.Description[..len])
+        }
+        Err(_) => "unknown adapter".to_string(),
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt;
+
+use super::screen_shot::CaptureMethod;
+
+// 截图失败原因：按失败阶段分类，不同阶段对应不同的处理方式（更新驱动、检查显示器配置、关闭占用桌面复制的程序等）。
+// 各方法的失败原因在回退过程中逐一收集，全部失败时汇总为 AllFailed
+#[derive(Debug, Clone)]
+pub enum CaptureError {
+    // D3D11 设备或 DXGI 工厂创建失败
+    Device(String),
+    // 找不到与显示器坐标匹配的适配器输出（显示器配置已变化）
+    NoMatchingOutput,
+    // 创建/重建桌面复制或捕获会话失败（被其他程序占用、安全桌面、全屏独占等）
+    Duplication(String),
+    // 取帧失败
+    Acquire(String),
+    // 取帧超时且没有可复用的上一帧
+    Timeout,
+    // 纹理创建、拷贝或映射回读失败
+    Readback(String),
+    // 系统不支持该方法
+    Unsupported(String),
+    // 截图成功但内容为空白
+    Blank,
+    // GDI 截图失败
+    Gdi(String),
+    // 依次尝试的各方法全部失败，按尝试顺序记录各自原因
+    AllFailed(Vec<(CaptureMethod, CaptureError)>),
+}
+
+impl CaptureError {
+    // 面向用户的简短说明（toast 使用）；汇总错误取首个方法（通常为首选方法）的原因
+    pub fn hint(&self) -> &'static str {
+        match self {
+            CaptureError::Device(_) => "显卡设备创建失败，请检查显卡驱动",
+            CaptureError::NoMatchingOutput => "找不到对应的显示器输出，显示器配置可能已变化",
+            CaptureError::Duplication(_) => "桌面复制不可用，可能被其他录屏程序占用或处于安全桌面",
+            CaptureError::Acquire(_) | CaptureError::Timeout => "获取屏幕画面失败",
+            CaptureError::Readback(_) => "读取屏幕画面失败（显存不足或设备已移除）",
+            CaptureError::Unsupported(_) => "当前系统不支持该截图方式",
+            CaptureError::Blank => "截图为空白，可能是受保护内容或混合显卡输出",
+            CaptureError::Gdi(_) => "GDI 截图失败",
+            CaptureError::AllFailed(errors) => errors.first().map(|(_, e)| e.hint()).unwrap_or("截图失败"),
+        }
+    }
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::Device(e) => write!(f, "device creation failed: {}", e),
+            CaptureError::NoMatchingOutput => write!(f, "no adapter output matches the monitor"),
+            CaptureError::Duplication(e) => write!(f, "duplication unavailable: {}", e),
+            CaptureError::Acquire(e) => write!(f, "frame acquisition failed: {}", e),
+            CaptureError::Timeout => write!(f, "frame acquisition timed out"),
+            CaptureError::Readback(e) => write!(f, "readback failed: {}", e),
+            CaptureError::Unsupported(e) => write!(f, "unsupported: {}", e),
+            CaptureError::Blank => write!(f, "blank content"),
+            CaptureError::Gdi(e) => write!(f, "GDI capture failed: {}", e),
+            CaptureError::AllFailed(errors) => {
+                write!(f, "all capture methods failed")?;
+                for (i, (method, e)) in errors.iter().enumerate() {
+                    write!(f, "{} {}: {}", if i == 0 { ":" } else { ";" }, method.name(), e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<CaptureError> for String {
+    fn from(e: CaptureError) -> Self {
+        e.to_string()
+    }
+}
@@ -1,4 +1,8 @@
+pub mod capabilities;
+mod capture_error;
 pub mod monitor;
 pub mod screen_shot;
+mod wgc;
+pub use capture_error::CaptureError;
 pub use monitor::MonitorInfo;
 pub use screen_shot::Image;
@@ -1,7 +1,8 @@
-use log::{debug, info};
+use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
 
 use crate::app::AppState;
+use crate::utils::rect::Rect;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitorInfo {
@@ -11,6 +12,9 @@ pub struct MonitorInfo {
     pub width: i32,
     pub height: i32,
     pub scale_factor: f64,
+    // 系统设备名（如 \\.\DISPLAY1），跨重启/重新插拔比索引更稳定
+    #[serde(default)]
+    pub name: Option<String>,
 }
 
 // 获取所有显示器信息，按照x坐标排序
@@ -33,6 +37,7 @@ pub fn list_monitors() -> Result<Vec<MonitorInfo>, String> {
                 width: size.width as i32,
                 height: size.height as i32,
                 scale_factor: monitor.scale_factor(),
+                name: monitor.name().cloned(),
             }
         })
         .collect::<Vec<_>>();
@@ -48,10 +53,84 @@ pub fn list_monitors() -> Result<Vec<MonitorInfo>, String> {
 
     for monitor in &monitors {
         info!(
-            "[list_monitors] monitor: {}, position: ({}, {}), size: {}x{}, scale_factor: {}",
-            monitor.id, monitor.x, monitor.y, monitor.width, monitor.height, monitor.scale_factor
+            "[list_monitors] monitor: {}, name: {:?}, position: ({}, {}), size: {}x{}, scale_factor: {}",
+            monitor.id, monitor.name, monitor.x, monitor.y, monitor.width, monitor.height, monitor.scale_factor
         );
     }
 
     Ok(monitors)
-}
\ No newline at end of file
+}
+
+// 按设备名查找显示器；找不到时回退到主显示器（再不行取第一个），第二个返回值表示是否发生了回退
+pub fn resolve_monitor_by_name(name: &str) -> Result<(MonitorInfo, bool), String> {
+    let monitors = list_monitors()?;
+    if let Some(m) = monitors.iter().find(|m| m.name.as_deref() == Some(name)) {
+        debug!("[resolve_monitor_by_name] matched {} -> monitor {}", name, m.id);
+        return Ok((m.clone(), false));
+    }
+    warn!("[resolve_monitor_by_name] monitor {} not found, falling back to primary", name);
+    let primary = AppState::get_main_window()
+        .map_err(|e| e.to_string())?
+        .primary_monitor()
+        .map_err(|e| format!("get primary monitor failed: {}", e))?;
+    let fallback = primary
+        .and_then(|p| {
+            let pos = p.position();
+            monitors.iter().find(|m| m.x == pos.x && m.y == pos.y).cloned()
+        })
+        .or_else(|| monitors.first().cloned())
+        .ok_or_else(|| "no monitor available".to_string())?;
+    Ok((fallback, true))
+}
+
+// 将桌面物理坐标下的框按显示器切分：与各显示器边界求交，并换算为该显示器 overlay 的本地坐标。
+// 跨越相邻显示器接缝的框会在两侧各得到一段；overlay 各自按自身 scale_factor 换算 CSS 像素
+pub fn split_rect_across_monitors(rect: &Rect, monitors: &[MonitorInfo]) -> Vec<(usize, Rect)> {
+    monitors
+        .iter()
+        .filter_map(|m| {
+            let bounds = Rect::new(m.x, m.y, m.width, m.height);
+            rect.intersection(&bounds)
+                .map(|r| (m.id, Rect::new(r.x - m.x, r.y - m.y, r.width, r.height)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(id: usize, x: i32, y: i32, width: i32, height: i32) -> MonitorInfo {
+        MonitorInfo { id, x, y, width, height, scale_factor: 1.0, name: None }
+    }
+
+    fn parts(rect: Rect, monitors: &[MonitorInfo]) -> Vec<(usize, (i32, i32, i32, i32))> {
+        split_rect_across_monitors(&rect, monitors)
+            .into_iter()
+            .map(|(id, r)| (id, (r.x, r.y, r.width, r.height)))
+            .collect()
+    }
+
+    #[test]
+    fn maps_desktop_rect_into_monitor_at_negative_offset() {
+        // 副屏位于主屏左侧且上移 200px：桌面坐标 (-1800, -100) 对应副屏本地 (120, 100)
+        let monitors = [monitor(0, -1920, -200, 1920, 1080), monitor(1, 0, 0, 2560, 1440)];
+        assert_eq!(parts(Rect::new(-1800, -100, 50, 60), &monitors), vec![(0, (120, 100, 50, 60))]);
+        // 副屏左上角
+        assert_eq!(parts(Rect::new(-1920, -200, 10, 10), &monitors), vec![(0, (0, 0, 10, 10))]);
+        // 主屏上方、副屏右侧以外的区域不属于任何显示器
+        assert!(parts(Rect::new(10, -150, 20, 20), &monitors).is_empty());
+    }
+
+    #[test]
+    fn splits_rect_straddling_the_negative_seam() {
+        let monitors = [monitor(0, -1920, -200, 1920, 1080), monitor(1, 0, 0, 2560, 1440)];
+        // 跨 x=0 接缝：左段在副屏右缘，右段在主屏左缘
+        assert_eq!(
+            parts(Rect::new(-30, 100, 80, 40), &monitors),
+            vec![(0, (1890, 300, 30, 40)), (1, (0, 100, 50, 40))]
+        );
+        // 跨副屏下缘（桌面 y=880）的部分被裁掉
+        assert_eq!(parts(Rect::new(-100, 860, 20, 40), &monitors), vec![(0, (1820, 1060, 20, 20))]);
+    }
+}
@@ -1,18 +1,25 @@
-use log::{debug, info};
+use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
 
+use super::capture_error::CaptureError;
 use super::monitor::{MonitorInfo};
 use std::sync::{Arc, Mutex, OnceLock};
 use std::collections::HashMap;
+use crate::utils::rect::Rect;
+use crate::utils::sync::lock_or_recover;
+use windows::Win32::Graphics::Direct3D11::D3D11_BOX;
 use windows::Win32::Graphics::Direct3D11::{D3D11CreateDevice, ID3D11Device, ID3D11DeviceContext, ID3D11Texture2D, D3D11_CPU_ACCESS_READ, D3D11_CREATE_DEVICE_BGRA_SUPPORT, D3D11_SDK_VERSION, D3D11_TEXTURE2D_DESC, D3D11_USAGE_STAGING};
+use windows::Win32::Graphics::Direct3D11::{ID3D11ShaderResourceView, D3D11_BIND_RENDER_TARGET, D3D11_BIND_SHADER_RESOURCE, D3D11_RESOURCE_MISC_GENERATE_MIPS, D3D11_USAGE_DEFAULT};
+use windows::Win32::Graphics::Dxgi::Common::DXGI_FORMAT;
 use windows::Win32::Graphics::Gdi::{BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDIBits, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, RGBQUAD, SRCCOPY};
 use windows::Win32::Graphics::Gdi::{GetDC, ReleaseDC};
-use windows::Win32::UI::WindowsAndMessaging::GetDesktopWindow;
 use windows::core::Interface;
 use windows::Win32::Graphics::Dxgi::{IDXGIOutputDuplication, DXGI_OUTDUPL_FRAME_INFO};
 use windows::Win32::Graphics::Dxgi::{IDXGIFactory1, CreateDXGIFactory1, IDXGIAdapter1, IDXGIOutput, IDXGIOutput1};
 use windows::Win32::Graphics::Dxgi::IDXGIAdapter;
-use windows::Win32::Graphics::Dxgi::DXGI_ERROR_WAIT_TIMEOUT;
+use windows::Win32::Graphics::Dxgi::{IDXGIResource, DXGI_ERROR_ACCESS_LOST, DXGI_ERROR_WAIT_TIMEOUT};
+use windows::Win32::Graphics::Dxgi::{DXGI_OUTDUPL_DESC, DXGI_OUTPUT_DESC};
+use windows::Win32::Graphics::Dxgi::Common::{DXGI_MODE_ROTATION, DXGI_MODE_ROTATION_ROTATE180, DXGI_MODE_ROTATION_ROTATE270, DXGI_MODE_ROTATION_ROTATE90};
 use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,15 +29,329 @@ pub struct Image {
 	pub data: Vec<u8>, // BGRA
 }
 
-// 对外统一的截图入口。后续可将 MonitorInfo 上的方法完全移走并在此实现具体逻辑。
+impl Image {
+	// 编码为 PNG（BGRA -> RGBA）
+	pub fn to_png(&self) -> Result<Vec<u8>, String> {
+		let width = self.width.max(0) as u32;
+		let height = self.height.max(0) as u32;
+		let expected = (width as usize) * (height as usize) * 4;
+		if self.data.len() < expected {
+			return Err(format!("image buffer too small: {} < {}", self.data.len(), expected));
+		}
+		let mut rgba = Vec::with_capacity(expected);
+		for px in self.data[..expected].chunks_exact(4) {
+			rgba.extend_from_slice(&[px[2], px[1], px[0], px[3]]);
+		}
+		let mut out = Vec::new();
+		{
+			let mut encoder = png::Encoder::new(&mut out, width, height);
+			encoder.set_color(png::ColorType::Rgba);
+			encoder.set_depth(png::BitDepth::Eight);
+			let mut writer = encoder.write_header().map_err(|e| format!("png header failed: {}", e))?;
+			writer.write_image_data(&rgba).map_err(|e| format!("png encode failed: {}", e))?;
+		}
+		Ok(out)
+	}
+
+	// 编码为 JPEG（BGRA，忽略 alpha），quality 取 1~100
+	pub fn to_jpeg(&self, quality: u8) -> Result<Vec<u8>, String> {
+		let width = u16::try_from(self.width.max(0)).map_err(|_| format!("image too wide for jpeg: {}", self.width))?;
+		let height = u16::try_from(self.height.max(0)).map_err(|_| format!("image too tall for jpeg: {}", self.height))?;
+		let mut out = Vec::new();
+		jpeg_encoder::Encoder::new(&mut out, quality)
+			.encode(&self.data, width, height, jpeg_encoder::ColorType::Bgra)
+			.map_err(|e| format!("jpeg encode failed: {}", e))?;
+		Ok(out)
+	}
+}
+
+// DXGI 桌面复制报告的帧呈现信息（DXGI_OUTDUPL_FRAME_INFO），仅 Optimized 方法提供
+#[derive(Debug, Clone, Copy)]
+pub struct FramePresent {
+	pub accumulated_frames: u32,
+	pub last_present_time: i64,
+}
+
+impl FramePresent {
+	// 自上次取帧以来没有新的桌面图像（LastPresentTime 为 0 表示只有鼠标等更新）
+	pub fn is_unchanged(&self) -> bool {
+		self.accumulated_frames == 0 || self.last_present_time == 0
+	}
+}
+
+// 一次截图的结果：图像、实际使用的方法与截图耗时；from_prefetch 由预取缓冲的消费方标记
+// scale 为图像相对显示器物理分辨率的实际倍率（GPU 降采样时小于 1）；present 无法判断时为 None
+// region 为实际截取的显示器局部区域（None 为整个显示器），图像左上角对应该区域左上角
+#[derive(Debug, Clone)]
+pub struct CaptureOutcome {
+	pub image: Image,
+	pub method: CaptureMethod,
+	pub elapsed: std::time::Duration,
+	pub from_prefetch: bool,
+	pub scale: f32,
+	pub present: Option<FramePresent>,
+	pub region: Option<Rect>,
+}
+
+impl CaptureOutcome {
+	// 画面确定未变化（无法判断时视为已变化）
+	pub fn is_unchanged(&self) -> bool {
+		self.present.is_some_and(|p| p.is_unchanged())
+	}
+}
+
+// 对外统一的截图入口，集中计时并记录实际使用的方法
+pub fn capture_monitor(monitor: &MonitorInfo) -> Result<CaptureOutcome, CaptureError> {
+	capture_monitor_scaled(monitor, 1.0)
+}
+
+// 带 GPU 降采样的截图：scale < 1 且走 Optimized 路径时在 GPU 上生成 mipmap，只回读不小于 scale 的最大一级，
+// 其余方法仍返回原分辨率；调用方以 CaptureOutcome::scale 为准
+pub fn capture_monitor_scaled(monitor: &MonitorInfo, scale: f32) -> Result<CaptureOutcome, CaptureError> {
+	capture_monitor_region(monitor, None, scale)
+}
+
+// 只截取显示器的局部区域（显示器局部坐标，裁剪到显示器范围）：Optimized 只拷贝/回读该区域，GDI 只 BitBlt 该区域，
+// 其余方法整屏截图后在 CPU 上裁剪；区域覆盖整个显示器或与之不相交时按整屏截图
+pub fn capture_monitor_region(monitor: &MonitorInfo, region: Option<&Rect>, scale: f32) -> Result<CaptureOutcome, CaptureError> {
+	let start = std::time::Instant::now();
+	let region = region.and_then(|r| clamp_region(monitor, r));
+	let (image, method, scale, present) = monitor.capture(scale, region.as_ref())?;
+	let elapsed = start.elapsed();
+	debug!("[capture_monitor] got buffer {}x{} ({} bytes, scale {:.3}, region {:?}) via {} in {:?}", image.width, image.height, image.data.len(), scale, region, method.name(), elapsed);
+	Ok(CaptureOutcome { image, method, elapsed, from_prefetch: false, scale, present, region })
+}
+
+// 区域与显示器求交；不相交或覆盖整个显示器时返回 None（整屏截图）
+pub fn clamp_region(monitor: &MonitorInfo, region: &Rect) -> Option<Rect> {
+	let r = region.intersection(&Rect::new(0, 0, monitor.width, monitor.height))?;
+	(r.width < monitor.width || r.height < monitor.height).then_some(r)
+}
+
+// 不支持按区域回读的方法：整屏截图后在 CPU 上裁剪（再与图像求交，防止图像尺寸与显示器不一致时越界）
+fn crop_to_region(image: Image, region: Option<&Rect>) -> Image {
+	let Some(r) = region else {
+		return image;
+	};
+	let x = r.x.clamp(0, (image.width - 1).max(0));
+	let y = r.y.clamp(0, (image.height - 1).max(0));
+	let w = r.width.clamp(1, (image.width - x).max(1)) as usize;
+	let h = r.height.clamp(1, (image.height - y).max(1)) as usize;
+	let stride = image.width.max(0) as usize * 4;
+	let mut data = Vec::with_capacity(w * h * 4);
+	for row in y as usize..y as usize + h {
+		let start = row * stride + x as usize * 4;
+		data.extend_from_slice(&image.data[start..start + w * 4]);
+	}
+	Image { width: w as i32, height: h as i32, data }
+}
+
+// 兼容入口：只需要图像时使用
 pub fn capture_monitor_image(monitor: &MonitorInfo) -> Result<Image, String> {
-	// 目前桥接到 MonitorInfo::screen_shot()
-	let img = monitor.screen_shot()?;
-	debug!("[capture_monitor_image] got buffer {}x{} ({} bytes)", img.width, img.height, img.data.len());
-	Ok(img.into())
+	capture_monitor(monitor).map(|o| o.image).map_err(String::from)
+}
+// 预热截图资源：提前创建 D3D 设备、duplication 与 staging texture，避免首帧承担创建延迟
+pub fn prewarm_capture(monitor: &MonitorInfo) -> Result<(), String> {
+	let start = std::time::Instant::now();
+	unsafe {
+		let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+	}
+	let manager = DirectXResourceManager::get_instance(monitor.id);
+	let mut mgr = lock_or_recover(&manager, "directx_manager");
+	mgr.initialize()?;
+	// duplication 可能基于输出适配器重建设备，因此 staging texture 放在其后创建
+	mgr.ensure_output_duplication(monitor.id, monitor.x, monitor.y, monitor.width, monitor.height)?;
+	mgr.ensure_staging_texture(monitor.width, monitor.height)?;
+	info!("[perf] prewarm_capture {} ms (monitor {})", start.elapsed().as_millis(), monitor.id);
+	Ok(())
+}
+
+// 停止监控时释放 DirectX 截图资源（设备、staging texture、duplication 与输出缓冲区），空闲时不占用 GPU；
+// 各显示器的管理器一并移除，下次开始监控时按需重建
+pub fn release_capture_resources() {
+	if let Some(managers) = DIRECTX_MANAGERS.get() {
+		let managers: Vec<_> = lock_or_recover(managers, "directx_managers").drain().map(|(_, m)| m).collect();
+		// 截图线程可能仍持有管理器的引用，先释放其中的资源，不等引用计数归零
+		for manager in managers {
+			lock_or_recover(&manager, "directx_manager").release();
+		}
+		info!("[release_capture_resources] DirectX capture resources released");
+	}
+	super::wgc::release_all();
+}
+
+// 副显示器停止监控时只释放该显示器的截图资源并移除其管理器，其余显示器不受影响
+pub fn release_monitor_capture_resources(monitor_id: usize) {
+	let manager = DIRECTX_MANAGERS.get().and_then(|m| lock_or_recover(m, "directx_managers").remove(&monitor_id));
+	if let Some(manager) = manager {
+		lock_or_recover(&manager, "directx_manager").release();
+		info!("[release_capture_resources] DirectX capture resources of monitor {} released", monitor_id);
+	}
+	super::wgc::release(monitor_id);
+}
+
+// 输出缓冲区容量超过当前所需的倍数阈值时收缩
+const OUTPUT_BUFFER_SHRINK_RATIO: usize = 2;
+
+#[derive(Debug, PartialEq)]
+enum BufferFit {
+    Keep,
+    Grow,
+    Shrink,
+}
+
+// 输出缓冲区的调整方式：容量超过所需的 OUTPUT_BUFFER_SHRINK_RATIO 倍时收缩，长度不足时扩容，否则沿用
+fn output_buffer_fit(capacity: usize, len: usize, needed: usize) -> BufferFit {
+    if capacity > needed.saturating_mul(OUTPUT_BUFFER_SHRINK_RATIO) {
+        BufferFit::Shrink
+    } else if len < needed {
+        BufferFit::Grow
+    } else {
+        BufferFit::Keep
+    }
+}
+
+// GPU 降采样最多回读到第几级 mipmap（每级宽高减半）
+const MAX_MIP_LEVEL: u32 = 4;
+
+// 取倍率不低于 scale 的最深一级 mipmap：k = floor(log2(1/scale))，剩余部分由调用方在 CPU 上缩放
+fn mip_level_for_scale(scale: f32) -> u32 {
+    if !(scale > 0.0 && scale < 0.9999) {
+        return 0;
+    }
+    ((1.0 / scale).log2().floor().max(0.0) as u32).min(MAX_MIP_LEVEL)
+}
+
+// 输出与显示器匹配（允许 10px 误差）：DesktopCoordinates 已是旋转后的桌面坐标，
+// 但部分驱动对竖屏输出报告未旋转的宽高，因此旋转 90/270 度时宽高互换也视为匹配
+fn output_matches(desc: &DXGI_OUTPUT_DESC, x: i32, y: i32, width: i32, height: i32) -> bool {
+    let ox = desc.DesktopCoordinates.left;
+    let oy = desc.DesktopCoordinates.top;
+    let ow = desc.DesktopCoordinates.right - desc.DesktopCoordinates.left; // Windows 坐标右下为开区间
+    let oh = desc.DesktopCoordinates.bottom - desc.DesktopCoordinates.top;
+    if x != ox || y != oy {
+        return false;
+    }
+    let size_match = |w: i32, h: i32| (width - w).abs() <= 10 && (height - h).abs() <= 10;
+    let rotated = desc.Rotation == DXGI_MODE_ROTATION_ROTATE90 || desc.Rotation == DXGI_MODE_ROTATION_ROTATE270;
+    size_match(ow, oh) || (rotated && size_match(oh, ow))
+}
+
+// Desktop Duplication 的帧纹理保持面板原始方向；按输出旋转角度转换为桌面方向（与 MonitorInfo 一致）。
+// 未旋转时返回 None，调用方直接使用原数据
+fn rotate_to_desktop(src: &[u8], tex_w: usize, tex_h: usize, rotation: DXGI_MODE_ROTATION) -> Option<(Vec<u8>, usize, usize)> {
+    let (dw, dh) = if rotation == DXGI_MODE_ROTATION_ROTATE90 || rotation == DXGI_MODE_ROTATION_ROTATE270 {
+        (tex_h, tex_w)
+    } else if rotation == DXGI_MODE_ROTATION_ROTATE180 {
+        (tex_w, tex_h)
+    } else {
+        return None;
+    };
+    let mut dst = vec![0u8; dw * dh * 4];
+    for y in 0..dh {
+        for x in 0..dw {
+            // 桌面坐标 (x, y) 对应的纹理坐标
+            let (tx, ty) = if rotation == DXGI_MODE_ROTATION_ROTATE90 {
+                (dh - 1 - y, x)
+            } else if rotation == DXGI_MODE_ROTATION_ROTATE270 {
+                (y, dw - 1 - x)
+            } else {
+                (dw - 1 - x, dh - 1 - y)
+            };
+            let si = (ty * tex_w + tx) * 4;
+            let di = (y * dw + x) * 4;
+            dst[di..di + 4].copy_from_slice(&src[si..si + 4]);
+        }
+    }
+    Some((dst, dw, dh))
+}
+
+// 显示器局部区域（桌面方向）换算为帧纹理（面板方向）中的 (left, top, right, bottom)，并裁剪到纹理范围；
+// 与 rotate_to_desktop 的映射互逆，回读的子纹理按同样方式旋转即得到该区域
+fn region_to_texture(region: &Rect, tex_w: i32, tex_h: i32, rotation: DXGI_MODE_ROTATION) -> (i32, i32, i32, i32) {
+    let rotated = rotation == DXGI_MODE_ROTATION_ROTATE90 || rotation == DXGI_MODE_ROTATION_ROTATE270;
+    let (dw, dh) = if rotated { (tex_h, tex_w) } else { (tex_w, tex_h) };
+    let x0 = region.x.clamp(0, dw);
+    let x1 = (region.x + region.width).clamp(x0, dw);
+    let y0 = region.y.clamp(0, dh);
+    let y1 = (region.y + region.height).clamp(y0, dh);
+    if rotation == DXGI_MODE_ROTATION_ROTATE90 {
+        (dh - y1, x0, dh - y0, x1)
+    } else if rotation == DXGI_MODE_ROTATION_ROTATE270 {
+        (y0, dw - x1, y1, dw - x0)
+    } else if rotation == DXGI_MODE_ROTATION_ROTATE180 {
+        (dw - x1, dh - y1, dw - x0, dh - y0)
+    } else {
+        (x0, y0, x1, y1)
+    }
+}
+
+// 已取得的帧：离开作用域时 ReleaseFrame。缓存的 duplication 上若有帧未释放，之后每次 AcquireNextFrame 都会失败，
+// 截图会一路退到较慢的回退方法，因此取得帧后的所有返回路径都经由该守卫释放
+struct AcquiredFrame<'a>(&'a IDXGIOutputDuplication);
+
+impl Drop for AcquiredFrame<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = self.0.ReleaseFrame();
+        }
+    }
 }
-// 全局 DirectX 资源管理器
-static DIRECTX_MANAGER: OnceLock<Arc<Mutex<DirectXResourceManager>>> = OnceLock::new();
+
+fn duplication_rotation(duplication: &IDXGIOutputDuplication) -> DXGI_MODE_ROTATION {
+    let mut desc = DXGI_OUTDUPL_DESC::default();
+    unsafe { duplication.GetDesc(&mut desc) };
+    desc.Rotation
+}
+
+// DuplicateOutput 重试：指数退避 + 抖动，避免显示切换后多路截图同时重试
+const DUPLICATE_RETRY_BASE_MS: u64 = 50;
+const DUPLICATE_RETRY_CAP_MS: u64 = 1000;
+
+// 第 attempt 次（从 0 开始）重试前的等待上限：base * 2^attempt，封顶 cap
+fn backoff_ceiling_ms(attempt: u32, base_ms: u64, cap_ms: u64) -> u64 {
+    base_ms.saturating_mul(1u64 << attempt.min(16)).min(cap_ms)
+}
+
+// 等抖动：在 [ceiling/2, ceiling] 内取值，保证最小间隔的同时打散并发重试
+fn backoff_delay(attempt: u32) -> std::time::Duration {
+    use std::hash::{BuildHasher, Hasher};
+    let ceiling = backoff_ceiling_ms(attempt, DUPLICATE_RETRY_BASE_MS, DUPLICATE_RETRY_CAP_MS);
+    let half = ceiling / 2;
+    let rnd = std::collections::hash_map::RandomState::new().build_hasher().finish();
+    std::time::Duration::from_millis(half + rnd % (ceiling - half + 1))
+}
+
+// 各截图方法共用的 DuplicateOutput 重试
+fn duplicate_output_with_backoff(
+    output1: &IDXGIOutput1,
+    device: &ID3D11Device,
+    max_retries: u32,
+    label: &str,
+) -> Result<IDXGIOutputDuplication, CaptureError> {
+    let mut attempt = 0;
+    loop {
+        match unsafe { output1.DuplicateOutput(device) } {
+            Ok(dup) => {
+                debug!("[{}] Output duplication created on attempt {}", label, attempt + 1);
+                return Ok(dup);
+            }
+            Err(e) => {
+                attempt += 1;
+                if attempt >= max_retries {
+                    return Err(CaptureError::Duplication(format!("DuplicateOutput failed after {} attempts: {e}", max_retries)));
+                }
+                let delay = backoff_delay(attempt - 1);
+                debug!("[{}] DuplicateOutput failed ({}), retry in {:?}", label, e, delay);
+                std::thread::sleep(delay);
+            }
+        }
+    }
+}
+
+// DirectX 资源管理器：每个显示器独立一份（设备、staging texture、输出缓冲区与锁），
+// 不同显示器的截图互不争用同一把锁
+static DIRECTX_MANAGERS: OnceLock<Mutex<HashMap<usize, Arc<Mutex<DirectXResourceManager>>>>> = OnceLock::new();
 
 	struct DirectXResourceManager {
     device: Option<ID3D11Device>,
@@ -43,11 +364,24 @@ static DIRECTX_MANAGER: OnceLock<Arc<Mutex<DirectXResourceManager>>> = OnceLock:
     // 为每个监视器缓存 duplication 以避免每帧重建
     duplications: HashMap<usize, CachedDuplication>,
     last_image_valid: bool,
+    // 上一帧在输出缓冲区中的尺寸与 mip 级别（超时复用上一帧时须与本次请求的级别一致）
+    last_image_size: (i32, i32),
+    last_image_level: u32,
+    // 上一帧对应的截图区域（x, y, 宽, 高），整屏为 None
+    last_image_region: Option<(i32, i32, i32, i32)>,
+    // GPU 降采样用的 mipmap 纹理及其 SRV，键为 (宽, 高, 级数)
+    mip_texture: Option<ID3D11Texture2D>,
+    mip_srv: Option<ID3D11ShaderResourceView>,
+    mip_key: (i32, i32, u32),
 }
 
 #[derive(Clone)]
 struct CachedDuplication {
     duplication: IDXGIOutputDuplication,
+    // 匹配到的输出：访问丢失时直接基于它重建 duplication，无需重新枚举适配器/输出
+    output: IDXGIOutput1,
+    // 新建后尚未取过帧（首帧可能还没有桌面图像）
+    fresh: bool,
     x: i32,
     y: i32,
     width: i32,
@@ -66,16 +400,24 @@ impl DirectXResourceManager {
             last_height: 0,
             duplications: HashMap::new(),
             last_image_valid: false,
+            last_image_size: (0, 0),
+            last_image_level: 0,
+            last_image_region: None,
+            mip_texture: None,
+            mip_srv: None,
+            mip_key: (0, 0, 0),
         }
     }
     
-    fn get_instance() -> Arc<Mutex<DirectXResourceManager>> {
-        DIRECTX_MANAGER.get_or_init(|| {
-            Arc::new(Mutex::new(DirectXResourceManager::new()))
-        }).clone()
+    fn get_instance(monitor_id: usize) -> Arc<Mutex<DirectXResourceManager>> {
+        let managers = DIRECTX_MANAGERS.get_or_init(|| Mutex::new(HashMap::new()));
+        lock_or_recover(managers, "directx_managers")
+            .entry(monitor_id)
+            .or_insert_with(|| Arc::new(Mutex::new(DirectXResourceManager::new())))
+            .clone()
     }
     
-    fn initialize(&mut self) -> Result<(), String> {
+    fn initialize(&mut self) -> Result<(), CaptureError> {
         if self.is_initialized {
             return Ok(());
         }
@@ -98,7 +440,7 @@ impl DirectXResourceManager {
             );
             
             if hr.is_err() || device.is_none() || context.is_none() {
-                return Err("Failed to create D3D11 device".to_string());
+                return Err(CaptureError::Device("D3D11CreateDevice failed".to_string()));
             }
             
             self.device = device;
@@ -111,7 +453,7 @@ impl DirectXResourceManager {
         Ok(())
     }
     
-    fn ensure_staging_texture(&mut self, width: i32, height: i32) -> Result<(), String> {
+    fn ensure_staging_texture(&mut self, width: i32, height: i32) -> Result<(), CaptureError> {
         // 如果尺寸没变，直接返回
         if self.last_width == width && self.last_height == height && self.staging_texture.is_some() {
             return Ok(());
@@ -135,17 +477,15 @@ impl DirectXResourceManager {
                 
                 let mut staging_texture: Option<ID3D11Texture2D> = None;
                 device.CreateTexture2D(&desc, None, Some(&mut staging_texture))
-                    .map_err(|e| format!("Failed to create staging texture: {}", e))?;
+                    .map_err(|e| CaptureError::Readback(format!("Failed to create staging texture: {}", e)))?;
                 
                 self.staging_texture = staging_texture;
                 self.last_width = width;
                 self.last_height = height;
                 
-                // 预分配输出缓冲区
+                // 预分配输出缓冲区（分辨率明显变小时同时收缩）
                 let buffer_size = (width * height * 4) as usize;
-                if self.output_buffer.len() < buffer_size {
-                    self.output_buffer.resize(buffer_size, 0);
-                }
+                self.fit_output_buffer(buffer_size);
                 
                 info!("[DirectXResourceManager] Created staging texture {}x{}", width, height);
             }
@@ -154,6 +494,58 @@ impl DirectXResourceManager {
         Ok(())
     }
     
+    // 创建/复用 mipmap 纹理（levels 级，含原始尺寸的第 0 级），用于 GPU 降采样
+    fn ensure_mip_texture(&mut self, width: i32, height: i32, levels: u32, format: DXGI_FORMAT) -> Result<(), CaptureError> {
+        if self.mip_key == (width, height, levels) && self.mip_texture.is_some() && self.mip_srv.is_some() {
+            return Ok(());
+        }
+        let device = self.device.as_ref().ok_or_else(|| CaptureError::Device("device not available".to_string()))?;
+        unsafe {
+            let mut desc = D3D11_TEXTURE2D_DESC::default();
+            desc.Width = width as u32;
+            desc.Height = height as u32;
+            desc.MipLevels = levels;
+            desc.ArraySize = 1;
+            desc.Format = format;
+            desc.SampleDesc.Count = 1;
+            desc.SampleDesc.Quality = 0;
+            desc.Usage = D3D11_USAGE_DEFAULT;
+            desc.BindFlags = (D3D11_BIND_RENDER_TARGET.0 | D3D11_BIND_SHADER_RESOURCE.0) as u32;
+            desc.CPUAccessFlags = 0;
+            desc.MiscFlags = D3D11_RESOURCE_MISC_GENERATE_MIPS.0 as u32;
+
+            let mut texture: Option<ID3D11Texture2D> = None;
+            device.CreateTexture2D(&desc, None, Some(&mut texture))
+                .map_err(|e| CaptureError::Readback(format!("Failed to create mip texture: {}", e)))?;
+            let texture = texture.ok_or_else(|| CaptureError::Readback("mip texture not created".to_string()))?;
+            let mut srv: Option<ID3D11ShaderResourceView> = None;
+            device.CreateShaderResourceView(&texture, None, Some(&mut srv))
+                .map_err(|e| CaptureError::Readback(format!("Failed to create mip SRV: {}", e)))?;
+
+            self.mip_texture = Some(texture);
+            self.mip_srv = srv;
+            self.mip_key = (width, height, levels);
+            info!("[DirectXResourceManager] Created mip texture {}x{} ({} levels)", width, height, levels);
+        }
+        Ok(())
+    }
+
+    // 释放全部 COM 资源与缓冲区，下次截图时由 initialize/ensure_* 惰性重建
+    fn release(&mut self) {
+        self.duplications.clear();
+        self.staging_texture = None;
+        self.mip_texture = None;
+        self.mip_srv = None;
+        self.mip_key = (0, 0, 0);
+        self.context = None;
+        self.device = None;
+        self.output_buffer = Vec::new();
+        self.is_initialized = false;
+        self.last_width = 0;
+        self.last_height = 0;
+        self.last_image_valid = false;
+    }
+
     fn get_device(&self) -> Option<&ID3D11Device> {
         self.device.as_ref()
     }
@@ -170,10 +562,29 @@ impl DirectXResourceManager {
         &mut self.output_buffer
     }
 
-    unsafe fn recreate_device_for_adapter(&mut self, adapter1: &IDXGIAdapter1) -> Result<(), String> {
+    // 调整输出缓冲区至 needed：不足则扩容；容量超过所需的 OUTPUT_BUFFER_SHRINK_RATIO 倍时收缩，
+    // 避免从 4K 切换到小屏后仍常驻大块内存
+    fn fit_output_buffer(&mut self, needed: usize) {
+        match output_buffer_fit(self.output_buffer.capacity(), self.output_buffer.len(), needed) {
+            BufferFit::Shrink => {
+                info!(
+                    "[DirectXResourceManager] Shrinking output buffer {} -> {} bytes",
+                    self.output_buffer.capacity(),
+                    needed
+                );
+                self.output_buffer = vec![0u8; needed];
+                self.output_buffer.shrink_to_fit();
+                self.last_image_valid = false;
+            }
+            BufferFit::Grow => self.output_buffer.resize(needed, 0),
+            BufferFit::Keep => {}
+        }
+    }
+
+    unsafe fn recreate_device_for_adapter(&mut self, adapter1: &IDXGIAdapter1) -> Result<(), CaptureError> {
         let adapter = adapter1
             .cast::<IDXGIAdapter>()
-            .map_err(|e| format!("IDXGIAdapter cast failed: {e}"))?;
+            .map_err(|e| CaptureError::Device(format!("IDXGIAdapter cast failed: {e}")))?;
 
         let mut device: Option<ID3D11Device> = None;
         let mut context: Option<ID3D11DeviceContext> = None;
@@ -189,7 +600,7 @@ impl DirectXResourceManager {
             Some(&mut context),
         );
         if hr.is_err() || device.is_none() || context.is_none() {
-            return Err("Failed to create D3D11 device for adapter".to_string());
+            return Err(CaptureError::Device("D3D11CreateDevice failed for adapter".to_string()));
         }
 
         self.device = device;
@@ -197,6 +608,9 @@ impl DirectXResourceManager {
         self.is_initialized = true;
         // 失效旧资源与缓存
         self.staging_texture = None;
+        self.mip_texture = None;
+        self.mip_srv = None;
+        self.mip_key = (0, 0, 0);
         self.last_width = 0;
         self.last_height = 0;
         self.duplications.clear();
@@ -210,7 +624,7 @@ impl DirectXResourceManager {
         y: i32,
         width: i32,
         height: i32,
-    ) -> Result<IDXGIOutputDuplication, String> {
+    ) -> Result<IDXGIOutputDuplication, CaptureError> {
         // 命中缓存则直接返回
         if let Some(cached) = self.duplications.get(&monitor_id) {
             if cached.x == x && cached.y == y && cached.width == width && cached.height == height {
@@ -219,7 +633,7 @@ impl DirectXResourceManager {
         }
 
         unsafe {
-            let factory: IDXGIFactory1 = CreateDXGIFactory1().map_err(|e| format!("CreateDXGIFactory1 failed: {e}"))?;
+            let factory: IDXGIFactory1 = CreateDXGIFactory1().map_err(|e| CaptureError::Device(format!("CreateDXGIFactory1 failed: {e}")))?;
             let mut sel_output: Option<IDXGIOutput> = None;
             let mut sel_adapter: Option<IDXGIAdapter1> = None;
             let mut i = 0;
@@ -227,15 +641,7 @@ impl DirectXResourceManager {
                 let mut j = 0;
                 while let Ok(o) = a.EnumOutputs(j) {
                     let desc = o.GetDesc().unwrap();
-                    let ox = desc.DesktopCoordinates.left;
-                    let oy = desc.DesktopCoordinates.top;
-                    let ow = desc.DesktopCoordinates.right - desc.DesktopCoordinates.left; // Windows 坐标右下为开区间
-                    let oh = desc.DesktopCoordinates.bottom - desc.DesktopCoordinates.top;
-
-                    let width_match = (width - ow).abs() <= 10;
-                    let height_match = (height - oh).abs() <= 10;
-
-                    if x == ox && y == oy && width_match && height_match {
+                    if output_matches(&desc, x, y, width, height) {
                         sel_output = Some(o);
                         sel_adapter = Some(a.clone());
                         break 'outer;
@@ -245,12 +651,12 @@ impl DirectXResourceManager {
                 i += 1;
             }
 
-            let output = sel_output.ok_or_else(|| "No matching adapter/output found".to_string())?;
-            let adapter1 = sel_adapter.ok_or_else(|| "No adapter for output".to_string())?;
+            let output = sel_output.ok_or(CaptureError::NoMatchingOutput)?;
+            let adapter1 = sel_adapter.ok_or(CaptureError::NoMatchingOutput)?;
 
             // 先尝试用现有设备创建 duplication；若参数错误，再基于该适配器重建设备并重试一次
-            let output1: IDXGIOutput1 = output.cast().map_err(|e| format!("Output1 cast failed: {e}"))?;
-            let mut ensure_device = |mgr: &mut DirectXResourceManager| -> Result<ID3D11Device, String> {
+            let output1: IDXGIOutput1 = output.cast().map_err(|e| CaptureError::Duplication(format!("Output1 cast failed: {e}")))?;
+            let mut ensure_device = |mgr: &mut DirectXResourceManager| -> Result<ID3D11Device, CaptureError> {
                 if let Some(d) = &mgr.device { return Ok(d.clone()); }
                 mgr.recreate_device_for_adapter(&adapter1)?;
                 Ok(mgr.device.as_ref().unwrap().clone())
@@ -264,26 +670,90 @@ impl DirectXResourceManager {
                     if code.0 as u32 == 0x80070057 { // E_INVALIDARG / 参数错误：设备与输出不匹配
                         self.recreate_device_for_adapter(&adapter1)?;
                         device = self.device.as_ref().unwrap().clone();
-                        output1.DuplicateOutput(&device)
+                        duplicate_output_with_backoff(&output1, &device, 3, "ensure_output_duplication")
                     } else {
-                        Err(e)
+                        Err(CaptureError::Duplication(format!("DuplicateOutput failed: {e}")))
                     }
                 }
-            }.map_err(|e| format!("DuplicateOutput failed: {e}"))?;
+            }?;
 
-            let cached = CachedDuplication { duplication: duplication.clone(), x, y, width, height };
+            let cached = CachedDuplication { duplication: duplication.clone(), output: output1, fresh: true, x, y, width, height };
             self.duplications.insert(monitor_id, cached);
             Ok(duplication)
         }
     }
+
+    // 访问丢失（模式切换、UAC 安全桌面、全屏独占等）后基于缓存的输出重建 duplication；
+    // 调用方须先释放手中旧的 duplication。失败时移除缓存，下次由 ensure_output_duplication 完整重建
+    fn reacquire_duplication(&mut self, monitor_id: usize) -> Result<IDXGIOutputDuplication, CaptureError> {
+        let cached = self.duplications.remove(&monitor_id).ok_or_else(|| CaptureError::Duplication("no cached duplication".to_string()))?;
+        let CachedDuplication { duplication, output, x, y, width, height, .. } = cached;
+        drop(duplication);
+        let device = self.device.clone().ok_or_else(|| CaptureError::Device("device not available".to_string()))?;
+        let duplication = duplicate_output_with_backoff(&output, &device, 3, "reacquire_duplication")?;
+        info!("[DirectXResourceManager] Re-acquired output duplication for monitor {}", monitor_id);
+        self.duplications.insert(monitor_id, CachedDuplication { duplication: duplication.clone(), output, fresh: true, x, y, width, height });
+        Ok(duplication)
+    }
+
+    // 读取并清除“新建后首次取帧”标记
+    fn take_fresh(&mut self, monitor_id: usize) -> bool {
+        match self.duplications.get_mut(&monitor_id) {
+            Some(cached) => std::mem::replace(&mut cached.fresh, false),
+            None => false,
+        }
+    }
+}
+
+// AcquireNextFrame 的默认等待/ms：optimized 逐级放宽，standard 只等一次，alternative 每次尝试等待较久
+const OPTIMIZED_ACQUIRE_STEPS_MS: [u32; 3] = [16, 33, 50];
+const STANDARD_ACQUIRE_TIMEOUT_MS: u32 = 16;
+const ALTERNATIVE_ACQUIRE_TIMEOUT_MS: u32 = 1000;
+
+// monitoring.acquire_timeout_ms：配置后三种 DirectX 方法统一使用该等待时长，0 或缺省时使用各自的默认值
+fn configured_acquire_timeout_ms() -> Option<u32> {
+    crate::config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.acquire_timeout_ms)
+        .filter(|t| *t > 0)
 }
 
+// optimized 的逐级等待：配置了超时时按 1:2:3 分三级，合计为该值
+fn optimized_acquire_steps() -> [u32; 3] {
+    match configured_acquire_timeout_ms() {
+        Some(t) => {
+            let first = (t / 6).max(1);
+            let second = (t / 3).max(1);
+            [first, second, t.saturating_sub(first + second).max(1)]
+        }
+        None => OPTIMIZED_ACQUIRE_STEPS_MS,
+    }
+}
+
+// 自适应等待下一帧：依次放宽超时，全部超时返回 Ok(None)，其他错误原样返回
+unsafe fn acquire_next_frame(
+    duplication: &IDXGIOutputDuplication,
+    frame_info: &mut DXGI_OUTDUPL_FRAME_INFO,
+) -> windows::core::Result<Option<IDXGIResource>> {
+    for timeout in optimized_acquire_steps() {
+        let mut resource = None;
+        match duplication.AcquireNextFrame(timeout, frame_info, &mut resource) {
+            Ok(_) => return Ok(resource),
+            Err(e) if e.code() == DXGI_ERROR_WAIT_TIMEOUT => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(None)
+}
+
+// DirectX 三种方法与 WGC 参与自适应选择；Gdi 为兜底方法，仅用于结果归因
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-enum CaptureMethod { Optimized, Standard, Alternative }
+pub enum CaptureMethod { Optimized, Wgc, Standard, Alternative, Gdi }
 
 #[derive(Clone, Debug)]
 struct CaptureStats {
     consec_optimized: u32,
+    consec_wgc: u32,
     consec_standard: u32,
     consec_alternative: u32,
     preferred: CaptureMethod,
@@ -293,6 +763,7 @@ impl Default for CaptureStats {
     fn default() -> Self {
         Self {
             consec_optimized: 0,
+            consec_wgc: 0,
             consec_standard: 0,
             consec_alternative: 0,
             preferred: CaptureMethod::Optimized,
@@ -307,11 +778,74 @@ fn state_map() -> &'static Mutex<HashMap<usize, CaptureStats>> {
     CAPTURE_STATE.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
+// 自适应截图方法统计的只读快照，供排查“卡在某个慢方法”时查看
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureStatsInfo {
+    pub consec_optimized: u32,
+    pub consec_wgc: u32,
+    pub consec_standard: u32,
+    pub consec_alternative: u32,
+    pub preferred: &'static str,
+    // 下一帧将首先尝试的方法
+    pub start_method: &'static str,
+}
+
+// 各显示器的截图方法统计，按显示器 id 升序
+pub fn capture_stats() -> Vec<(usize, CaptureStatsInfo)> {
+    // 先复制快照再计算起始方法（choose_start_method 自行加锁）
+    let mut snapshot: Vec<(usize, CaptureStats)> = lock_or_recover(state_map(), "capture_state")
+        .iter()
+        .map(|(id, s)| (*id, s.clone()))
+        .collect();
+    snapshot.sort_unstable_by_key(|(id, _)| *id);
+    snapshot
+        .into_iter()
+        .map(|(id, stats)| {
+            (id, CaptureStatsInfo {
+                consec_optimized: stats.consec_optimized,
+                consec_wgc: stats.consec_wgc,
+                consec_standard: stats.consec_standard,
+                consec_alternative: stats.consec_alternative,
+                preferred: stats.preferred.name(),
+                start_method: choose_start_method(id).name(),
+            })
+        })
+        .collect()
+}
+
+// 清空指定显示器（None 为全部）的统计，下一帧重新从 Optimized 开始学习；返回清除的条目数
+pub fn reset_capture_stats(monitor_id: Option<usize>) -> usize {
+    let mut map = lock_or_recover(state_map(), "capture_state");
+    let removed = match monitor_id {
+        Some(id) => map.remove(&id).map(|_| 1).unwrap_or(0),
+        None => {
+            let n = map.len();
+            map.clear();
+            n
+        }
+    };
+    info!("[capture_state] reset stats for {:?} ({} entries)", monitor_id, removed);
+    removed
+}
+
+impl CaptureMethod {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CaptureMethod::Optimized => "optimized",
+            CaptureMethod::Wgc => "wgc",
+            CaptureMethod::Standard => "standard",
+            CaptureMethod::Alternative => "alternative",
+            CaptureMethod::Gdi => "gdi",
+        }
+    }
+}
+
 fn choose_start_method(monitor_id: usize) -> CaptureMethod {
-    let map = state_map().lock().ok();
-    if let Some(m) = map.and_then(|m| m.get(&monitor_id).cloned()) {
+    let stats = lock_or_recover(state_map(), "capture_state").get(&monitor_id).cloned();
+    if let Some(m) = stats {
         // 按性能优先选择达到阈值的方法
         if m.consec_optimized >= SUCCESS_THRESHOLD { return CaptureMethod::Optimized; }
+        if m.consec_wgc >= SUCCESS_THRESHOLD { return CaptureMethod::Wgc; }
         if m.consec_standard >= SUCCESS_THRESHOLD { return CaptureMethod::Standard; }
         if m.consec_alternative >= SUCCESS_THRESHOLD { return CaptureMethod::Alternative; }
         // 否则使用上次首选，默认 Optimized
@@ -321,23 +855,30 @@ fn choose_start_method(monitor_id: usize) -> CaptureMethod {
 }
 
 fn record_result(monitor_id: usize, method: CaptureMethod, success: bool) {
-    let mut map = match state_map().lock() { Ok(g) => g, Err(_) => return };
+    let mut map = lock_or_recover(state_map(), "capture_state");
     let entry = map.entry(monitor_id).or_insert_with(|| CaptureStats { preferred: CaptureMethod::Optimized, ..Default::default() });
     // 更新连续计数
     match method {
         CaptureMethod::Optimized => {
             entry.consec_optimized = if success { entry.consec_optimized.saturating_add(1) } else { 0 };
         }
+        CaptureMethod::Wgc => {
+            entry.consec_wgc = if success { entry.consec_wgc.saturating_add(1) } else { 0 };
+        }
         CaptureMethod::Standard => {
             entry.consec_standard = if success { entry.consec_standard.saturating_add(1) } else { 0 };
         }
         CaptureMethod::Alternative => {
             entry.consec_alternative = if success { entry.consec_alternative.saturating_add(1) } else { 0 };
         }
+        // GDI 不参与自适应选择
+        CaptureMethod::Gdi => return,
     }
     // 依据阈值提升首选项（按性能从高到低）
     entry.preferred = if entry.consec_optimized >= SUCCESS_THRESHOLD {
         CaptureMethod::Optimized
+    } else if entry.consec_wgc >= SUCCESS_THRESHOLD {
+        CaptureMethod::Wgc
     } else if entry.consec_standard >= SUCCESS_THRESHOLD {
         CaptureMethod::Standard
     } else if entry.consec_alternative >= SUCCESS_THRESHOLD {
@@ -348,48 +889,76 @@ fn record_result(monitor_id: usize, method: CaptureMethod, success: bool) {
     };
 
     debug!(
-        "[capture_state] monitor={} meth={:?} ok={} consec: opt={} std={} alt={} prefer={:?}",
+        "[capture_state] monitor={} meth={:?} ok={} consec: opt={} wgc={} std={} alt={} prefer={:?}",
         monitor_id,
         method,
         success,
         entry.consec_optimized,
+        entry.consec_wgc,
         entry.consec_standard,
         entry.consec_alternative,
         entry.preferred
     );
 }
 
+// 各显示器最近一次回退到 GDI 的原因：原因变化时才输出 warn，避免逐帧刷屏；DirectX 恢复时输出一次 info
+static FALLBACK_REASONS: OnceLock<Mutex<HashMap<usize, String>>> = OnceLock::new();
+
+fn note_fallback_reason(monitor_id: usize, reason: Option<&CaptureError>) {
+    let mut map = lock_or_recover(FALLBACK_REASONS.get_or_init(|| Mutex::new(HashMap::new())), "fallback_reasons");
+    match reason {
+        Some(e) => {
+            let text = e.to_string();
+            if map.get(&monitor_id) != Some(&text) {
+                warn!("[screen_shot] monitor {} falling back to GDI ({}): {}", monitor_id, e.hint(), text);
+                map.insert(monitor_id, text);
+            }
+        }
+        None => {
+            if map.remove(&monitor_id).is_some() {
+                info!("[screen_shot] monitor {} DirectX capture recovered", monitor_id);
+            }
+        }
+    }
+}
+
 impl MonitorInfo {
-    pub fn screen_shot(&self) -> Result<Image, String> {
+    // 截图并返回实际使用的方法与图像倍率：优先 DirectX，失败或空白时回退 GDI（GDI 始终为原分辨率）
+    // region 为已裁剪到显示器范围的局部区域，返回的图像只含该区域
+    pub fn capture(&self, scale: f32, region: Option<&Rect>) -> Result<(Image, CaptureMethod, f32, Option<FramePresent>), CaptureError> {
         let start = std::time::Instant::now();
         // 移除逐帧 DPI 感知设置，避免反复 E_ACCESSDENIED
         
         // 首先尝试 DirectX 方法
-        match self.screen_shot_directx() {
-            Ok(image) => {
+        let mut errors = match self.screen_shot_directx(scale, region) {
+            Ok((image, method, scale, present)) => {
                 // 检查是否获取到有效内容（不是全零）
                 if self.has_valid_content(&image) {
                     debug!("[screen_shot] DirectX method succeeded");
-                    return Ok(image);
+                    note_fallback_reason(self.id, None);
+                    return Ok((image, method, scale, present));
                 } else {
                     debug!("[screen_shot] DirectX method returned blank content, using GDI fallback");
+                    vec![(method, CaptureError::Blank)]
                 }
             }
-            Err(e) => {
-                debug!("[screen_shot] DirectX method failed: {}, using GDI fallback", e);
-            }
-        }
+            Err(errors) => errors,
+        };
+        note_fallback_reason(self.id, Some(&CaptureError::AllFailed(errors.clone())));
 
         // 如果 DirectX 失败或返回空白内容，使用 GDI 方法
-        let result = self.screen_shot_gdi();
+        let result = self.screen_shot_gdi(region);
         let elapsed = start.elapsed();
         info!("[perf] screen_shot {} ms", elapsed.as_millis());
-        result
+        match result {
+            Ok(image) => Ok((image, CaptureMethod::Gdi, 1.0, None)),
+            Err(e) => {
+                errors.push((CaptureMethod::Gdi, CaptureError::Gdi(e)));
+                Err(CaptureError::AllFailed(errors))
+            }
+        }
     }
 
-    #[allow(dead_code)]
-    fn set_dpi_awareness(&self) { /* no-op: handled at process init or by manifest */ }
-
     fn has_valid_content(&self, image: &Image) -> bool {
         // 采样若干点判断是否为“近乎纯色”或“全零”帧
         let width = image.width.max(1) as usize;
@@ -426,13 +995,18 @@ impl MonitorInfo {
         non_zero > 0 && different_colors > 0
     }
 
-    fn screen_shot_gdi(&self) -> Result<Image, String> {
+    fn screen_shot_gdi(&self, region: Option<&Rect>) -> Result<Image, String> {
+        // 源矩形（虚拟桌面坐标）：有区域时只 BitBlt 该区域
+        let (src_x, src_y, width, height) = match region {
+            Some(r) => (self.x + r.x, self.y + r.y, r.width, r.height),
+            None => (self.x, self.y, self.width, self.height),
+        };
         unsafe {
             let start_time = std::time::Instant::now();
             
-            // 获取桌面窗口的DC
-            let desktop = GetDesktopWindow();
-            let dc = GetDC(Some(desktop));
+            // 取整个虚拟桌面的屏幕 DC：坐标系原点为主显示器左上角，位于主显示器左/上方的显示器坐标为负，
+            // 与 MonitorInfo 的 x/y 一致，BitBlt 可直接以其为源坐标
+            let dc = GetDC(None);
             if dc.is_invalid() {
                 return Err("Failed to get desktop DC".to_string());
             }
@@ -440,18 +1014,18 @@ impl MonitorInfo {
             // 创建兼容的DC和位图
             let mem_dc = CreateCompatibleDC(Some(dc));
             if mem_dc.is_invalid() {
-                let released = ReleaseDC(Some(desktop), dc);
+                let released = ReleaseDC(None, dc);
                 if released == 0 {
                     debug!("[screen_shot_gdi] ReleaseDC failed when mem_dc invalid");
                 }
                 return Err("Failed to create compatible DC".to_string());
             }
 
-            let bitmap = CreateCompatibleBitmap(dc, self.width, self.height);
+            let bitmap = CreateCompatibleBitmap(dc, width, height);
             if bitmap.is_invalid() {
                 let ok = DeleteDC(mem_dc).as_bool();
                 if !ok { debug!("[screen_shot_gdi] DeleteDC failed after CreateCompatibleBitmap error"); }
-                let released = ReleaseDC(Some(desktop), dc);
+                let released = ReleaseDC(None, dc);
                 if released == 0 { debug!("[screen_shot_gdi] ReleaseDC failed after CreateCompatibleBitmap error"); }
                 return Err("Failed to create compatible bitmap".to_string());
             }
@@ -463,7 +1037,7 @@ impl MonitorInfo {
                 if !ok1 { debug!("[screen_shot_gdi] DeleteObject failed after SelectObject error"); }
                 let ok2 = DeleteDC(mem_dc).as_bool();
                 if !ok2 { debug!("[screen_shot_gdi] DeleteDC failed after SelectObject error"); }
-                let released = ReleaseDC(Some(desktop), dc);
+                let released = ReleaseDC(None, dc);
                 if released == 0 { debug!("[screen_shot_gdi] ReleaseDC failed after SelectObject error"); }
                 return Err("Failed to select bitmap".to_string());
             }
@@ -473,11 +1047,11 @@ impl MonitorInfo {
                 mem_dc,
                 0,
                 0,
-                self.width,
-                self.height,
+                width,
+                height,
                 Some(dc),
-                self.x,
-                self.y,
+                src_x,
+                src_y,
                 SRCCOPY,
             );
 
@@ -487,7 +1061,7 @@ impl MonitorInfo {
                 if !ok1 { debug!("[screen_shot_gdi] DeleteObject failed after BitBlt error"); }
                 let ok2 = DeleteDC(mem_dc).as_bool();
                 if !ok2 { debug!("[screen_shot_gdi] DeleteDC failed after BitBlt error"); }
-                let released = ReleaseDC(Some(desktop), dc);
+                let released = ReleaseDC(None, dc);
                 if released == 0 { debug!("[screen_shot_gdi] ReleaseDC failed after BitBlt error"); }
                 return Err("BitBlt failed".to_string());
             }
@@ -496,8 +1070,8 @@ impl MonitorInfo {
             let mut bmi = BITMAPINFO {
                 bmiHeader: BITMAPINFOHEADER {
                     biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
-                    biWidth: self.width,
-                    biHeight: -self.height, // 负值表示自上而下
+                    biWidth: width,
+                    biHeight: -height, // 负值表示自上而下
                     biPlanes: 1,
                     biBitCount: 32,
                     biCompression: BI_RGB.0,
@@ -511,7 +1085,7 @@ impl MonitorInfo {
             };
 
             // 分配缓冲区
-            let buffer_size = (self.width * self.height * 4) as usize;
+            let buffer_size = (width * height * 4) as usize;
             let mut buffer = vec![0u8; buffer_size];
 
             // 获取位图数据
@@ -519,7 +1093,7 @@ impl MonitorInfo {
                 mem_dc,
                 bitmap,
                 0,
-                self.height as u32,
+                height as u32,
                 Some(buffer.as_mut_ptr() as *mut _),
                 &mut bmi,
                 DIB_RGB_COLORS,
@@ -531,7 +1105,7 @@ impl MonitorInfo {
                 if !ok1 { debug!("[screen_shot_gdi] DeleteObject failed after GetDIBits error"); }
                 let ok2 = DeleteDC(mem_dc).as_bool();
                 if !ok2 { debug!("[screen_shot_gdi] DeleteDC failed after GetDIBits error"); }
-                let released = ReleaseDC(Some(desktop), dc);
+                let released = ReleaseDC(None, dc);
                 if released == 0 { debug!("[screen_shot_gdi] ReleaseDC failed after GetDIBits error"); }
                 return Err("GetDIBits failed".to_string());
             }
@@ -542,162 +1116,243 @@ impl MonitorInfo {
             if !ok1 { debug!("[screen_shot_gdi] DeleteObject failed during cleanup"); }
             let ok2 = DeleteDC(mem_dc).as_bool();
             if !ok2 { debug!("[screen_shot_gdi] DeleteDC failed during cleanup"); }
-            let released = ReleaseDC(Some(desktop), dc);
+            let released = ReleaseDC(None, dc);
             if released == 0 { debug!("[screen_shot_gdi] ReleaseDC failed during cleanup"); }
 
             let elapsed = start_time.elapsed();
-            debug!("[screen_shot_gdi] GDI screenshot completed in {:?}: {}x{}", elapsed, self.width, self.height);
+            debug!("[screen_shot_gdi] GDI screenshot completed in {:?}: {}x{}", elapsed, width, height);
 
             Ok(Image {
-                width: self.width,
-                height: self.height,
+                width,
+                height,
                 data: buffer,
             })
         }
     }
 
-    fn screen_shot_directx(&self) -> Result<Image, String> {
-        // 状态机：优先选择达到阈值的高性能方法；失败则向下回退
+    // 失败时按尝试顺序返回各方法的失败原因
+    fn screen_shot_directx(&self, scale: f32, region: Option<&Rect>) -> Result<(Image, CaptureMethod, f32, Option<FramePresent>), Vec<(CaptureMethod, CaptureError)>> {
+        // 状态机：优先选择达到阈值的高性能方法；失败则向下回退。
+        // WGC 位于 optimized 之后（混合显卡笔记本上 DXGI 可能间歇性返回空白帧），WGC 失败时仍回退到 DXGI 方法
         let start = choose_start_method(self.id);
         let mut order: Vec<CaptureMethod> = match start {
-            CaptureMethod::Optimized => vec![CaptureMethod::Optimized, CaptureMethod::Standard, CaptureMethod::Alternative],
-            CaptureMethod::Standard => vec![CaptureMethod::Standard, CaptureMethod::Alternative],
-            CaptureMethod::Alternative => vec![CaptureMethod::Alternative],
+            CaptureMethod::Optimized => vec![CaptureMethod::Optimized, CaptureMethod::Wgc, CaptureMethod::Standard, CaptureMethod::Alternative],
+            CaptureMethod::Wgc => vec![CaptureMethod::Wgc, CaptureMethod::Optimized, CaptureMethod::Standard, CaptureMethod::Alternative],
+            CaptureMethod::Standard => vec![CaptureMethod::Standard, CaptureMethod::Alternative, CaptureMethod::Wgc],
+            CaptureMethod::Alternative => vec![CaptureMethod::Alternative, CaptureMethod::Wgc],
+            CaptureMethod::Gdi => Vec::new(),
         };
         debug!("[screen_shot_directx] State start method: {:?}", start);
+        let mut errors: Vec<(CaptureMethod, CaptureError)> = Vec::new();
 
         for method in order.drain(..) {
             let res = match method {
                 CaptureMethod::Optimized => {
                     debug!("[screen_shot_directx] Trying optimized method");
-                    self.screen_shot_directx_optimized()
+                    self.screen_shot_directx_optimized(mip_level_for_scale(scale), region)
+                }
+                CaptureMethod::Wgc => {
+                    debug!("[screen_shot_directx] Trying WGC method");
+                    super::wgc::capture(self).map(|image| (crop_to_region(image, region), 1.0, None))
                 }
                 CaptureMethod::Standard => {
                     debug!("[screen_shot_directx] Trying standard method");
-                    self.screen_shot_directx_standard()
+                    self.screen_shot_directx_standard().map(|image| (crop_to_region(image, region), 1.0, None))
                 }
                 CaptureMethod::Alternative => {
                     debug!("[screen_shot_directx] Trying alternative method");
-                    self.screen_shot_directx_alternative()
+                    self.screen_shot_directx_alternative().map(|image| (crop_to_region(image, region), 1.0, None))
                 }
+                CaptureMethod::Gdi => continue,
             };
 
             match res {
-                Ok(image) => {
+                Ok((image, image_scale, present)) => {
                     let ok = self.has_valid_content(&image);
                     if ok {
                         record_result(self.id, method, true);
                         debug!("[screen_shot_directx] {:?} method succeeded", method);
-                        return Ok(image);
+                        return Ok((image, method, image_scale, present));
                     } else {
                         record_result(self.id, method, false);
                         debug!("[screen_shot_directx] {:?} method returned blank content", method);
+                        errors.push((method, CaptureError::Blank));
                         continue;
                     }
                 }
                 Err(e) => {
-                    record_result(self.id, method, false);
+                    // 等待超时只说明这段时间没有新帧，不计为方法失败，避免自适应选择因此降级
+                    if !matches!(e, CaptureError::Timeout) {
+                        record_result(self.id, method, false);
+                    }
                     debug!("[screen_shot_directx] {:?} method failed: {}", method, e);
+                    errors.push((method, e));
                     continue;
                 }
             }
         }
 
-        Err("All DirectX methods failed or returned blank".to_string())
+        Err(errors)
     }
 
     // 新增：优化的 DirectX 截图函数，使用资源管理器
-    fn screen_shot_directx_optimized(&self) -> Result<Image, String> {
+    // mip_level > 0 时先在 GPU 上生成 mipmap，只把该级（宽高各缩小 2^mip_level 倍）拷到 staging 回读，返回图像与实际倍率；
+    // 同时返回本帧的呈现信息，新建 duplication 后的首帧无从比较，返回 None。
+    // region 非空时只把该区域对应的子矩形拷到 staging 回读（D3D11_BOX），旋转输出按面板方向换算
+    fn screen_shot_directx_optimized(&self, mip_level: u32, region: Option<&Rect>) -> Result<(Image, f32, Option<FramePresent>), CaptureError> {
         unsafe {
             let start_time = std::time::Instant::now();
             
             // 获取资源管理器实例
-            let manager = DirectXResourceManager::get_instance();
+            let manager = DirectXResourceManager::get_instance(self.id);
             
             // 先确保资源管理器初始化（不提前克隆上下文，避免后续重建设备后变成悬空指针）
             {
-                let mut mgr = manager.lock().map_err(|e| format!("Failed to lock resource manager: {}", e))?;
+                let mut mgr = lock_or_recover(&manager, "directx_manager");
                 mgr.initialize()?;
             }
             
             // 复用缓存的 duplication，避免每帧重建（此步骤可能重建设备以匹配输出适配器）
             let duplication = {
-                let mut mgr = manager.lock().map_err(|e| format!("Failed to lock resource manager: {}", e))?;
+                let mut mgr = lock_or_recover(&manager, "directx_manager");
                 mgr.ensure_output_duplication(self.id, self.x, self.y, self.width, self.height)?
             };
             
             // 获取下一帧：自适应等待，若连续超时尝试复用上一帧
             let mut frame_info = DXGI_OUTDUPL_FRAME_INFO::default();
-            let mut resource = None;
-            let timeouts = [16u32, 33u32, 50u32];
-            let mut got = false;
-            for to in timeouts {
-                let hr = duplication.AcquireNextFrame(to, &mut frame_info, &mut resource);
-                match hr {
-                    Ok(_) => { got = true; break; }
-                    Err(e) => {
-                        let code = e.code();
-                        if code == DXGI_ERROR_WAIT_TIMEOUT { continue; }
-                        return Err(format!("AcquireNextFrame failed: 0x{:X}", code.0));
-                    }
-                }
+            let mut acquired = acquire_next_frame(&duplication, &mut frame_info);
+            // 访问丢失：重建 duplication 后重试一次，仍失败则交由上层回退到 standard/alternative/GDI
+            let access_lost = matches!(&acquired, Err(e) if e.code() == DXGI_ERROR_ACCESS_LOST);
+            let duplication = if access_lost {
+                debug!("[screen_shot_directx_optimized] Access lost, re-acquiring duplication");
+                drop(duplication);
+                let duplication = lock_or_recover(&manager, "directx_manager").reacquire_duplication(self.id)?;
+                acquired = acquire_next_frame(&duplication, &mut frame_info);
+                duplication
+            } else {
+                duplication
+            };
+            let mut resource = acquired.map_err(|e| CaptureError::Acquire(format!("AcquireNextFrame failed: 0x{:X}", e.code().0)))?;
+            // 新建 duplication 后的首帧可能尚无桌面图像（LastPresentTime 为 0，内容全黑）：丢弃该帧再取一次，
+            // 避免空白帧被计为 optimized 方法的失败
+            let fresh = lock_or_recover(&manager, "directx_manager").take_fresh(self.id);
+            if fresh && resource.is_some() && frame_info.LastPresentTime == 0 {
+                debug!("[screen_shot_directx_optimized] First frame after duplication has no image yet, retrying");
+                drop(resource.take());
+                let _ = duplication.ReleaseFrame();
+                resource = acquire_next_frame(&duplication, &mut frame_info)
+                    .map_err(|e| CaptureError::Acquire(format!("AcquireNextFrame failed: 0x{:X}", e.code().0)))?;
             }
-            if !got {
-                if let Ok(mgr) = manager.lock() {
-                    let need = (self.width as usize * self.height as usize * 4) as usize;
-                    if mgr.last_image_valid && mgr.output_buffer.len() >= need {
+            if resource.is_none() {
+                {
+                    let mgr = lock_or_recover(&manager, "directx_manager");
+                    let (last_w, last_h) = mgr.last_image_size;
+                    let need = (last_w.max(0) as usize * last_h.max(0) as usize * 4) as usize;
+                    let region_key = region.map(|r| (r.x, r.y, r.width, r.height));
+                    if mgr.last_image_valid && mgr.last_image_level == mip_level && mgr.last_image_region == region_key && need > 0 && mgr.output_buffer.len() >= need {
                         let image_data = mgr.output_buffer[..need].to_vec();
                         let elapsed = start_time.elapsed();
-                        debug!("[screen_shot_directx_optimized] Reuse last frame after timeouts in {:?}: {}x{}", elapsed, self.width, self.height);
-                        return Ok(Image { width: self.width, height: self.height, data: image_data });
+                        debug!("[screen_shot_directx_optimized] Reuse last frame after timeouts in {:?}: {}x{}", elapsed, last_w, last_h);
+                        let scale = last_w as f32 / region.map(|r| r.width).unwrap_or(self.width).max(1) as f32;
+                        // 超时说明期间没有新帧
+                        let present = FramePresent { accumulated_frames: 0, last_present_time: 0 };
+                        return Ok((Image { width: last_w, height: last_h, data: image_data }, scale, Some(present)));
                     }
                 }
-                return Err("AcquireNextFrame timeout".to_string());
+                return Err(CaptureError::Timeout);
             }
-            let resource = match resource { Some(r) => r, None => { return Err("AcquireNextFrame returned no resource".to_string()); } };
+            let resource = match resource { Some(r) => r, None => { return Err(CaptureError::Acquire("AcquireNextFrame returned no resource".to_string())); } };
+            // 声明在纹理与映射之前，任何返回路径上都在 Unmap 之后最后析构并 ReleaseFrame
+            let _frame = AcquiredFrame(&duplication);
             
             // 检查是否有累积帧
             if frame_info.AccumulatedFrames == 0 {
                 debug!("[screen_shot_directx_optimized] No accumulated frames");
             }
+            let present = (!fresh).then_some(FramePresent {
+                accumulated_frames: frame_info.AccumulatedFrames,
+                last_present_time: frame_info.LastPresentTime,
+            });
             
             // 按帧的实际尺寸创建/复用 staging texture
-            let tex: ID3D11Texture2D = resource.cast().map_err(|e| format!("Resource cast failed: {e}"))?;
+            let tex: ID3D11Texture2D = resource.cast().map_err(|e| CaptureError::Readback(format!("Resource cast failed: {e}")))?;
             let mut desc = windows::Win32::Graphics::Direct3D11::D3D11_TEXTURE2D_DESC::default();
             tex.GetDesc(&mut desc);
             let frame_w = desc.Width as i32;
             let frame_h = desc.Height as i32;
-            {
-                let mut mgr = manager.lock().map_err(|e| format!("Failed to lock resource manager: {}", e))?;
-                mgr.ensure_staging_texture(frame_w, frame_h)?;
+            let rotation = duplication_rotation(&duplication);
+            // 回读矩形（纹理坐标）：整帧或区域对应的子矩形；mip 级别的坐标为原坐标逐级减半（向下取整，宽高至少 1）
+            let (left, top, right, bottom) = match region {
+                Some(r) => region_to_texture(r, frame_w, frame_h, rotation),
+                None => (0, 0, frame_w, frame_h),
+            };
+            if right <= left || bottom <= top {
+                return Err(CaptureError::Readback(format!("capture region {:?} outside frame {}x{}", region, frame_w, frame_h)));
             }
+            let level_w = (frame_w >> mip_level).max(1);
+            let level_h = (frame_h >> mip_level).max(1);
+            let box_left = (left >> mip_level).min(level_w - 1);
+            let box_top = (top >> mip_level).min(level_h - 1);
+            let read_w = ((right >> mip_level) - box_left).clamp(1, level_w - box_left);
+            let read_h = ((bottom >> mip_level) - box_top).clamp(1, level_h - box_top);
+            let src_box = region.map(|_| D3D11_BOX {
+                left: box_left as u32,
+                top: box_top as u32,
+                front: 0,
+                right: (box_left + read_w) as u32,
+                bottom: (box_top + read_h) as u32,
+                back: 1,
+            });
+            let src_box_ptr = src_box.as_ref().map(|b| b as *const D3D11_BOX);
+            let mip = {
+                let mut mgr = lock_or_recover(&manager, "directx_manager");
+                mgr.ensure_staging_texture(read_w, read_h)?;
+                if mip_level > 0 {
+                    mgr.ensure_mip_texture(frame_w, frame_h, mip_level + 1, desc.Format)?;
+                    match (mgr.mip_texture.clone(), mgr.mip_srv.clone()) {
+                        (Some(t), Some(v)) => Some((t, v)),
+                        _ => return Err(CaptureError::Readback("mip texture not available".to_string())),
+                    }
+                } else {
+                    None
+                }
+            };
             let staging_texture = {
-                let mgr = manager.lock().map_err(|e| format!("Failed to lock resource manager: {}", e))?;
-                mgr.get_staging_texture().cloned().ok_or("Staging texture not available")?
+                let mgr = lock_or_recover(&manager, "directx_manager");
+                mgr.get_staging_texture().cloned().ok_or_else(|| CaptureError::Readback("staging texture not available".to_string()))?
             };
             
             // 关键：在 duplication/纹理准备完成后，再获取“当前最新”的上下文，避免与重建后的设备不一致
             let context = {
-                let mgr = manager.lock().map_err(|e| format!("Failed to lock resource manager: {}", e))?;
-                mgr.get_context().cloned().ok_or("Context not available")?
+                let mgr = lock_or_recover(&manager, "directx_manager");
+                mgr.get_context().cloned().ok_or_else(|| CaptureError::Device("context not available".to_string()))?
             };
-            context.CopyResource(&staging_texture, &tex);
+            match &mip {
+                Some((mip_texture, mip_srv)) => {
+                    // 原帧写入第 0 级，由 GPU 生成各级缩小图，只把目标级拷到 staging
+                    context.CopySubresourceRegion(mip_texture, 0, 0, 0, 0, &tex, 0, None);
+                    context.GenerateMips(mip_srv);
+                    context.CopySubresourceRegion(&staging_texture, 0, 0, 0, 0, mip_texture, mip_level, src_box_ptr);
+                }
+                None if src_box_ptr.is_some() => context.CopySubresourceRegion(&staging_texture, 0, 0, 0, 0, &tex, 0, src_box_ptr),
+                None => context.CopyResource(&staging_texture, &tex),
+            }
             
             // 读取像素数据到复用的缓冲区
             let mut mapped = windows::Win32::Graphics::Direct3D11::D3D11_MAPPED_SUBRESOURCE::default();
             context.Map(&staging_texture, 0, windows::Win32::Graphics::Direct3D11::D3D11_MAP_READ, 0, Some(&mut mapped))
-                .map_err(|e| { let _ = duplication.ReleaseFrame(); format!("Map failed: {e}") })?;
+                .map_err(|e| CaptureError::Readback(format!("Map failed: {e}")))?;
             
             let pitch = mapped.RowPitch as usize;
-            let width = frame_w as usize;
-            let height = frame_h as usize;
+            let width = read_w as usize;
+            let height = read_h as usize;
             let copy_bytes_per_row = std::cmp::min(width * 4, pitch);
             
-            let image_data = {
-                let mut mgr = manager.lock().map_err(|e| format!("Failed to lock resource manager: {}", e))?;
-                let output_buffer = mgr.get_output_buffer();
+            let (image_data, width, height) = {
+                let mut mgr = lock_or_recover(&manager, "directx_manager");
                 let needed = width * height * 4;
-                if output_buffer.len() < needed { output_buffer.resize(needed, 0); }
+                mgr.fit_output_buffer(needed);
+                let output_buffer = mgr.get_output_buffer();
                 for y in 0..height {
                     let src = (mapped.pData as *const u8).wrapping_add(y * pitch);
                     let start = y * width * 4;
@@ -705,22 +1360,32 @@ impl MonitorInfo {
                     let dst_slice = &mut output_buffer[start..end];
                     std::ptr::copy_nonoverlapping(src, dst_slice.as_mut_ptr(), copy_bytes_per_row);
                 }
-                let out = output_buffer[..needed].to_vec();
+                // 旋转输出：转换为桌面方向，并回写缓冲区，使超时复用上一帧时方向一致
+                let out = match rotate_to_desktop(&output_buffer[..needed], width, height, rotation) {
+                    Some((rotated, w, h)) => {
+                        output_buffer[..needed].copy_from_slice(&rotated);
+                        (rotated, w, h)
+                    }
+                    None => (output_buffer[..needed].to_vec(), width, height),
+                };
                 mgr.last_image_valid = true;
+                mgr.last_image_size = (out.1 as i32, out.2 as i32);
+                mgr.last_image_level = mip_level;
+                mgr.last_image_region = region.map(|r| (r.x, r.y, r.width, r.height));
                 out
             };
             
             context.Unmap(&staging_texture, 0);
-            duplication.ReleaseFrame().ok();
             
             let elapsed = start_time.elapsed();
-            debug!("[screen_shot_directx_optimized] Optimized DirectX screenshot completed in {:?}: {}x{}", elapsed, width, height);
+            debug!("[screen_shot_directx_optimized] Optimized DirectX screenshot completed in {:?}: {}x{} (mip level {})", elapsed, width, height, mip_level);
             
-            Ok(Image { width: width as i32, height: height as i32, data: image_data })
+            let scale = if mip_level > 0 { read_w as f32 / (right - left).max(1) as f32 } else { 1.0 };
+            Ok((Image { width: width as i32, height: height as i32, data: image_data }, scale, present))
         }
     }
 
-    fn screen_shot_directx_standard(&self) -> Result<Image, String> {
+    fn screen_shot_directx_standard(&self) -> Result<Image, CaptureError> {
         unsafe {
             debug!("[screen_shot_directx_standard] Starting standard DirectX method...");
             
@@ -732,7 +1397,7 @@ impl MonitorInfo {
             // 2. 创建DXGI工厂
             let factory: IDXGIFactory1 = match CreateDXGIFactory1() {
                 Ok(f) => f,
-                Err(e) => return Err(format!("CreateDXGIFactory1 failed: {e}")),
+                Err(e) => return Err(CaptureError::Device(format!("CreateDXGIFactory1 failed: {e}"))),
             };
             
             // 3. 枚举适配器和输出，找到目标显示器
@@ -746,16 +1411,7 @@ impl MonitorInfo {
                 
                 while let Ok(o) = a.EnumOutputs(j) {
                     let desc = o.GetDesc().unwrap();
-                    let ox = desc.DesktopCoordinates.left;
-                    let oy = desc.DesktopCoordinates.top;
-                    let ow = desc.DesktopCoordinates.right - desc.DesktopCoordinates.left + 1;
-                    let oh = desc.DesktopCoordinates.bottom - desc.DesktopCoordinates.top;
-                    
-                    // 使用更宽松的匹配条件，允许10像素的误差
-                    let width_match = (self.width - ow).abs() <= 10;
-                    let height_match = (self.height - oh).abs() <= 10;
-                    
-                    if self.x == ox && self.y == oy && width_match && height_match {
+                    if output_matches(&desc, self.x, self.y, self.width, self.height) {
                         debug!("[screen_shot_directx_standard] Found matching output: Adapter={}, Output={}", i, j);
                         adapter = Some(a.clone());
                         output = Some(o);
@@ -769,12 +1425,12 @@ impl MonitorInfo {
             }
             
             if !found {
-                return Err("No matching adapter/output found".to_string());
+                return Err(CaptureError::NoMatchingOutput);
             }
             
-            let adapter = match adapter { Some(a) => a, None => return Err("No adapter found".to_string()) };
+            let adapter = match adapter { Some(a) => a, None => return Err(CaptureError::NoMatchingOutput) };
             let adapter = adapter.cast::<windows::Win32::Graphics::Dxgi::IDXGIAdapter>().unwrap();
-            let output = match output { Some(o) => o, None => return Err("No output found".to_string()) };
+            let output = match output { Some(o) => o, None => return Err(CaptureError::NoMatchingOutput) };
             
             // 4. 创建D3D11设备
             let mut device: Option<ID3D11Device> = None;
@@ -791,46 +1447,28 @@ impl MonitorInfo {
                 Some(&mut context),
             );
             if hr.is_err() || device.is_none() || context.is_none() {
-                return Err("D3D11CreateDevice failed".to_string());
+                return Err(CaptureError::Device("D3D11CreateDevice failed".to_string()));
             }
             let device = device.unwrap();
             let context = context.unwrap();
             
             // 5. 获取Output1和Duplication
-            let output1: IDXGIOutput1 = output.cast().map_err(|e| format!("Output1 cast failed: {e}"))?;
+            let output1: IDXGIOutput1 = output.cast().map_err(|e| CaptureError::Duplication(format!("Output1 cast failed: {e}")))?;
             
             // 尝试多次获取duplication，有时第一次会失败
-            let mut duplication: Option<IDXGIOutputDuplication> = None;
-            let mut retry_count = 0;
-            const MAX_RETRIES: i32 = 3;
-            
-            while duplication.is_none() && retry_count < MAX_RETRIES {
-                match output1.DuplicateOutput(&device) {
-                    Ok(dup) => {
-                        duplication = Some(dup);
-                        debug!("[screen_shot_directx_standard] Output duplication created on attempt {}", retry_count + 1);
-                    }
-                    Err(e) => {
-                        retry_count += 1;
-                        if retry_count >= MAX_RETRIES {
-                            return Err(format!("DuplicateOutput failed after {} attempts: {e}", MAX_RETRIES));
-                        }
-                        std::thread::sleep(std::time::Duration::from_millis(100));
-                    }
-                }
-            }
-            
-            let duplication = duplication.unwrap();
+            const MAX_RETRIES: u32 = 3;
+            let duplication = duplicate_output_with_backoff(&output1, &device, MAX_RETRIES, "screen_shot_directx_standard")?;
             
             // 6. 获取下一帧
             let mut frame_info = DXGI_OUTDUPL_FRAME_INFO::default();
             let mut resource = None;
             // 将标准方法的等待也降低，减少卡顿
-            let hr = duplication.AcquireNextFrame(16, &mut frame_info, &mut resource);
+            let timeout = configured_acquire_timeout_ms().unwrap_or(STANDARD_ACQUIRE_TIMEOUT_MS);
+            let hr = duplication.AcquireNextFrame(timeout, &mut frame_info, &mut resource);
             if hr.is_err() {
                 let code = hr.unwrap_err().code();
-                if code == DXGI_ERROR_WAIT_TIMEOUT { return Err("AcquireNextFrame timeout".to_string()); }
-                return Err(format!("AcquireNextFrame failed: 0x{:X}", code.0));
+                if code == DXGI_ERROR_WAIT_TIMEOUT { return Err(CaptureError::Timeout); }
+                return Err(CaptureError::Acquire(format!("AcquireNextFrame failed: 0x{:X}", code.0)));
             }
             let resource = resource.unwrap();
             
@@ -840,7 +1478,7 @@ impl MonitorInfo {
             }
             
             // 7. 拷贝到CPU可读的Texture2D
-            let tex: ID3D11Texture2D = resource.cast().map_err(|e| format!("Resource cast failed: {e}"))?;
+            let tex: ID3D11Texture2D = resource.cast().map_err(|e| CaptureError::Readback(format!("Resource cast failed: {e}")))?;
             let mut desc = D3D11_TEXTURE2D_DESC::default();
             tex.GetDesc(&mut desc);
             
@@ -850,13 +1488,13 @@ impl MonitorInfo {
             cpu_desc.CPUAccessFlags = D3D11_CPU_ACCESS_READ.0 as u32;
             cpu_desc.MiscFlags = 0;
             let mut cpu_tex: Option<ID3D11Texture2D> = None;
-            device.CreateTexture2D(&cpu_desc, None, Some(&mut cpu_tex)).map_err(|e| format!("CreateTexture2D failed: {e}"))?;
+            device.CreateTexture2D(&cpu_desc, None, Some(&mut cpu_tex)).map_err(|e| CaptureError::Readback(format!("CreateTexture2D failed: {e}")))?;
             let cpu_tex = cpu_tex.unwrap();
             context.CopyResource(&cpu_tex, &tex);
             
             // 8. 读取像素数据
             let mut mapped = windows::Win32::Graphics::Direct3D11::D3D11_MAPPED_SUBRESOURCE::default();
-            context.Map(&cpu_tex, 0, windows::Win32::Graphics::Direct3D11::D3D11_MAP_READ, 0, Some(&mut mapped)).map_err(|e| format!("Map failed: {e}"))?;
+            context.Map(&cpu_tex, 0, windows::Win32::Graphics::Direct3D11::D3D11_MAP_READ, 0, Some(&mut mapped)).map_err(|e| CaptureError::Readback(format!("Map failed: {e}")))?;
             let pitch = mapped.RowPitch as usize;
             let mut buf = vec![0u8; (desc.Width * desc.Height * 4) as usize];
             
@@ -880,19 +1518,22 @@ impl MonitorInfo {
             }
             
             context.Unmap(&cpu_tex, 0);
+            let rotation = duplication_rotation(&duplication);
             duplication.ReleaseFrame().ok();
             
             debug!("[screen_shot_directx_standard] DirectX screenshot completed: {}x{}", desc.Width, desc.Height);
             
+            let (buf, width, height) = rotate_to_desktop(&buf, desc.Width as usize, desc.Height as usize, rotation)
+                .unwrap_or((buf, desc.Width as usize, desc.Height as usize));
             Ok(Image {
-                width: desc.Width as i32,
-                height: desc.Height as i32,
+                width: width as i32,
+                height: height as i32,
                 data: buf,
             })
         }
     }
 
-    fn screen_shot_directx_alternative(&self) -> Result<Image, String> {
+    fn screen_shot_directx_alternative(&self) -> Result<Image, CaptureError> {
         unsafe {
             debug!("[screen_shot_directx_alternative] Starting alternative method...");
             
@@ -905,7 +1546,7 @@ impl MonitorInfo {
             // 创建DXGI工厂
             let factory: IDXGIFactory1 = match CreateDXGIFactory1() {
                 Ok(f) => f,
-                Err(e) => return Err(format!("CreateDXGIFactory1 failed: {e}")),
+                Err(e) => return Err(CaptureError::Device(format!("CreateDXGIFactory1 failed: {e}"))),
             };
             
             // 找到目标显示器
@@ -918,16 +1559,7 @@ impl MonitorInfo {
                 let mut j = 0;
                 while let Ok(o) = a.EnumOutputs(j) {
                     let desc = o.GetDesc().unwrap();
-                    let ox = desc.DesktopCoordinates.left;
-                    let oy = desc.DesktopCoordinates.top;
-                    let ow = desc.DesktopCoordinates.right - desc.DesktopCoordinates.left + 1;
-                    let oh = desc.DesktopCoordinates.bottom - desc.DesktopCoordinates.top;
-                    
-                    // 使用更宽松的匹配条件
-                    let width_match = (self.width - ow).abs() <= 10;
-                    let height_match = (self.height - oh).abs() <= 10;
-                    
-                    if self.x == ox && self.y == oy && width_match && height_match {
+                    if output_matches(&desc, self.x, self.y, self.width, self.height) {
                         adapter = Some(a.clone());
                         output = Some(o);
                         found = true;
@@ -940,7 +1572,7 @@ impl MonitorInfo {
             }
             
             if !found {
-                return Err("No matching adapter/output found".to_string());
+                return Err(CaptureError::NoMatchingOutput);
             }
             
             let adapter = adapter.unwrap();
@@ -962,45 +1594,29 @@ impl MonitorInfo {
                 Some(&mut context),
             );
             if hr.is_err() || device.is_none() || context.is_none() {
-                return Err("D3D11CreateDevice failed".to_string());
+                return Err(CaptureError::Device("D3D11CreateDevice failed".to_string()));
             }
             let device = device.unwrap();
             let context = context.unwrap();
             
             // 获取Output1和Duplication
-            let output1: IDXGIOutput1 = output.cast().map_err(|e| format!("Output1 cast failed: {e}"))?;
+            let output1: IDXGIOutput1 = output.cast().map_err(|e| CaptureError::Duplication(format!("Output1 cast failed: {e}")))?;
             
             // 尝试多次获取duplication
-            let mut duplication: Option<IDXGIOutputDuplication> = None;
-            let mut retry_count = 0;
-            const MAX_RETRIES: i32 = 5;
-            
-            while duplication.is_none() && retry_count < MAX_RETRIES {
-                match output1.DuplicateOutput(&device) {
-                    Ok(dup) => {
-                        duplication = Some(dup);
-                        debug!("[screen_shot_directx_alternative] Output duplication created on attempt {}", retry_count + 1);
-                    }
-                    Err(e) => {
-                        retry_count += 1;
-                        if retry_count >= MAX_RETRIES {
-                            return Err(format!("DuplicateOutput failed after {} attempts: {e}", MAX_RETRIES));
-                        }
-                        std::thread::sleep(std::time::Duration::from_millis(200));
-                    }
-                }
-            }
-            
-            let duplication = duplication.unwrap();
+            const MAX_RETRIES: u32 = 5;
+            let duplication = duplicate_output_with_backoff(&output1, &device, MAX_RETRIES, "screen_shot_directx_alternative")?;
             
             // 等待并获取帧，尝试多次
             let mut frame_info = DXGI_OUTDUPL_FRAME_INFO::default();
             let mut resource = None;
             let mut frame_attempts = 0;
             const MAX_FRAME_ATTEMPTS: i32 = 10;
+            let timeout = configured_acquire_timeout_ms().unwrap_or(ALTERNATIVE_ACQUIRE_TIMEOUT_MS);
             
             while frame_attempts < MAX_FRAME_ATTEMPTS {
-                let hr = duplication.AcquireNextFrame(1000, &mut frame_info, &mut resource);
+                let hr = duplication.AcquireNextFrame(timeout, &mut frame_info, &mut resource);
+                // 本次是否为等待超时：全部尝试用尽时据此区分超时与其他错误
+                let timed_out = matches!(&hr, Err(e) if e.code() == DXGI_ERROR_WAIT_TIMEOUT);
                 if hr.is_ok() && resource.is_some() {
                     // 如果有累积帧，继续处理
                     if frame_info.AccumulatedFrames > 0 {
@@ -1011,7 +1627,10 @@ impl MonitorInfo {
                 
                 frame_attempts += 1;
                 if frame_attempts >= MAX_FRAME_ATTEMPTS {
-                    return Err("Failed to acquire frame with accumulated frames".to_string());
+                    if timed_out {
+                        return Err(CaptureError::Timeout);
+                    }
+                    return Err(CaptureError::Acquire("no frame with accumulated updates".to_string()));
                 }
                 
                 // 释放当前帧并重试
@@ -1026,7 +1645,7 @@ impl MonitorInfo {
             let resource = resource.unwrap();
             
             // 拷贝到CPU可读的Texture2D
-            let tex: ID3D11Texture2D = resource.cast().map_err(|e| format!("Resource cast failed: {e}"))?;
+            let tex: ID3D11Texture2D = resource.cast().map_err(|e| CaptureError::Readback(format!("Resource cast failed: {e}")))?;
             let mut desc = D3D11_TEXTURE2D_DESC::default();
             tex.GetDesc(&mut desc);
             
@@ -1038,14 +1657,14 @@ impl MonitorInfo {
             
             let mut cpu_tex: Option<ID3D11Texture2D> = None;
             device.CreateTexture2D(&cpu_desc, None, Some(&mut cpu_tex))
-                .map_err(|e| format!("CreateTexture2D failed: {e}"))?;
+                .map_err(|e| CaptureError::Readback(format!("CreateTexture2D failed: {e}")))?;
             let cpu_tex = cpu_tex.unwrap();
             context.CopyResource(&cpu_tex, &tex);
             
             // 读取像素数据
             let mut mapped = windows::Win32::Graphics::Direct3D11::D3D11_MAPPED_SUBRESOURCE::default();
             context.Map(&cpu_tex, 0, windows::Win32::Graphics::Direct3D11::D3D11_MAP_READ, 0, Some(&mut mapped))
-                .map_err(|e| format!("Map failed: {e}"))?;
+                .map_err(|e| CaptureError::Readback(format!("Map failed: {e}")))?;
             
             let pitch = mapped.RowPitch as usize;
             let mut buf = vec![0u8; (desc.Width * desc.Height * 4) as usize];
@@ -1058,15 +1677,108 @@ impl MonitorInfo {
             }
             
             context.Unmap(&cpu_tex, 0);
+            let rotation = duplication_rotation(&duplication);
             duplication.ReleaseFrame().ok();
             
             debug!("[screen_shot_directx_alternative] Alternative method completed: {}x{}", desc.Width, desc.Height);
             
+            let (buf, width, height) = rotate_to_desktop(&buf, desc.Width as usize, desc.Height as usize, rotation)
+                .unwrap_or((buf, desc.Width as usize, desc.Height as usize));
             Ok(Image {
-                width: desc.Width as i32,
-                height: desc.Height as i32,
+                width: width as i32,
+                height: height as i32,
                 data: buf,
             })
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_ceiling_doubles_per_attempt() {
+        assert_eq!(backoff_ceiling_ms(0, 50, 1000), 50);
+        assert_eq!(backoff_ceiling_ms(1, 50, 1000), 100);
+        assert_eq!(backoff_ceiling_ms(2, 50, 1000), 200);
+        assert_eq!(backoff_ceiling_ms(4, 50, 1000), 800);
+    }
+
+    #[test]
+    fn backoff_ceiling_is_capped() {
+        assert_eq!(backoff_ceiling_ms(5, DUPLICATE_RETRY_BASE_MS, DUPLICATE_RETRY_CAP_MS), DUPLICATE_RETRY_CAP_MS);
+        assert_eq!(backoff_ceiling_ms(30, DUPLICATE_RETRY_BASE_MS, DUPLICATE_RETRY_CAP_MS), DUPLICATE_RETRY_CAP_MS);
+    }
+
+    #[test]
+    fn backoff_ceiling_saturates_shift_at_16() {
+        assert_eq!(backoff_ceiling_ms(16, 1, u64::MAX), 1 << 16);
+        assert_eq!(backoff_ceiling_ms(17, 1, u64::MAX), 1 << 16);
+        assert_eq!(backoff_ceiling_ms(u32::MAX, 1, u64::MAX), 1 << 16);
+        // 乘法溢出时饱和而不是回绕
+        assert_eq!(backoff_ceiling_ms(16, u64::MAX / 2, u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn backoff_delay_jitter_stays_within_half_to_full_ceiling() {
+        for attempt in 0..8 {
+            let ceiling = backoff_ceiling_ms(attempt, DUPLICATE_RETRY_BASE_MS, DUPLICATE_RETRY_CAP_MS);
+            for _ in 0..200 {
+                let ms = backoff_delay(attempt).as_millis() as u64;
+                assert!(ms >= ceiling / 2 && ms <= ceiling, "attempt {}: {}ms not in [{}, {}]", attempt, ms, ceiling / 2, ceiling);
+            }
+        }
+    }
+    const BYTES_4K: usize = 3840 * 2160 * 4;
+    const BYTES_1440P: usize = 2560 * 1440 * 4;
+    const BYTES_1080P: usize = 1920 * 1080 * 4;
+
+    #[test]
+    fn output_buffer_shrinks_after_resolution_drop() {
+        // 4K -> 1080p：容量是所需的 4 倍，超过 OUTPUT_BUFFER_SHRINK_RATIO
+        assert_eq!(output_buffer_fit(BYTES_4K, BYTES_4K, BYTES_1080P), BufferFit::Shrink);
+    }
+
+    #[test]
+    fn output_buffer_kept_within_shrink_slack() {
+        // 1440p -> 1080p：容量不到所需的 2 倍，沿用原缓冲区
+        assert_eq!(output_buffer_fit(BYTES_1440P, BYTES_1440P, BYTES_1080P), BufferFit::Keep);
+        assert_eq!(output_buffer_fit(BYTES_1080P * 2, BYTES_1080P * 2, BYTES_1080P), BufferFit::Keep);
+        assert_eq!(output_buffer_fit(BYTES_1080P, BYTES_1080P, BYTES_1080P), BufferFit::Keep);
+    }
+
+    #[test]
+    fn output_buffer_grows_when_too_short() {
+        assert_eq!(output_buffer_fit(BYTES_1080P, BYTES_1080P, BYTES_4K), BufferFit::Grow);
+        assert_eq!(output_buffer_fit(0, 0, BYTES_1080P), BufferFit::Grow);
+    }
+    fn has_manager(monitor_id: usize) -> bool {
+        DIRECTX_MANAGERS
+            .get()
+            .is_some_and(|m| lock_or_recover(m, "directx_managers").contains_key(&monitor_id))
+    }
+
+    #[test]
+    fn release_drops_managers_and_next_start_rebuilds_them() {
+        // 使用不会与真实显示器冲突的编号
+        let id = 9001;
+        let first = DirectXResourceManager::get_instance(id);
+        lock_or_recover(&first, "directx_manager").output_buffer = vec![0u8; 64];
+        assert!(has_manager(id));
+
+        release_capture_resources();
+        assert!(!has_manager(id));
+        // 仍被持有的旧管理器也已释放资源
+        assert_eq!(lock_or_recover(&first, "directx_manager").output_buffer.capacity(), 0);
+
+        let second = DirectXResourceManager::get_instance(id);
+        assert!(!Arc::ptr_eq(&first, &second));
+        let mgr = lock_or_recover(&second, "directx_manager");
+        assert!(!mgr.is_initialized && mgr.output_buffer.is_empty() && mgr.duplications.is_empty());
+        drop(mgr);
+
+        release_capture_resources();
+        assert!(!has_manager(id));
+    }
+}
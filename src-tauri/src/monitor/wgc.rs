@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use log::{debug, info, warn};
+use windows::core::{factory, Interface};
+use windows::Graphics::Capture::{Direct3D11CaptureFramePool, GraphicsCaptureItem, GraphicsCaptureSession};
+use windows::Graphics::DirectX::Direct3D11::IDirect3DDevice;
+use windows::Graphics::DirectX::DirectXPixelFormat;
+use windows::Graphics::SizeInt32;
+use windows::Win32::Foundation::POINT;
+use windows::Win32::Graphics::Direct3D11::{
+    D3D11CreateDevice, ID3D11Device, ID3D11DeviceContext, ID3D11Texture2D, D3D11_CPU_ACCESS_READ,
+    D3D11_CREATE_DEVICE_BGRA_SUPPORT, D3D11_MAP_READ, D3D11_MAPPED_SUBRESOURCE, D3D11_SDK_VERSION,
+    D3D11_TEXTURE2D_DESC, D3D11_USAGE_STAGING,
+};
+use windows::Win32::Graphics::Dxgi::IDXGIDevice;
+use windows::Win32::Graphics::Gdi::{MonitorFromPoint, MONITOR_DEFAULTTONEAREST};
+use windows::Win32::System::WinRT::Direct3D11::{CreateDirect3D11DeviceFromDXGIDevice, IDirect3DDxgiInterfaceAccess};
+use windows::Win32::System::WinRT::Graphics::Capture::IGraphicsCaptureItemInterop;
+
+use super::capture_error::CaptureError;
+use super::monitor::MonitorInfo;
+use super::screen_shot::Image;
+use crate::utils::sync::lock_or_recover;
+
+// Windows.Graphics.Capture 截图：每个显示器常驻一个 free-threaded 帧池与捕获会话，
+// 截图时取帧池中最新的一帧拷到 staging 回读。WGC 仅在画面变化时产生新帧，无新帧时复用上一帧。
+// 系统不支持（旧版 Windows 10）时记住结果，之后直接失败交由 DXGI 方法处理。
+
+// 首帧等待上限：会话刚启动时帧池可能尚无帧
+const FIRST_FRAME_TIMEOUT: Duration = Duration::from_millis(200);
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+static UNSUPPORTED: AtomicBool = AtomicBool::new(false);
+// 每个显示器一个会话槽：全局表只在取槽时短暂加锁，创建会话与等待首帧只持有该显示器自己的槽锁，
+// 不阻塞其他显示器的截图
+type SessionSlot = Arc<Mutex<Option<WgcSession>>>;
+static SESSIONS: OnceLock<Mutex<HashMap<usize, SessionSlot>>> = OnceLock::new();
+
+struct WgcSession {
+    device: ID3D11Device,
+    context: ID3D11DeviceContext,
+    rt_device: IDirect3DDevice,
+    pool: Direct3D11CaptureFramePool,
+    session: GraphicsCaptureSession,
+    size: SizeInt32,
+    staging: Option<(ID3D11Texture2D, i32, i32)>,
+    // 监控的显示器几何，变化时重建会话
+    geometry: (i32, i32, i32, i32),
+    last_image: Option<Image>,
+}
+
+impl Drop for WgcSession {
+    fn drop(&mut self) {
+        let _ = self.session.Close();
+        let _ = self.pool.Close();
+    }
+}
+
+fn sessions() -> &'static Mutex<HashMap<usize, SessionSlot>> {
+    SESSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn session_slot(monitor_id: usize) -> SessionSlot {
+    lock_or_recover(sessions(), "wgc_sessions").entry(monitor_id).or_default().clone()
+}
+
+// 能力探测：系统是否支持 Windows.Graphics.Capture（不创建会话）
+pub fn check_supported() -> Result<(), String> {
+    match GraphicsCaptureSession::IsSupported() {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Windows.Graphics.Capture is not supported on this system".to_string()),
+        Err(e) => Err(format!("GraphicsCaptureSession::IsSupported failed: {e}")),
+    }
+}
+
+// 停止监控时释放全部捕获会话；正在截图的槽在该次截图结束后随最后一个引用释放
+pub fn release_all() {
+    if let Some(map) = SESSIONS.get() {
+        lock_or_recover(map, "wgc_sessions").clear();
+    }
+}
+
+// 释放单个显示器的捕获会话（副显示器停止监控时）
+pub fn release(monitor_id: usize) {
+    if let Some(map) = SESSIONS.get() {
+        lock_or_recover(map, "wgc_sessions").remove(&monitor_id);
+    }
+}
+
+pub fn capture(monitor: &MonitorInfo) -> Result<Image, CaptureError> {
+    if UNSUPPORTED.load(Ordering::Relaxed) {
+        return Err(CaptureError::Unsupported("Windows.Graphics.Capture is not supported on this system".to_string()));
+    }
+    let geometry = (monitor.x, monitor.y, monitor.width, monitor.height);
+    let slot = session_slot(monitor.id);
+    let mut slot = lock_or_recover(&slot, "wgc_session");
+    if slot.as_ref().is_some_and(|s| s.geometry != geometry) {
+        *slot = None;
+    }
+    let session = match slot.take() {
+        Some(session) => session,
+        None => create_session(monitor)?,
+    };
+    let session = slot.insert(session);
+    match grab_frame(session) {
+        Ok(image) => Ok(image),
+        Err(e) => {
+            // 会话出错后丢弃，下次重新创建
+            *slot = None;
+            Err(e)
+        }
+    }
+}
+
+fn create_session(monitor: &MonitorInfo) -> Result<WgcSession, CaptureError> {
+    unsafe {
+        if !GraphicsCaptureSession::IsSupported().unwrap_or(false) {
+            UNSUPPORTED.store(true, Ordering::Relaxed);
+            return Err(CaptureError::Unsupported("Windows.Graphics.Capture is not supported on this system".to_string()));
+        }
+        let interop = factory::<GraphicsCaptureItem, IGraphicsCaptureItemInterop>().map_err(|e| {
+            UNSUPPORTED.store(true, Ordering::Relaxed);
+            CaptureError::Unsupported(format!("GraphicsCaptureItem interop unavailable: {e}"))
+        })?;
+        let center = POINT { x: monitor.x + monitor.width / 2, y: monitor.y + monitor.height / 2 };
+        let hmonitor = MonitorFromPoint(center, MONITOR_DEFAULTTONEAREST);
+        let item: GraphicsCaptureItem = interop
+            .CreateForMonitor(hmonitor)
+            .map_err(|e| CaptureError::Duplication(format!("CreateForMonitor failed: {e}")))?;
+
+        let mut device: Option<ID3D11Device> = None;
+        let mut context: Option<ID3D11DeviceContext> = None;
+        D3D11CreateDevice(
+            None,
+            windows::Win32::Graphics::Direct3D::D3D_DRIVER_TYPE_HARDWARE,
+            windows::Win32::Foundation::HMODULE::default(),
+            D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+            None,
+            D3D11_SDK_VERSION,
+            Some(&mut device),
+            None,
+            Some(&mut context),
+        )
+        .map_err(|e| CaptureError::Device(format!("D3D11CreateDevice failed: {e}")))?;
+        let device = device.ok_or_else(|| CaptureError::Device("D3D11 device not created".to_string()))?;
+        let context = context.ok_or_else(|| CaptureError::Device("D3D11 context not created".to_string()))?;
+        let dxgi_device: IDXGIDevice = device
+            .cast()
+            .map_err(|e| CaptureError::Device(format!("IDXGIDevice cast failed: {e}")))?;
+        let rt_device: IDirect3DDevice = CreateDirect3D11DeviceFromDXGIDevice(&dxgi_device)
+            .and_then(|d| d.cast())
+            .map_err(|e| CaptureError::Device(format!("CreateDirect3D11DeviceFromDXGIDevice failed: {e}")))?;
+
+        let size = item
+            .Size()
+            .map_err(|e| CaptureError::Duplication(format!("GraphicsCaptureItem size failed: {e}")))?;
+        let pool = Direct3D11CaptureFramePool::CreateFreeThreaded(&rt_device, DirectXPixelFormat::B8G8R8A8UIntNormalized, 2, size)
+            .map_err(|e| CaptureError::Duplication(format!("CreateFreeThreaded failed: {e}")))?;
+        let session = pool
+            .CreateCaptureSession(&item)
+            .map_err(|e| CaptureError::Duplication(format!("CreateCaptureSession failed: {e}")))?;
+        // 黄色边框与光标捕获：旧系统不支持这些开关，忽略失败
+        let _ = session.SetIsBorderRequired(false);
+        let _ = session.SetIsCursorCaptureEnabled(false);
+        session
+            .StartCapture()
+            .map_err(|e| CaptureError::Duplication(format!("StartCapture failed: {e}")))?;
+        info!("[wgc] capture session started for monitor {} ({}x{})", monitor.id, size.Width, size.Height);
+
+        Ok(WgcSession {
+            device,
+            context,
+            rt_device,
+            pool,
+            session,
+            size,
+            staging: None,
+            geometry: (monitor.x, monitor.y, monitor.width, monitor.height),
+            last_image: None,
+        })
+    }
+}
+
+fn grab_frame(s: &mut WgcSession) -> Result<Image, CaptureError> {
+    // 取出帧池中积压的全部帧，只保留最新一帧
+    let started = Instant::now();
+    let mut latest = None;
+    loop {
+        while let Ok(frame) = s.pool.TryGetNextFrame() {
+            if let Some(old) = latest.replace(frame) {
+                let _ = old.Close();
+            }
+        }
+        if latest.is_some() || s.last_image.is_some() || started.elapsed() >= FIRST_FRAME_TIMEOUT {
+            break;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    let frame = match latest {
+        Some(f) => f,
+        None => {
+            // 画面无变化：复用上一帧
+            return s.last_image.clone().ok_or(CaptureError::Timeout);
+        }
+    };
+
+    let result = unsafe { read_frame(s, &frame) };
+    let _ = frame.Close();
+    let image = result?;
+    s.last_image = Some(image.clone());
+    Ok(image)
+}
+
+unsafe fn read_frame(
+    s: &mut WgcSession,
+    frame: &windows::Graphics::Capture::Direct3D11CaptureFrame,
+) -> Result<Image, CaptureError> {
+    // 显示器分辨率变化：按新尺寸重建帧池，本帧仍按实际纹理尺寸读取
+    let content = frame
+        .ContentSize()
+        .map_err(|e| CaptureError::Acquire(format!("ContentSize failed: {e}")))?;
+    if content.Width != s.size.Width || content.Height != s.size.Height {
+        debug!("[wgc] content size changed {}x{} -> {}x{}", s.size.Width, s.size.Height, content.Width, content.Height);
+        s.pool
+            .Recreate(&s.rt_device, DirectXPixelFormat::B8G8R8A8UIntNormalized, 2, content)
+            .map_err(|e| CaptureError::Duplication(format!("Recreate frame pool failed: {e}")))?;
+        s.size = content;
+    }
+
+    let surface = frame
+        .Surface()
+        .map_err(|e| CaptureError::Acquire(format!("frame surface failed: {e}")))?;
+    let access: IDirect3DDxgiInterfaceAccess = surface
+        .cast()
+        .map_err(|e| CaptureError::Readback(format!("surface access cast failed: {e}")))?;
+    let texture: ID3D11Texture2D = access
+        .GetInterface()
+        .map_err(|e| CaptureError::Readback(format!("surface texture failed: {e}")))?;
+    let mut desc = D3D11_TEXTURE2D_DESC::default();
+    texture.GetDesc(&mut desc);
+    let width = (desc.Width as i32).min(content.Width.max(1));
+    let height = (desc.Height as i32).min(content.Height.max(1));
+
+    let staging = match &s.staging {
+        Some((tex, w, h)) if *w == desc.Width as i32 && *h == desc.Height as i32 => tex.clone(),
+        _ => {
+            let mut sd = desc;
+            sd.MipLevels = 1;
+            sd.ArraySize = 1;
+            sd.Usage = D3D11_USAGE_STAGING;
+            sd.BindFlags = 0;
+            sd.CPUAccessFlags = D3D11_CPU_ACCESS_READ.0 as u32;
+            sd.MiscFlags = 0;
+            let mut tex: Option<ID3D11Texture2D> = None;
+            s.device
+                .CreateTexture2D(&sd, None, Some(&mut tex))
+                .map_err(|e| CaptureError::Readback(format!("CreateTexture2D failed: {e}")))?;
+            let tex = tex.ok_or_else(|| CaptureError::Readback("staging texture not created".to_string()))?;
+            s.staging = Some((tex.clone(), desc.Width as i32, desc.Height as i32));
+            tex
+        }
+    };
+
+    s.context.CopyResource(&staging, &texture);
+    let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+    s.context
+        .Map(&staging, 0, D3D11_MAP_READ, 0, Some(&mut mapped))
+        .map_err(|e| CaptureError::Readback(format!("Map failed: {e}")))?;
+    let pitch = mapped.RowPitch as usize;
+    let row_bytes = width as usize * 4;
+    if pitch < row_bytes {
+        s.context.Unmap(&staging, 0);
+        warn!("[wgc] unexpected row pitch {} < {}", pitch, row_bytes);
+        return Err(CaptureError::Readback("unexpected row pitch".to_string()));
+    }
+    let mut data = vec![0u8; row_bytes * height as usize];
+    for y in 0..height as usize {
+        let src = (mapped.pData as *const u8).add(y * pitch);
+        std::ptr::copy_nonoverlapping(src, data[y * row_bytes..].as_mut_ptr(), row_bytes);
+    }
+    s.context.Unmap(&staging, 0);
+    Ok(Image { width, height, data })
+}
@@ -1,3 +1,3 @@
 pub mod mosaic;
 
-pub use mosaic::Mosaic;
\ No newline at end of file
+pub use mosaic::{pixelate_regions, Mosaic, MosaicStyle};
\ No newline at end of file
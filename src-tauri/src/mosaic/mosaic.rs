@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+use crate::monitor::screen_shot::Image;
+use crate::utils::rect::Rect;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Mosaic {
     pub x: i32,
@@ -7,4 +10,110 @@ pub struct Mosaic {
     pub width: i32,
     pub height: i32,
     pub angle: f32,
-}
\ No newline at end of file
+    // 纯色遮罩时逐框下发的填充色（#rrggbb），其余样式缺省
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    // 漏检保持期间的不透明度（0~1），正常检测到的框缺省（完全不透明）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alpha: Option<f32>,
+}
+
+// 遮罩样式：随 payload 下发，由 overlay.html 按类型渲染
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MosaicStyle {
+    // 默认：按 mosaic_style CSS 渲染像素化图案
+    #[default]
+    Pixelate,
+    // 贴纸：id 为 emoji 字符，或图片地址（http(s)/data/asset URL），按框大小绘制
+    Sticker { id: String },
+    // 纯色：color 为 CSS 颜色；"auto" 时取人脸框周围的平均色，使遮罩融入背景
+    Solid { color: String },
+    // 模糊：overlay 读不到下层屏幕像素，模糊作用于不透明底色上的图案，radius 为物理像素
+    Blur { radius: u32 },
+}
+
+impl MosaicStyle {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            MosaicStyle::Pixelate => Ok(()),
+            MosaicStyle::Sticker { id } => {
+                let id = id.trim();
+                if id.is_empty() {
+                    return Err("sticker id must not be empty".to_string());
+                }
+                if id.chars().any(|c| c.is_control()) {
+                    return Err("sticker id must not contain control characters".to_string());
+                }
+                Ok(())
+            }
+            MosaicStyle::Blur { radius } => {
+                if *radius == 0 || *radius > 100 {
+                    return Err(format!("blur radius must be in 1..=100, got {}", radius));
+                }
+                Ok(())
+            }
+            MosaicStyle::Solid { color } => {
+                let color = color.trim();
+                if color.is_empty() {
+                    return Err("solid color must not be empty".to_string());
+                }
+                if color.chars().any(|c| c.is_control()) {
+                    return Err("solid color must not contain control characters".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // 是否需要在 cal() 中从截图采样每个框的周边颜色
+    pub fn samples_color(&self) -> bool {
+        matches!(self, MosaicStyle::Solid { color } if color.trim().eq_ignore_ascii_case("auto"))
+    }
+}
+
+// 在 BGRA 图像上对各区域做像素化：按 block×block 分块，块内取平均色（边缘不足一块的按实际大小）。
+// block 为 0 时按区域短边的 1/8 取块（至少 8px），与 overlay 默认的马赛克效果一致
+pub fn pixelate_regions(image: &mut Image, rects: &[Rect], block: u32) {
+    let bounds = Rect::new(0, 0, image.width, image.height);
+    let stride = image.width as usize * 4;
+    if image.data.len() < stride * image.height.max(0) as usize {
+        return;
+    }
+    for rect in rects {
+        let r = match rect.intersection(&bounds) {
+            Some(r) => r,
+            None => continue,
+        };
+        let block = if block == 0 { (r.width.min(r.height) / 8).max(8) } else { block as i32 };
+        let mut by = r.y;
+        while by < r.y + r.height {
+            let bh = block.min(r.y + r.height - by);
+            let mut bx = r.x;
+            while bx < r.x + r.width {
+                let bw = block.min(r.x + r.width - bx);
+                let mut sum = [0u64; 3];
+                for y in by..by + bh {
+                    let row = y as usize * stride;
+                    for x in bx..bx + bw {
+                        let i = row + x as usize * 4;
+                        sum[0] += image.data[i] as u64;
+                        sum[1] += image.data[i + 1] as u64;
+                        sum[2] += image.data[i + 2] as u64;
+                    }
+                }
+                let n = (bw * bh) as u64;
+                let avg = [(sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8];
+                for y in by..by + bh {
+                    let row = y as usize * stride;
+                    for x in bx..bx + bw {
+                        let i = row + x as usize * 4;
+                        image.data[i..i + 3].copy_from_slice(&avg);
+                    }
+                }
+                bx += bw;
+            }
+            by += bh;
+        }
+    }
+}
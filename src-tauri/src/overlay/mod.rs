@@ -12,22 +12,34 @@ use windows::Win32::{
         GetWindowLongW, SetWindowLongW, GWL_EXSTYLE, WS_EX_TRANSPARENT, WS_EX_LAYERED,
         SetWindowPos, HWND_TOPMOST, HWND_NOTOPMOST, SWP_NOMOVE, SWP_NOSIZE, SWP_NOACTIVATE, SWP_SHOWWINDOW,
         SetWindowDisplayAffinity, WINDOW_DISPLAY_AFFINITY, WDA_EXCLUDEFROMCAPTURE,
+        GetWindowDisplayAffinity, IsWindow, IsWindowVisible, WS_EX_TOPMOST,
+        CreateWindowExW, DestroyWindow, WS_EX_TOOLWINDOW, WS_POPUP,
     },
 };
+use serde::Serialize;
 
 use crate::{app::AppState, monitor::MonitorInfo};
 // 不再在创建时下发样式，前端会在初始化时 invoke 获取
 
-pub async fn create_overlay_window(
+// 工作显示器 overlay 的窗口标签；副显示器的 overlay 标签为 overlay-<显示器序号>
+pub const MAIN_OVERLAY_LABEL: &str = "overlay";
+
+pub fn extra_overlay_label(monitor_id: usize) -> String {
+    format!("{}-{}", MAIN_OVERLAY_LABEL, monitor_id)
+}
+
+// 按标签创建 overlay：只有工作显示器的 overlay 记入 OverlayState，副显示器的 overlay 按标签投递与关闭
+pub async fn create_labeled_overlay_window(
     monitor: &MonitorInfo,
-) {
-    info!("[create_overlay_window] Starting overlay window creation...");
+    label: &str,
+) -> Result<(), String> {
+    info!("[create_overlay_window] Starting overlay window creation ({})...", label);
     info!("[create_overlay_window] Monitor info: x={}, y={}, width={}, height={}, scale_factor={}", 
           monitor.x, monitor.y, monitor.width, monitor.height, monitor.scale_factor);
     
     // 如果已存在，先关闭
-    if let Some(existing_window) = AppState::get_global().unwrap().handle.get_webview_window("overlay") {
-        warn!("[create_overlay_window] close existing window: {}", "overlay");
+    if let Some(existing_window) = AppState::get_global().unwrap().handle.get_webview_window(label) {
+        warn!("[create_overlay_window] close existing window: {}", label);
         if let Err(e) = existing_window.close() {
             error!(
                 "[create_overlay_window] close existing window failed: {}",
@@ -57,10 +69,10 @@ pub async fn create_overlay_window(
     
     let window = tauri::WebviewWindowBuilder::new(
         &handle,
-        "overlay",
+        label,
         tauri::WebviewUrl::App("overlay.html".into()),
     )
-    .title("overlay")
+    .title(label)
     .transparent(true)
     .decorations(false)
     .shadow(false)
@@ -71,23 +83,27 @@ pub async fn create_overlay_window(
     .always_on_top(true)
     .build();
 
-    if let Err(e) = &window {
-        error!("[create_overlay_window] create overlay window failed: {}", e);
-        panic!(
-            "[create_overlay_window] create overlay window failed: {}",
-            e
-        );
-    }
-    
-    let window = window.unwrap();
+    let window = match window {
+        Ok(w) => w,
+        Err(e) => {
+            error!("[create_overlay_window] create overlay window failed: {}", e);
+            return Err(e.to_string());
+        }
+    };
     info!("[create_overlay_window] Window created successfully");
 
-    if log::max_level() == log::LevelFilter::Debug {
+    let open_devtools = crate::config::get_config()
+        .and_then(|c| c.system)
+        .and_then(|s| s.overlay_devtools)
+        .unwrap_or(false);
+    if open_devtools {
         let _ = window.open_devtools();
     }
     
-    OverlayState::set_window(window.clone());
-    info!("[create_overlay_window] Window stored in OverlayState");
+    if label == MAIN_OVERLAY_LABEL {
+        OverlayState::set_window(window.clone());
+        info!("[create_overlay_window] Window stored in OverlayState");
+    }
 
     // 样式获取改由前端初始化时通过 invoke('get_mosaic_style') 完成
     
@@ -97,6 +113,11 @@ pub async fn create_overlay_window(
     } else {
         info!("[create_overlay_window] Window position set successfully");
     }
+    // 定位后再按物理像素校正尺寸：创建时的逻辑尺寸按创建所在显示器的 DPI 换算，移到 DPI 不同的显示器
+    // （常见于位于主显示器左/上方、坐标为负的副屏）后会被系统再缩放一次，导致边缘露出或溢出
+    if let Err(e) = window.set_size(tauri::PhysicalSize::new(monitor.width.max(1) as u32, monitor.height.max(1) as u32)) {
+        error!("[create_overlay_window] set size failed: {}", e);
+    }
     // 确保窗口位置正确
     info!("[create_overlay_window] Getting window handle...");
     match window.hwnd() {
@@ -144,6 +165,7 @@ pub async fn create_overlay_window(
     }
     
     info!("[create_overlay_window] Overlay window creation completed");
+    Ok(())
 }
 
 fn set_window_transparent_style(window: &tauri::WebviewWindow, hwnd_raw: i64) {
@@ -202,8 +224,97 @@ unsafe fn apply_click_through_to_hwnd(hwnd: HWND) {
     }
 }
 
+// 能力探测：在一个不显示的临时窗口上设置 WDA_EXCLUDEFROMCAPTURE 并读回，确认系统支持从截图中排除窗口。
+// 旧系统（Windows 10 2004 之前）设置失败或读回值不符
+pub fn probe_exclude_from_capture() -> Result<(), String> {
+    unsafe {
+        let hwnd = CreateWindowExW(
+            WS_EX_TOOLWINDOW,
+            windows::core::w!("STATIC"),
+            windows::core::w!(""),
+            WS_POPUP,
+            0,
+            0,
+            1,
+            1,
+            None,
+            None,
+            None,
+            None,
+        )
+        .map_err(|e| format!("CreateWindowExW failed: {e}"))?;
+        let result = SetWindowDisplayAffinity(hwnd, WINDOW_DISPLAY_AFFINITY(WDA_EXCLUDEFROMCAPTURE.0))
+            .map_err(|e| format!("SetWindowDisplayAffinity failed: {e}"))
+            .and_then(|_| {
+                let mut affinity: u32 = 0;
+                GetWindowDisplayAffinity(hwnd, &mut affinity).map_err(|e| format!("GetWindowDisplayAffinity failed: {e}"))?;
+                if affinity == WDA_EXCLUDEFROMCAPTURE.0 {
+                    Ok(())
+                } else {
+                    Err(format!("affinity reads back as 0x{:x}", affinity))
+                }
+            });
+        let _ = DestroyWindow(hwnd);
+        result
+    }
+}
+
+// overlay 的真实窗口状态：用于前端展示“已保护”指示，而非仅表示“已创建”
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OverlayStatus {
+    pub exists: bool,
+    pub visible: bool,
+    pub topmost: bool,
+    pub excluded_from_capture: bool,
+}
+
+pub fn overlay_status() -> OverlayStatus {
+    let Some(window) = OverlayState::get_window() else {
+        return OverlayStatus::default();
+    };
+    let hwnd = match window.hwnd() {
+        Ok(h) => HWND(h.0 as *mut _),
+        Err(e) => {
+            warn!("[overlay_status] Failed to get window handle: {:?}", e);
+            return OverlayStatus::default();
+        }
+    };
+    unsafe {
+        // 窗口可能已被关闭而 OverlayState 仍持有旧句柄
+        if !IsWindow(Some(hwnd)).as_bool() {
+            return OverlayStatus::default();
+        }
+        let exstyle = GetWindowLongW(hwnd, GWL_EXSTYLE);
+        let mut affinity: u32 = 0;
+        let excluded = GetWindowDisplayAffinity(hwnd, &mut affinity).is_ok()
+            && affinity == WDA_EXCLUDEFROMCAPTURE.0;
+        OverlayStatus {
+            exists: true,
+            visible: IsWindowVisible(hwnd).as_bool(),
+            topmost: exstyle & WS_EX_TOPMOST.0 as i32 != 0,
+            excluded_from_capture: excluded,
+        }
+    }
+}
+
+// 可重复调用：取出并清空 OverlayState 后关闭，窗口已被关闭（如随应用退出）时只记录
 pub fn close_overlay_window() {
-    if let Some(window) = OverlayState::get_window() {
-        window.close().unwrap();
+    let Some(window) = OverlayState::take_window() else {
+        return;
+    };
+    if let Err(e) = window.close() {
+        warn!("[close_overlay_window] close overlay window failed: {}", e);
+    }
+}
+
+// 关闭副显示器的 overlay；窗口不存在时忽略
+pub fn close_labeled_overlay_window(label: &str) {
+    let Ok(app) = AppState::get_global() else {
+        return;
+    };
+    if let Some(window) = app.handle.get_webview_window(label) {
+        if let Err(e) = window.close() {
+            warn!("[close_overlay_window] close overlay window {} failed: {}", label, e);
+        }
     }
 }
@@ -1,10 +1,13 @@
-use crate::mosaic::Mosaic;
+use crate::config::{self, MosaicPadding};
+use crate::mosaic::{Mosaic, MosaicStyle};
 use crate::utils::rect::Rect;
-use log::{info};
+use crate::utils::sync::lock_or_recover;
+use log::{debug, info, warn};
 use std::sync::{OnceLock, Mutex};
 use std::sync::atomic::{AtomicU64, Ordering};
+use serde::Serialize;
 use serde_json::Value;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::app::AppState;
 use tauri::Emitter;
@@ -13,42 +16,197 @@ use crate::overlay::overlay_state::OverlayState;
 
 static LATEST_MOSAIC: OnceLock<Mutex<Option<Value>>> = OnceLock::new();
 static SEQ: AtomicU64 = AtomicU64::new(0);
+// 副显示器 overlay 的遮罩序号：与 SEQ 分开计数，避免干扰工作显示器“当前遮罩是否仍为检测结果”的判断
+static EXTRA_SEQ: AtomicU64 = AtomicU64::new(0);
 
-// 最近一次需要主动推送给前端的 payload（仅保留最新），按 ~60fps 节流
+// seq 统计：已投递的最新 seq、被覆盖未投递的 seq 总数与单次最大缺口
+static LAST_EMITTED_SEQ: AtomicU64 = AtomicU64::new(0);
+static EMITTED_COUNT: AtomicU64 = AtomicU64::new(0);
+static SKIPPED_SEQ: AtomicU64 = AtomicU64::new(0);
+static MAX_SEQ_GAP: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MosaicEmitStats {
+    pub generated: u64,
+    pub emitted: u64,
+    pub skipped: u64,
+    pub last_emitted_seq: u64,
+    pub max_gap: u64,
+}
+
+// 手动注入的测试框（显示器物理像素坐标），绕过截图与检测，每帧追加到遮罩列表末尾
+static TEST_BOXES: OnceLock<Mutex<Vec<Rect>>> = OnceLock::new();
+
+fn test_boxes() -> &'static Mutex<Vec<Rect>> {
+    TEST_BOXES.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+fn test_mosaics() -> Vec<Mosaic> {
+    lock_or_recover(test_boxes(), "test_boxes")
+        .iter()
+        .map(|r| Mosaic { x: r.x, y: r.y, width: r.width, height: r.height, angle: 0.0, color: None, alpha: None })
+        .collect()
+}
+
+// 漏检保持：记录最近下发的检测框及其最后一次被检测到的时间；
+// 新一帧中没有 IoU 匹配的旧框在 mosaic_hold_ms 内继续下发并线性淡出，匹配到的由新框取代。
+// 静止画面下监控循环跳过未变化的帧、不再调用 hold_recent，淡出由推送线程按时间继续驱动（见 fade_held_payload）
+const HOLD_MATCH_IOU: f32 = 0.3;
+
+struct HeldMosaic {
+    mosaic: Mosaic,
+    last_seen: Instant,
+}
+
+struct HoldState {
+    entries: Vec<HeldMosaic>,
+    // 最近一次 hold_recent 的时间：last_seen 早于它的是本帧未检测到、正在淡出的框
+    frame_at: Instant,
+}
+
+static HELD: OnceLock<Mutex<HoldState>> = OnceLock::new();
+
+fn held() -> &'static Mutex<HoldState> {
+    HELD.get_or_init(|| Mutex::new(HoldState { entries: Vec::new(), frame_at: Instant::now() }))
+}
+
+fn mosaic_hold() -> Duration {
+    let ms = config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.mosaic_hold_ms)
+        .unwrap_or(0);
+    Duration::from_millis(ms)
+}
+
+fn held_rect(m: &Mosaic) -> Rect {
+    Rect::new(m.x, m.y, m.width, m.height)
+}
+
+// 按 now 生成待下发的遮罩：本帧检测到的框在前且不透明，淡出中的框随后，透明度按距最后一次检测的时间线性下降
+fn render_held(entries: &[HeldMosaic], frame_at: Instant, now: Instant, hold: Duration) -> Vec<Mosaic> {
+    let detected = entries.iter().filter(|h| h.last_seen >= frame_at).map(|h| h.mosaic.clone());
+    let fading = entries.iter().filter(|h| h.last_seen < frame_at).map(|h| {
+        let remain = 1.0 - now.duration_since(h.last_seen).as_secs_f32() / hold.as_secs_f32();
+        Mosaic { alpha: Some(remain.clamp(0.0, 1.0)), ..h.mosaic.clone() }
+    });
+    detected.chain(fading).collect()
+}
+
+// 合并本帧检测框与仍在保持期内的旧框，返回待下发的遮罩
+fn hold_recent(detected: Vec<Mosaic>) -> Vec<Mosaic> {
+    let hold = mosaic_hold();
+    let mut state = lock_or_recover(held(), "held_mosaics");
+    if hold.is_zero() {
+        state.entries.clear();
+        return detected;
+    }
+    let now = Instant::now();
+    state.entries.retain(|h| {
+        let r = held_rect(&h.mosaic);
+        now.duration_since(h.last_seen) < hold && !detected.iter().any(|d| held_rect(d).iou(&r) >= HOLD_MATCH_IOU)
+    });
+    if !state.entries.is_empty() {
+        debug!("[hold_recent] holding {} undetected mosaics", state.entries.len());
+    }
+    state.entries.extend(detected.into_iter().map(|mosaic| HeldMosaic { mosaic, last_seen: now }));
+    state.frame_at = now;
+    render_held(&state.entries, now, now, hold)
+}
+
+// 两帧之间按时间继续淡出：有淡出中的框时丢弃已过期的框并返回本次应下发的遮罩，
+// 过期的框在其消失后的第一次调用中仍返回一次（不含该框），使前端移除它；没有淡出中的框时返回 None
+fn fade_held(state: &mut HoldState, now: Instant, hold: Duration) -> Option<Vec<Mosaic>> {
+    let frame_at = state.frame_at;
+    if !state.entries.iter().any(|h| h.last_seen < frame_at) {
+        return None;
+    }
+    state.entries.retain(|h| h.last_seen >= frame_at || now.duration_since(h.last_seen) < hold);
+    Some(render_held(&state.entries, frame_at, now, hold))
+}
+
+// 推送线程在没有新 payload 时调用：基于最新 payload 重发淡出后的遮罩（锁定期间不淡出）
+fn fade_held_payload() -> Option<Value> {
+    if crate::system::monitoring::mosaics_locked() {
+        return None;
+    }
+    let hold = mosaic_hold();
+    let mut mosaics = fade_held(&mut lock_or_recover(held(), "held_mosaics"), Instant::now(), hold)?;
+    mosaics.extend(test_mosaics());
+    let mut payload = get_latest_mosaic_payload()?;
+    let seq = SEQ.fetch_add(1, Ordering::SeqCst) + 1;
+    let now_ms: i64 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    if let Value::Object(ref mut map) = payload {
+        map.insert("mosaics".to_string(), serde_json::json!(mosaics));
+        map.insert("seq".to_string(), serde_json::json!(seq));
+        map.insert("ts".to_string(), serde_json::json!(now_ms));
+    }
+    set_latest(&payload);
+    Some(payload)
+}
+
+// 丢弃保持中的遮罩（清除遮罩、停止监控时调用）
+pub fn clear_held_mosaics() {
+    lock_or_recover(held(), "held_mosaics").entries.clear();
+}
+
+// 最近一次需要主动推送给前端的 payload（仅保留最新），按 overlay_fps（缺省 ~60fps）节流
 static MOSAIC_EMIT_BUF: OnceLock<Mutex<Option<Value>>> = OnceLock::new();
 static MOSAIC_EMIT_THREAD: OnceLock<()> = OnceLock::new();
+// 推送节拍（ms），0 表示尚未读取配置
+static EMIT_INTERVAL_MS: AtomicU64 = AtomicU64::new(0);
+
+// 最新一帧遮罩的序号，每次生成遮罩 payload 递增
+pub fn current_seq() -> u64 {
+    SEQ.load(Ordering::SeqCst)
+}
+
+// 按 overlay_fps 重新计算推送节拍；开始监控时调用
+pub fn refresh_emit_interval() -> u64 {
+    let fps = config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.overlay_fps)
+        .unwrap_or(60)
+        .clamp(15, 240);
+    let interval = (1000 / fps as u64).max(1);
+    EMIT_INTERVAL_MS.store(interval, Ordering::Relaxed);
+    debug!("[mosaic_emit] overlay_fps={} interval={} ms", fps, interval);
+    interval
+}
 
 fn set_latest(payload: &Value) {
     let lock = LATEST_MOSAIC.get_or_init(|| Mutex::new(None));
-    if let Ok(mut guard) = lock.lock() {
-        *guard = Some(payload.clone());
-    }
+    *lock_or_recover(lock, "latest_mosaic") = Some(payload.clone());
 }
 
 fn set_latest_for_emit(payload: &Value) {
     let lock = MOSAIC_EMIT_BUF.get_or_init(|| Mutex::new(None));
-    if let Ok(mut guard) = lock.lock() {
-        *guard = Some(payload.clone());
-    }
+    *lock_or_recover(lock, "mosaic_emit_buf") = Some(payload.clone());
 }
 
 fn spawn_emit_thread_once() {
     MOSAIC_EMIT_THREAD.get_or_init(|| {
         std::thread::spawn(|| {
             loop {
-                // 16ms 节拍（~60fps）
-                std::thread::sleep(Duration::from_millis(16));
+                let interval = match EMIT_INTERVAL_MS.load(Ordering::Relaxed) {
+                    0 => refresh_emit_interval(),
+                    v => v,
+                };
+                std::thread::sleep(Duration::from_millis(interval));
 
                 let payload_opt = {
                     let lock = MOSAIC_EMIT_BUF.get_or_init(|| Mutex::new(None));
-                    if let Ok(mut guard) = lock.lock() {
-                        guard.take()
-                    } else {
-                        None
-                    }
+                    lock_or_recover(lock, "mosaic_emit_buf").take()
                 };
+                // 没有新帧（如静止画面跳过了检测）时，保持中的遮罩继续按时间淡出
+                let payload_opt = payload_opt.or_else(fade_held_payload);
 
                 if let Some(mut payload) = payload_opt {
+                    if let Some(seq) = payload.get("seq").and_then(|v| v.as_u64()) {
+                        record_emitted_seq(seq);
+                    }
                     // 在投递前记录发送时间戳（毫秒）
                     let emit_ms = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0);
                     if let serde_json::Value::Object(ref mut map) = payload {
@@ -67,29 +225,202 @@ fn spawn_emit_thread_once() {
     });
 }
 
+// 记录一次投递：与上次投递的 seq 比较，差值即为在缓冲中被覆盖、未到达前端的帧数
+fn record_emitted_seq(seq: u64) {
+    EMITTED_COUNT.fetch_add(1, Ordering::Relaxed);
+    let last = LAST_EMITTED_SEQ.swap(seq, Ordering::SeqCst);
+    if last == 0 || seq <= last + 1 {
+        return;
+    }
+    let gap = seq - last - 1;
+    let total = SKIPPED_SEQ.fetch_add(gap, Ordering::Relaxed) + gap;
+    let prev_max = MAX_SEQ_GAP.fetch_max(gap, Ordering::Relaxed);
+    if gap > prev_max {
+        warn!("[mosaic_emit] seq gap grew to {} (seq {} -> {}), total skipped {}", gap, last, seq, total);
+    }
+}
+
+pub fn get_mosaic_emit_stats() -> MosaicEmitStats {
+    MosaicEmitStats {
+        generated: SEQ.load(Ordering::SeqCst),
+        emitted: EMITTED_COUNT.load(Ordering::Relaxed),
+        skipped: SKIPPED_SEQ.load(Ordering::Relaxed),
+        last_emitted_seq: LAST_EMITTED_SEQ.load(Ordering::SeqCst),
+        max_gap: MAX_SEQ_GAP.load(Ordering::Relaxed),
+    }
+}
+
+// 结构化 mosaic_style 对应的遮罩样式
+fn style_from_mosaic_style(s: &config::MosaicStyleConfig) -> MosaicStyle {
+    match s.kind {
+        config::MosaicKind::Solid => MosaicStyle::Solid { color: s.color.clone().unwrap_or_else(|| "#000000".to_string()) },
+        config::MosaicKind::Pixelate => MosaicStyle::Pixelate,
+        config::MosaicKind::Blur => MosaicStyle::Blur { radius: s.blur_radius.unwrap_or(12) },
+    }
+}
+
+// 当前遮罩样式：优先 cover_style，其次结构化的 mosaic_style；配置非法时回退到默认像素化
+pub fn cover_style() -> MosaicStyle {
+    let style = config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.cover_style.or_else(|| m.mosaic_style.structured().map(style_from_mosaic_style)))
+        .unwrap_or_default();
+    match style.validate() {
+        Ok(()) => style,
+        Err(e) => {
+            debug!("[cover_style] invalid cover_style, fallback to pixelate: {}", e);
+            MosaicStyle::default()
+        }
+    }
+}
+
+// 样式变化后按当前样式重发最新一帧，无需等待下一次检测
+pub fn reemit_with_current_style() {
+    let mut payload = match get_latest_mosaic_payload() {
+        Some(p) => p,
+        None => return,
+    };
+    payload["style"] = serde_json::to_value(cover_style()).unwrap_or(Value::Null);
+    payload["seq"] = Value::from(SEQ.fetch_add(1, Ordering::SeqCst) + 1);
+    set_latest(&payload);
+    set_latest_for_emit(&payload);
+    spawn_emit_thread_once();
+}
+
+fn feather_px() -> u32 {
+    config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.feather_px)
+        .unwrap_or(0)
+}
+
+// 像素化色块边长（物理像素）；缺省或 0 时为 None，前端沿用随框缩放的色块，超出范围时钳制
+pub fn pixelate_block_px() -> Option<u32> {
+    let v = config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| {
+            let from_style = m.mosaic_style.structured().filter(|s| s.kind == config::MosaicKind::Pixelate).and_then(|s| s.block_size);
+            m.pixelate_block_px.filter(|v| *v != 0).or(from_style)
+        })
+        .filter(|v| *v != 0)?;
+    let clamped = v.clamp(config::PIXELATE_BLOCK_MIN, config::PIXELATE_BLOCK_MAX);
+    if clamped != v {
+        debug!("[pixelate_block_px] {} out of range, clamped to {}", v, clamped);
+    }
+    Some(clamped)
+}
+
+fn active_border() -> bool {
+    config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.active_border)
+        .unwrap_or(false)
+}
+
 pub fn get_latest_mosaic_payload() -> Option<Value> {
     let lock = LATEST_MOSAIC.get_or_init(|| Mutex::new(None));
-    lock.lock().ok().and_then(|g| g.clone())
+    lock_or_recover(lock, "latest_mosaic").clone()
+}
+
+// 注入一个测试框并立即推送一帧；该框在 clear_mosaics 或停止监控前一直保留
+pub fn inject_test_box(rect: Rect, dpi_scale: f64) -> Result<(), String> {
+    if rect.width <= 0 || rect.height <= 0 {
+        return Err(format!("invalid test box size {}x{}", rect.width, rect.height));
+    }
+    info!("[inject_test_box] {:?}", rect);
+    lock_or_recover(test_boxes(), "test_boxes").push(rect);
+    apply_mosaic_with_angle(Vec::new(), None, 1.0, dpi_scale);
+    Ok(())
+}
+
+// 清除测试框并推送一帧空遮罩；监控进行中时下一帧检测结果会重新覆盖
+pub fn clear_mosaics(dpi_scale: f64) {
+    lock_or_recover(test_boxes(), "test_boxes").clear();
+    clear_held_mosaics();
+    apply_mosaic_with_angle(Vec::new(), None, 1.0, dpi_scale);
+}
+
+pub fn clear_test_boxes() {
+    lock_or_recover(test_boxes(), "test_boxes").clear();
+}
+
+// 最新一帧遮罩是否完全覆盖该点（工作显示器内的物理像素坐标，与 inject_test_box 一致）。
+// 旋转框先把点转到框的局部坐标再判断；淡出或漏检保持中的半透明遮罩不算覆盖
+pub fn covers_point(x: i32, y: i32) -> bool {
+    let Some(payload) = get_latest_mosaic_payload() else {
+        return false;
+    };
+    let frame_alpha = payload.get("alpha").and_then(|a| a.as_f64()).unwrap_or(1.0) as f32;
+    if frame_alpha < 1.0 {
+        return false;
+    }
+    let mosaics: Vec<Mosaic> = payload
+        .get("mosaics")
+        .and_then(|m| serde_json::from_value(m.clone()).ok())
+        .unwrap_or_default();
+    mosaics.iter().filter(|m| m.alpha.unwrap_or(1.0) >= 1.0).any(|m| {
+        let rect = Rect::new(m.x, m.y, m.width, m.height);
+        if m.angle == 0.0 {
+            return rect.contains_point(x, y);
+        }
+        let cx = m.x as f32 + m.width as f32 / 2.0;
+        let cy = m.y as f32 + m.height as f32 / 2.0;
+        let (sin, cos) = (-m.angle).to_radians().sin_cos();
+        let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+        let lx = cx + dx * cos - dy * sin;
+        let ly = cy + dx * sin + dy * cos;
+        rect.contains_point(lx.floor() as i32, ly.floor() as i32)
+    })
+}
+
+fn mosaic_padding() -> MosaicPadding {
+    config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.mosaic_padding)
+        .unwrap_or_default()
+}
+
+// 检测框 -> 遮罩框：先按 mosaic_padding 各边外扩（框的局部方向，旋转框的中心偏移随角度旋转），
+// 再以 mosaic_scale 绕新中心缩放。未旋转的框裁剪到显示器范围；旋转框由 overlay 按屏幕裁剪，避免裁剪改变形状后露出边角
+fn expand_rect(rect: &Rect, angle: f32, s: f32, pad: &MosaicPadding, bounds: Option<&Rect>) -> Rect {
+    let (w, h) = (rect.width as f32, rect.height as f32);
+    let (l, r, t, b) = pad.edges_px(w, h);
+    let pw = (w + l + r).max(1.0);
+    let ph = (h + t + b).max(1.0);
+    let (ox, oy) = ((r - l) / 2.0, (b - t) / 2.0);
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let cx = rect.x as f32 + w / 2.0 + ox * cos - oy * sin;
+    let cy = rect.y as f32 + h / 2.0 + ox * sin + oy * cos;
+    let (nw, nh) = (pw * s, ph * s);
+    let out = Rect::new((cx - nw / 2.0).round() as i32, (cy - nh / 2.0).round() as i32, nw.round() as i32, nh.round() as i32);
+    match bounds {
+        Some(bounds) if angle == 0.0 => out.intersection(bounds).unwrap_or(out),
+        _ => out,
+    }
+}
+
+fn monitor_bounds() -> Option<Rect> {
+    crate::system::monitoring::working_monitor().map(|m| Rect::new(0, 0, m.width, m.height))
+}
+
+// 录制、烘焙遮罩等在截图上合成遮罩的路径使用同一套外扩与缩放，与 overlay 显示一致
+pub fn mosaic_rect(rect: &Rect, mosaic_scale: f32) -> Rect {
+    expand_rect(rect, 0.0, mosaic_scale, &mosaic_padding(), monitor_bounds().as_ref())
 }
 
 pub fn apply_mosaic(rects: Vec<Rect>, mosaic_scale: f32, dpi_scale: f64) {
-    // 在发送给 overlay 前进行缩放：保持中心不变
-    // 公式：w' = w*s, h' = h*s, x' = x - (w' - w)/2, y' = y - (h' - h)/2
-    let s = mosaic_scale;
+    // 在发送给 overlay 前外扩并缩放
+    let pad = mosaic_padding();
+    let bounds = monitor_bounds();
     let mosaics: Vec<Mosaic> = rects
         .into_iter()
         .map(|rect| {
-            let new_w_f = (rect.width as f32) * s;
-            let new_h_f = (rect.height as f32) * s;
-            let dx = ((new_w_f - rect.width as f32) / 2.0).round() as i32;
-            let dy = ((new_h_f - rect.height as f32) / 2.0).round() as i32;
-            let w = new_w_f.round() as i32;
-            let h = new_h_f.round() as i32;
-            let x = rect.x - dx;
-            let y = rect.y - dy;
-            Mosaic { x, y, width: w, height: h, angle: 0.0 }
+            let r = expand_rect(&rect, 0.0, mosaic_scale, &pad, bounds.as_ref());
+            Mosaic { x: r.x, y: r.y, width: r.width, height: r.height, angle: 0.0, color: None, alpha: None }
         })
         .collect();
+    let mut mosaics = hold_recent(mosaics);
+    mosaics.extend(test_mosaics());
     
     info!("[apply_mosaic] Applying {} mosaics (mosaic_scale={}, dpi_scale={})", mosaics.len(), mosaic_scale, dpi_scale);
     
@@ -104,6 +435,13 @@ pub fn apply_mosaic(rects: Vec<Rect>, mosaic_scale: f32, dpi_scale: f64) {
         "mosaics": mosaics,
         // 传给前端用于 DPI 适配（overlay.html 按此除以坐标）
         "scale_factor": dpi_scale,
+        // 边缘羽化宽度（物理像素），0 为硬边
+        "feather_px": feather_px(),
+        // 像素化色块边长（物理像素），null 时随框大小缩放
+        "pixelate_block_px": pixelate_block_px(),
+        // 脉动描边由前端按时间自行动画，后端只下发开关
+        "active_border": active_border(),
+        "style": cover_style(),
         "seq": seq,
         "ts": now_ms
     });
@@ -113,24 +451,22 @@ pub fn apply_mosaic(rects: Vec<Rect>, mosaic_scale: f32, dpi_scale: f64) {
     spawn_emit_thread_once();
 }
 
-// 带角度版本：items 为 (Rect, angle_deg)
-pub fn apply_mosaic_with_angle(items: Vec<(Rect, f32)>, mosaic_scale: f32, dpi_scale: f64) {
-    // 在发送给 overlay 前进行缩放：保持中心不变
-    let s = mosaic_scale;
+// 带角度版本：items 为 (Rect, angle_deg)；colors 与 items 一一对应（纯色 auto 样式时由 cal() 采样）
+pub fn apply_mosaic_with_angle(items: Vec<(Rect, f32)>, colors: Option<Vec<String>>, mosaic_scale: f32, dpi_scale: f64) {
+    // 在发送给 overlay 前外扩并缩放
+    let pad = mosaic_padding();
+    let bounds = monitor_bounds();
+    let mut colors = colors.map(|c| c.into_iter());
     let mosaics: Vec<Mosaic> = items
         .into_iter()
         .map(|(rect, angle)| {
-            let new_w_f = (rect.width as f32) * s;
-            let new_h_f = (rect.height as f32) * s;
-            let dx = ((new_w_f - rect.width as f32) / 2.0).round() as i32;
-            let dy = ((new_h_f - rect.height as f32) / 2.0).round() as i32;
-            let w = new_w_f.round() as i32;
-            let h = new_h_f.round() as i32;
-            let x = rect.x - dx;
-            let y = rect.y - dy;
-            Mosaic { x, y, width: w, height: h, angle }
+            let r = expand_rect(&rect, angle, mosaic_scale, &pad, bounds.as_ref());
+            let color = colors.as_mut().and_then(|c| c.next());
+            Mosaic { x: r.x, y: r.y, width: r.width, height: r.height, angle, color, alpha: None }
         })
         .collect();
+    let mut mosaics = hold_recent(mosaics);
+    mosaics.extend(test_mosaics());
 
     info!("[apply_mosaic_with_angle] Applying {} mosaics (mosaic_scale={}, dpi_scale={})", mosaics.len(), mosaic_scale, dpi_scale);
 
@@ -142,10 +478,149 @@ pub fn apply_mosaic_with_angle(items: Vec<(Rect, f32)>, mosaic_scale: f32, dpi_s
     let payload = serde_json::json!({
         "mosaics": mosaics,
         "scale_factor": dpi_scale,
+        "feather_px": feather_px(),
+        "pixelate_block_px": pixelate_block_px(),
+        "active_border": active_border(),
+        "style": cover_style(),
         "seq": seq,
         "ts": now_ms
     });
     set_latest(&payload);
     set_latest_for_emit(&payload);
     spawn_emit_thread_once();
-}
\ No newline at end of file
+}
+
+// 副显示器 overlay：按窗口标签直接投递（检测节拍已限制频率，不经节流线程）；
+// payload 带 overlay 标签，各 overlay 只处理发给自己的帧。漏检保持、测试框与最新帧缓存只作用于工作显示器
+pub fn apply_mosaic_to_overlay(label: &str, items: Vec<(Rect, f32)>, mosaic_scale: f32, dpi_scale: f64, bounds: &Rect) {
+    let pad = mosaic_padding();
+    let mosaics: Vec<Mosaic> = items
+        .into_iter()
+        .map(|(rect, angle)| {
+            let r = expand_rect(&rect, angle, mosaic_scale, &pad, Some(bounds));
+            Mosaic { x: r.x, y: r.y, width: r.width, height: r.height, angle, color: None, alpha: None }
+        })
+        .collect();
+    debug!("[apply_mosaic_to_overlay] {} mosaics -> {}", mosaics.len(), label);
+    let seq = EXTRA_SEQ.fetch_add(1, Ordering::SeqCst) + 1;
+    let now_ms: i64 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    let payload = serde_json::json!({
+        "mosaics": mosaics,
+        "scale_factor": dpi_scale,
+        "feather_px": feather_px(),
+        "pixelate_block_px": pixelate_block_px(),
+        "active_border": active_border(),
+        "style": cover_style(),
+        "seq": seq,
+        "ts": now_ms,
+        "emit_ts": now_ms,
+        "overlay": label
+    });
+    if let Ok(app) = AppState::get_global() {
+        let _ = app.handle.emit_to(label, "mosaic-update", payload);
+    }
+}
+
+// 停止监控前的淡出：基于最新一帧遮罩，按 ~60fps 逐步降低 alpha 并直接投递给 overlay，阻塞直至完成
+pub fn fade_out_mosaics(duration_ms: u64) {
+    if duration_ms == 0 {
+        return;
+    }
+    // 清空待发送缓冲，避免节流线程在淡出过程中插入一帧不透明的旧 payload
+    {
+        let lock = MOSAIC_EMIT_BUF.get_or_init(|| Mutex::new(None));
+        lock_or_recover(lock, "mosaic_emit_buf").take();
+    }
+    let base = match get_latest_mosaic_payload() {
+        Some(p) => p,
+        None => return,
+    };
+    let has_mosaics = base
+        .get("mosaics")
+        .and_then(|m| m.as_array())
+        .map(|a| !a.is_empty())
+        .unwrap_or(false);
+    if !has_mosaics {
+        return;
+    }
+    let window = match OverlayState::get_window() {
+        Some(w) => w,
+        None => return,
+    };
+
+    let steps = (duration_ms / 16).max(1);
+    info!("[fade_out_mosaics] fading out over {} ms ({} steps)", duration_ms, steps);
+    for i in 1..=steps {
+        let alpha = 1.0 - (i as f64 / steps as f64);
+        let mut payload = base.clone();
+        if let Value::Object(ref mut map) = payload {
+            let seq = SEQ.fetch_add(1, Ordering::SeqCst) + 1;
+            let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0);
+            map.insert("seq".to_string(), serde_json::json!(seq));
+            map.insert("ts".to_string(), serde_json::json!(now_ms));
+            map.insert("emit_ts".to_string(), serde_json::json!(now_ms));
+            map.insert("alpha".to_string(), serde_json::json!(alpha));
+        }
+        set_latest(&payload);
+        let _ = window.emit("mosaic-update", payload);
+        std::thread::sleep(Duration::from_millis(16));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mosaic(x: i32) -> Mosaic {
+        Mosaic { x, y: 0, width: 100, height: 100, angle: 0.0, color: None, alpha: None }
+    }
+
+    fn ms(v: u64) -> Duration {
+        Duration::from_millis(v)
+    }
+
+    fn alpha_of(mosaics: &[Mosaic], x: i32) -> Option<f32> {
+        mosaics.iter().find(|m| m.x == x).and_then(|m| m.alpha)
+    }
+
+    #[test]
+    fn held_mosaic_keeps_fading_on_a_static_screen() {
+        let hold = ms(400);
+        let t0 = Instant::now();
+        // 帧 1（t0）检测到 A；帧 2（t0+100ms）漏检 A、只检测到 B，此后画面静止不再有新帧
+        let t1 = t0 + ms(100);
+        let mut state = HoldState {
+            entries: vec![
+                HeldMosaic { mosaic: mosaic(0), last_seen: t0 },
+                HeldMosaic { mosaic: mosaic(500), last_seen: t1 },
+            ],
+            frame_at: t1,
+        };
+        let shown = render_held(&state.entries, t1, t1, hold);
+        assert_eq!(shown[0].x, 500);
+        assert_eq!(shown[0].alpha, None);
+        assert!((alpha_of(&shown, 0).unwrap() - 0.75).abs() < 1e-4);
+
+        // 推送线程按时间继续淡出 A，B 保持不透明
+        let mid = fade_held(&mut state, t0 + ms(300), hold).unwrap();
+        assert!((alpha_of(&mid, 0).unwrap() - 0.25).abs() < 1e-4);
+        assert_eq!(alpha_of(&mid, 500), None);
+
+        // 过期后再下发一次（不含 A），之后不再重发
+        let gone = fade_held(&mut state, t0 + ms(450), hold).unwrap();
+        assert_eq!(gone.len(), 1);
+        assert_eq!(gone[0].x, 500);
+        assert!(fade_held(&mut state, t0 + ms(500), hold).is_none());
+    }
+
+    #[test]
+    fn nothing_to_fade_when_every_box_was_detected() {
+        let t0 = Instant::now();
+        let mut state = HoldState { entries: vec![HeldMosaic { mosaic: mosaic(0), last_seen: t0 }], frame_at: t0 };
+        assert!(fade_held(&mut state, t0 + ms(1000), ms(400)).is_none());
+        assert_eq!(state.entries.len(), 1);
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::Mutex;
 use once_cell::sync::Lazy;
 use tauri::WebviewWindow;
+use crate::utils::sync::lock_or_recover;
 
 static OVERLAY_STATE: Lazy<Mutex<Option<OverlayState>>> = Lazy::new(|| Mutex::new(None));
 
@@ -12,10 +13,15 @@ pub struct OverlayState {
 impl OverlayState {
 
     pub fn get_window() -> Option<WebviewWindow> {
-        OVERLAY_STATE.lock().unwrap().as_ref().map(|state| state.window.clone())
+        lock_or_recover(&OVERLAY_STATE, "overlay_state").as_ref().map(|state| state.window.clone())
     }
 
     pub fn set_window(window: WebviewWindow) {
-        *OVERLAY_STATE.lock().unwrap() = Some(OverlayState { window });
+        *lock_or_recover(&OVERLAY_STATE, "overlay_state") = Some(OverlayState { window });
+    }
+
+    // 取出并清空当前窗口，之后 get_window 返回 None
+    pub fn take_window() -> Option<WebviewWindow> {
+        lock_or_recover(&OVERLAY_STATE, "overlay_state").take().map(|state| state.window)
     }
 }
\ No newline at end of file
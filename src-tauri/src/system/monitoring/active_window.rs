@@ -0,0 +1,79 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use log::debug;
+use once_cell::sync::Lazy;
+use windows::Win32::Foundation::RECT;
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, GetWindowRect, GetWindowThreadProcessId, IsIconic, IsWindowVisible,
+};
+
+use crate::config;
+use crate::monitor::MonitorInfo;
+use crate::utils::rect::Rect;
+use crate::utils::sync::lock_or_recover;
+
+// 跟随活动窗口：0 = 沿用配置，1 = 运行时关闭，2 = 运行时开启
+const FOLLOW_FROM_CONFIG: u8 = 0;
+const FOLLOW_OFF: u8 = 1;
+const FOLLOW_ON: u8 = 2;
+static FOLLOW_OVERRIDE: AtomicU8 = AtomicU8::new(FOLLOW_FROM_CONFIG);
+
+// 最近一次跟踪到的（非本进程）窗口矩形，屏幕物理坐标
+static LAST_TRACKED: Lazy<Mutex<Option<Rect>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn is_enabled() -> bool {
+    match FOLLOW_OVERRIDE.load(Ordering::Relaxed) {
+        FOLLOW_ON => true,
+        FOLLOW_OFF => false,
+        _ => config::get_config()
+            .and_then(|c| c.monitoring)
+            .and_then(|m| m.follow_active_window)
+            .unwrap_or(false),
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    FOLLOW_OVERRIDE.store(if enabled { FOLLOW_ON } else { FOLLOW_OFF }, Ordering::Relaxed);
+    lock_or_recover(&LAST_TRACKED, "active_window").take();
+}
+
+pub fn reset() {
+    lock_or_recover(&LAST_TRACKED, "active_window").take();
+}
+
+// 每帧轮询前台窗口，返回其与工作显示器的交集（显示器内坐标）；
+// 窗口最小化/隐藏或不在该显示器上时返回 None，调用方据此暂停检测。
+// 前台为本应用窗口（如主界面）时沿用上一次跟踪的窗口，避免切回设置界面就丢失目标。
+pub fn tracked_rect(monitor: &MonitorInfo) -> Option<Rect> {
+    let screen_rect = unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return None;
+        }
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+        if pid == std::process::id() {
+            lock_or_recover(&LAST_TRACKED, "active_window").clone()?
+        } else {
+            if IsIconic(hwnd).as_bool() || !IsWindowVisible(hwnd).as_bool() {
+                debug!("[active_window] foreground window minimized or hidden, pause");
+                return None;
+            }
+            let mut r = RECT::default();
+            if GetWindowRect(hwnd, &mut r).is_err() {
+                return None;
+            }
+            let rect = Rect::new(r.left, r.top, r.right - r.left, r.bottom - r.top);
+            *lock_or_recover(&LAST_TRACKED, "active_window") = Some(rect.clone());
+            rect
+        }
+    };
+    let local = Rect::new(
+        screen_rect.x - monitor.x,
+        screen_rect.y - monitor.y,
+        screen_rect.width,
+        screen_rect.height,
+    );
+    local.intersection(&Rect::new(0, 0, monitor.width, monitor.height))
+}
@@ -0,0 +1,116 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{debug, info, warn};
+
+use crate::ai::faces::FaceDecision;
+use crate::ai::python_env;
+use crate::config;
+use crate::monitor::screen_shot::Image;
+
+// 识别判定审计：把每个判定（遮罩/未遮罩）的小缩略图与分数写入滚动目录，供阈值调优与误报分析
+const DEFAULT_MAX_FILES: usize = 500;
+const THUMBNAIL_MAX_SIDE: i32 = 96;
+// 待写队列上限；写盘跟不上时直接丢弃，绝不阻塞检测循环
+const QUEUE_CAPACITY: usize = 64;
+
+struct AuditItem {
+    thumbnail: Image,
+    score: Option<f32>,
+    covered: bool,
+    ts_ms: u128,
+    index: usize,
+}
+
+static WRITER: OnceLock<Option<SyncSender<AuditItem>>> = OnceLock::new();
+
+pub fn is_enabled() -> bool {
+    config::get_config()
+        .and_then(|c| c.system)
+        .and_then(|s| s.decision_audit)
+        .unwrap_or(false)
+}
+
+fn gallery_dir() -> Result<PathBuf, String> {
+    Ok(python_env::get_app_data_dir()?.join("decisions"))
+}
+
+fn writer() -> Option<&'static SyncSender<AuditItem>> {
+    WRITER
+        .get_or_init(|| {
+            let dir = match gallery_dir().and_then(|d| fs::create_dir_all(&d).map(|_| d).map_err(|e| e.to_string())) {
+                Ok(d) => d,
+                Err(e) => {
+                    warn!("[audit] decision gallery unavailable: {}", e);
+                    return None;
+                }
+            };
+            let max_files = config::get_config()
+                .and_then(|c| c.system)
+                .and_then(|s| s.decision_audit_max_files)
+                .unwrap_or(DEFAULT_MAX_FILES)
+                .max(1);
+            let (tx, rx) = sync_channel::<AuditItem>(QUEUE_CAPACITY);
+            std::thread::spawn(move || {
+                // 文件名以零填充时间戳开头，按名称排序即按时间排序
+                let mut files: VecDeque<PathBuf> = fs::read_dir(&dir)
+                    .map(|rd| rd.filter_map(|e| e.ok().map(|e| e.path())).filter(|p| p.is_file()).collect::<Vec<_>>())
+                    .map(|mut v| {
+                        v.sort();
+                        v.into()
+                    })
+                    .unwrap_or_default();
+                info!("[audit] writing decision thumbnails to {:?} (max {} files)", dir, max_files);
+                for item in rx {
+                    let score = item.score.map(|s| format!("{:.3}", s)).unwrap_or_else(|| "na".to_string());
+                    let name = format!(
+                        "{:015}_{:02}_{}_{}.png",
+                        item.ts_ms,
+                        item.index,
+                        if item.covered { "covered" } else { "uncovered" },
+                        score
+                    );
+                    let path = dir.join(name);
+                    match item.thumbnail.to_png().and_then(|png| fs::write(&path, png).map_err(|e| e.to_string())) {
+                        Ok(()) => files.push_back(path),
+                        Err(e) => debug!("[audit] write thumbnail failed: {}", e),
+                    }
+                    while files.len() > max_files {
+                        if let Some(old) = files.pop_front() {
+                            let _ = fs::remove_file(old);
+                        }
+                    }
+                }
+            });
+            Some(tx)
+        })
+        .as_ref()
+}
+
+// 在检测线程调用：仅做裁剪与缩小（缩略图很小），编码与写盘交给后台线程
+pub fn record(image: &Image, decisions: &[FaceDecision]) {
+    let Some(tx) = writer() else { return };
+    let ts_ms = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    for (index, d) in decisions.iter().enumerate() {
+        let Some(rect) = d.rect.intersection(&crate::utils::rect::Rect::new(0, 0, image.width, image.height)) else {
+            continue;
+        };
+        let crop = super::crop_image_bgra(image, &rect);
+        let longest = crop.width.max(crop.height).max(1);
+        let thumbnail = if longest > THUMBNAIL_MAX_SIDE {
+            super::downscale_image_bgra(&crop, THUMBNAIL_MAX_SIDE as f32 / longest as f32)
+        } else {
+            crop
+        };
+        let item = AuditItem { thumbnail, score: d.score, covered: d.covered, ts_ms, index };
+        match tx.try_send(item) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => debug!("[audit] writer busy, drop thumbnail"),
+            Err(TrySendError::Disconnected(_)) => return,
+        }
+    }
+}
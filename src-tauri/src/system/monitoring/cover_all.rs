@@ -0,0 +1,56 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::info;
+use once_cell::sync::Lazy;
+
+use crate::api::emitter;
+use crate::utils::sync::lock_or_recover;
+
+// 临时全屏遮罩：截止时间之前 cal() 跳过截图与检测，直接遮住整个显示器，到期后自动恢复检测驱动的遮罩
+static COVER_ALL_UNTIL: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+// 每次设置递增，旧的倒计时线程据此退出，避免重复提示
+static COVER_ALL_GEN: AtomicU64 = AtomicU64::new(0);
+
+pub fn is_active() -> bool {
+    let mut until = lock_or_recover(&COVER_ALL_UNTIL, "cover_all");
+    match *until {
+        Some(t) if Instant::now() < t => true,
+        Some(_) => {
+            until.take();
+            false
+        }
+        None => false,
+    }
+}
+
+// duration_ms 为 0 时取消；重复调用以最后一次为准
+pub fn set(duration_ms: u64) {
+    let generation = COVER_ALL_GEN.fetch_add(1, Ordering::SeqCst) + 1;
+    if duration_ms == 0 {
+        if lock_or_recover(&COVER_ALL_UNTIL, "cover_all").take().is_some() {
+            info!("[cover_all] cancelled");
+            emitter::emit_toast_close();
+        }
+        return;
+    }
+    let until = Instant::now() + Duration::from_millis(duration_ms);
+    *lock_or_recover(&COVER_ALL_UNTIL, "cover_all") = Some(until);
+    info!("[cover_all] covering the whole monitor for {} ms", duration_ms);
+
+    // 倒计时提示：每秒刷新剩余秒数，结束时关闭提示
+    std::thread::spawn(move || loop {
+        if COVER_ALL_GEN.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        let now = Instant::now();
+        if now >= until {
+            emitter::emit_toast_close();
+            return;
+        }
+        let remaining = until - now;
+        emitter::emit_toast(&format!("全屏遮罩中，{} 秒后恢复", remaining.as_secs_f32().ceil() as u64));
+        std::thread::sleep(remaining.min(Duration::from_secs(1)));
+    });
+}
@@ -0,0 +1,223 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Condvar, Mutex, OnceLock};
+
+use log::{debug, info};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::utils::sync::lock_or_recover;
+
+// 检测任务队列：监控线程与各副显示器的检测线程提交检测任务，由唯一的检测线程串行执行，显示器之间按提交先后轮转，避免某个显示器饿死。
+// 队列只串行化检测本身：last_decisions、validate_face_folder、sync_faces_library、reload_faces、preload_targets_* 等
+// 仍在各自的调用线程上直接获取 GIL，与检测线程竞争 GIL 而不经过本队列。
+// 丢弃策略：每个 id 最多保留一个待执行任务，新任务直接替换尚未开始的旧任务（latest-wins），
+// 被替换的任务随闭包一起丢弃，其提交方收到 None 并跳过该帧。监控线程提交后阻塞等待结果，
+// 因此同一显示器通常不会同时有两个待执行任务；替换发生在重启监控后旧线程的任务仍在排队、或并发调用 benchmark 等情形。
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct Pending {
+    jobs: HashMap<usize, Job>,
+    order: VecDeque<usize>,
+}
+
+static PENDING: Lazy<Mutex<Pending>> = Lazy::new(|| Mutex::new(Pending { jobs: HashMap::new(), order: VecDeque::new() }));
+static PENDING_CV: Condvar = Condvar::new();
+static WORKER: OnceLock<()> = OnceLock::new();
+
+static SUBMITTED: AtomicU64 = AtomicU64::new(0);
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+static COMPLETED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DetectionQueueStats {
+    pub submitted: u64,
+    pub dropped: u64,
+    pub completed: u64,
+    pub pending: usize,
+}
+
+fn spawn_worker_once() {
+    WORKER.get_or_init(|| {
+        info!("[detection_queue] starting detection worker");
+        std::thread::spawn(|| loop {
+            let job = {
+                let mut pending = lock_or_recover(&PENDING, "detection_queue");
+                loop {
+                    if let Some(id) = pending.order.pop_front() {
+                        if let Some(job) = pending.jobs.remove(&id) {
+                            break job;
+                        }
+                        continue;
+                    }
+                    pending = PENDING_CV.wait(pending).unwrap_or_else(|e| e.into_inner());
+                }
+            };
+            job();
+            COMPLETED.fetch_add(1, Ordering::Relaxed);
+        });
+    });
+}
+
+// 提交任务；若该显示器已有未开始的任务则替换之（被替换任务的接收端随即返回 Err）
+pub fn submit<R, F>(monitor_id: usize, f: F) -> mpsc::Receiver<R>
+where
+    R: Send + 'static,
+    F: FnOnce() -> R + Send + 'static,
+{
+    spawn_worker_once();
+    let (tx, rx) = mpsc::sync_channel(1);
+    let job: Job = Box::new(move || {
+        let _ = tx.send(f());
+    });
+    SUBMITTED.fetch_add(1, Ordering::Relaxed);
+    {
+        let mut pending = lock_or_recover(&PENDING, "detection_queue");
+        if pending.jobs.insert(monitor_id, job).is_some() {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+            debug!("[detection_queue] dropped stale job for monitor {}", monitor_id);
+        } else {
+            pending.order.push_back(monitor_id);
+        }
+    }
+    PENDING_CV.notify_one();
+    rx
+}
+
+// 提交并阻塞等待结果；任务被同一显示器的新任务替换时返回 None
+pub fn run_latest<R, F>(monitor_id: usize, f: F) -> Option<R>
+where
+    R: Send + 'static,
+    F: FnOnce() -> R + Send + 'static,
+{
+    submit(monitor_id, f).recv().ok()
+}
+
+// 丢弃所有尚未开始的任务（配置变化后旧帧不再可信），返回丢弃数
+pub fn clear_pending() -> usize {
+    let mut pending = lock_or_recover(&PENDING, "detection_queue");
+    let n = pending.jobs.len();
+    pending.jobs.clear();
+    pending.order.clear();
+    DROPPED.fetch_add(n as u64, Ordering::Relaxed);
+    n
+}
+
+pub fn stats() -> DetectionQueueStats {
+    DetectionQueueStats {
+        submitted: SUBMITTED.load(Ordering::Relaxed),
+        dropped: DROPPED.load(Ordering::Relaxed),
+        completed: COMPLETED.load(Ordering::Relaxed),
+        pending: lock_or_recover(&PENDING, "detection_queue").jobs.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    // 队列与检测线程是全局的，相关测试串行执行
+    static QUEUE_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    // 提交一个阻塞检测线程的任务，待其开始执行后返回；向返回的 Sender 发送后任务结束
+    fn occupy_worker(monitor_id: usize) -> (mpsc::Sender<()>, mpsc::Receiver<&'static str>) {
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let rx = submit(monitor_id, move || {
+            started_tx.send(()).unwrap();
+            let _ = release_rx.recv();
+            "busy"
+        });
+        started_rx.recv_timeout(Duration::from_secs(5)).expect("worker did not start the job");
+        (release_tx, rx)
+    }
+
+    #[test]
+    fn newer_job_replaces_pending_job_for_the_same_id() {
+        let _g = lock_or_recover(&QUEUE_TEST_LOCK, "queue_test");
+        let (release, busy) = occupy_worker(2000);
+        let old = submit(2001, || "old");
+        let new = submit(2001, || "new");
+        release.send(()).unwrap();
+        assert_eq!(busy.recv_timeout(Duration::from_secs(5)), Ok("busy"));
+        assert!(old.recv_timeout(Duration::from_secs(5)).is_err());
+        assert_eq!(new.recv_timeout(Duration::from_secs(5)), Ok("new"));
+    }
+
+    #[test]
+    fn replaced_job_keeps_its_turn_between_ids() {
+        let _g = lock_or_recover(&QUEUE_TEST_LOCK, "queue_test");
+        let order = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let push = |tag: &'static str| {
+            let order = std::sync::Arc::clone(&order);
+            move || lock_or_recover(&order, "order").push(tag)
+        };
+        let (release, busy) = occupy_worker(2002);
+        let a1 = submit(2003, push("a1"));
+        let b = submit(2004, push("b"));
+        let a2 = submit(2003, push("a2"));
+        release.send(()).unwrap();
+        assert_eq!(busy.recv_timeout(Duration::from_secs(5)), Ok("busy"));
+        assert!(a1.recv_timeout(Duration::from_secs(5)).is_err());
+        assert!(a2.recv_timeout(Duration::from_secs(5)).is_ok());
+        assert!(b.recv_timeout(Duration::from_secs(5)).is_ok());
+        // 替换不改变排队位置：2003 仍先于 2004 执行，执行的是较新的任务
+        assert_eq!(*lock_or_recover(&order, "order"), vec!["a2", "b"]);
+    }
+
+    #[test]
+    fn run_latest_returns_none_when_superseded() {
+        let _g = lock_or_recover(&QUEUE_TEST_LOCK, "queue_test");
+        let (release, busy) = occupy_worker(2005);
+        let waiter = std::thread::spawn(|| run_latest(2006, || "old"));
+        // 等旧任务入队后再提交新任务
+        while lock_or_recover(&PENDING, "detection_queue").jobs.get(&2006).is_none() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        let new = submit(2006, || "new");
+        assert_eq!(waiter.join().unwrap(), None);
+        release.send(()).unwrap();
+        assert_eq!(busy.recv_timeout(Duration::from_secs(5)), Ok("busy"));
+        assert_eq!(new.recv_timeout(Duration::from_secs(5)), Ok("new"));
+    }
+
+    #[test]
+    fn clear_pending_drops_queued_jobs() {
+        let _g = lock_or_recover(&QUEUE_TEST_LOCK, "queue_test");
+        let (release, busy) = occupy_worker(1000);
+        let a = submit(1001, || 1);
+        let b = submit(1002, || 2);
+        assert_eq!(clear_pending(), 2);
+        // 任务随闭包一起丢弃，接收端立即断开（run_latest 据此返回 None）
+        assert!(a.recv_timeout(Duration::from_secs(5)).is_err());
+        assert!(b.recv_timeout(Duration::from_secs(5)).is_err());
+        release.send(()).unwrap();
+        assert_eq!(busy.recv_timeout(Duration::from_secs(5)), Ok("busy"));
+    }
+
+    #[test]
+    fn generation_bump_discards_in_flight_result() {
+        let _g = lock_or_recover(&QUEUE_TEST_LOCK, "queue_test");
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let detection = std::thread::spawn(move || {
+            super::super::run_detection(1003, move || {
+                started_tx.send(()).unwrap();
+                let _ = release_rx.recv();
+                "stale"
+            })
+        });
+        started_rx.recv_timeout(Duration::from_secs(5)).expect("worker did not start the job");
+        // 检测进行中流水线被重置（等同 reset_pipeline 的代数递增）
+        super::super::PIPELINE_GENERATION.fetch_add(1, Ordering::SeqCst);
+        release_tx.send(()).unwrap();
+        assert!(detection.join().unwrap().is_none());
+
+        let generation = super::super::PIPELINE_GENERATION.load(Ordering::SeqCst);
+        assert_eq!(super::super::run_detection(1003, || "fresh"), Some(("fresh", generation)));
+    }
+}
@@ -0,0 +1,82 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use log::{error, info};
+use windows::core::w;
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW, TranslateMessage, MSG,
+    WINDOW_STYLE, WM_DISPLAYCHANGE, WNDCLASSW, WS_EX_TOOLWINDOW,
+};
+
+// 分辨率变化或显示器插拔：系统向所有顶层窗口广播 WM_DISPLAYCHANGE（仅消息窗口收不到广播，
+// 因此这里用一个从不显示的顶层窗口接收），收到后置位，由 cal() 在下一轮重新枚举显示器
+static PENDING: AtomicBool = AtomicBool::new(false);
+static LISTENER: OnceLock<()> = OnceLock::new();
+
+// 取出待处理标记
+pub fn take_pending() -> bool {
+    PENDING.swap(false, Ordering::SeqCst)
+}
+
+// 启动显示配置变化监听（仅一次）
+pub fn ensure_listener() {
+    LISTENER.get_or_init(|| {
+        std::thread::spawn(|| {
+            if let Err(e) = unsafe { run_listener() } {
+                error!("[display_change] display change listener failed: {}", e);
+            }
+        });
+    });
+}
+
+unsafe fn run_listener() -> Result<(), String> {
+    let instance = GetModuleHandleW(None).map_err(|e| format!("GetModuleHandleW failed: {e}"))?;
+    let class_name = w!("ScreenGhostDisplayListener");
+    let wc = WNDCLASSW {
+        lpfnWndProc: Some(wnd_proc),
+        hInstance: instance.into(),
+        lpszClassName: class_name,
+        ..Default::default()
+    };
+    if RegisterClassW(&wc) == 0 {
+        return Err("RegisterClassW failed".to_string());
+    }
+    // 不调用 ShowWindow，窗口始终隐藏；WS_EX_TOOLWINDOW 保证不会出现在任务栏与 Alt+Tab 中
+    CreateWindowExW(
+        WS_EX_TOOLWINDOW,
+        class_name,
+        w!(""),
+        WINDOW_STYLE::default(),
+        0,
+        0,
+        0,
+        0,
+        None,
+        None,
+        Some(instance.into()),
+        None,
+    )
+    .map_err(|e| format!("CreateWindowExW failed: {e}"))?;
+    info!("[display_change] display change listener started");
+
+    let mut msg = MSG::default();
+    while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+        let _ = TranslateMessage(&msg);
+        DispatchMessageW(&msg);
+    }
+    Ok(())
+}
+
+extern "system" fn wnd_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if msg == WM_DISPLAYCHANGE {
+        // wparam 为新的色深，lparam 低/高 16 位为主显示器新的宽/高
+        let width = lparam.0 & 0xFFFF;
+        let height = (lparam.0 >> 16) & 0xFFFF;
+        info!("[display_change] display settings changed (primary {}x{})", width, height);
+        PENDING.store(true, Ordering::SeqCst);
+        return LRESULT(0);
+    }
+    unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+}
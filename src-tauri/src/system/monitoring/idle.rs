@@ -0,0 +1,70 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use log::{debug, info, warn};
+
+use super::{last_frame_buf, release_capture_resources_locked, MonitorState};
+use crate::ai::faces;
+use crate::config;
+use crate::utils::sync::lock_or_recover;
+
+// 空闲资源回收：常驻托盘时未监控期间释放截图资源（DXGI 复制接口、纹理、输出缓冲区）与缓存帧，
+// 可选释放识别模型（ORT 会话，回收显存）。各资源均在下次使用时惰性重建：截图由 DirectXResourceManager
+// 按需初始化，模型由 faces.py 在下一次检测/提取特征时以上次的 provider 重新加载
+
+// 空闲检查间隔
+const IDLE_POLL: Duration = Duration::from_secs(5);
+
+static WATCHER: OnceLock<()> = OnceLock::new();
+
+/// 立即释放空闲资源；监控进行中时拒绝（截图与检测正在使用这些资源）
+pub fn release_idle_resources(release_model: bool) -> Result<(), String> {
+    if MonitorState::is_working_set() {
+        return Err("monitoring is running".to_string());
+    }
+    release_capture_resources_locked();
+    lock_or_recover(last_frame_buf(), "last_frame").take();
+    if release_model {
+        match faces::release_face_model() {
+            Ok(true) => info!("[release_idle_resources] face model released"),
+            Ok(false) => debug!("[release_idle_resources] face model was not loaded"),
+            Err(e) => warn!("[release_idle_resources] release face model failed: {}", e),
+        }
+    }
+    info!("[release_idle_resources] idle resources released (release_model={})", release_model);
+    Ok(())
+}
+
+// 按 idle_release_secs 在停止监控一段时间后自动回收（每段空闲期只回收一次）；未配置时不做任何事
+pub fn ensure_watcher() {
+    WATCHER.get_or_init(|| {
+        std::thread::spawn(|| {
+            let mut idle_since: Option<Instant> = None;
+            let mut released = false;
+            loop {
+                std::thread::sleep(IDLE_POLL);
+                if MonitorState::is_working_set() {
+                    idle_since = None;
+                    released = false;
+                    continue;
+                }
+                let m = config::get_config().and_then(|c| c.monitoring);
+                let after = match m.as_ref().and_then(|m| m.idle_release_secs).filter(|s| *s > 0) {
+                    Some(s) => Duration::from_secs(s),
+                    None => continue,
+                };
+                let since = *idle_since.get_or_insert_with(Instant::now);
+                if released || since.elapsed() < after {
+                    continue;
+                }
+                let release_model = m.and_then(|m| m.idle_release_model).unwrap_or(false);
+                info!("[idle] idle for {} s, releasing resources", since.elapsed().as_secs());
+                // 与开始监控竞争时 release_idle_resources 会拒绝，下一段空闲期再回收
+                if let Err(e) = release_idle_resources(release_model) {
+                    debug!("[idle] release skipped: {}", e);
+                }
+                released = true;
+            }
+        });
+    });
+}
@@ -1,28 +1,189 @@
+mod active_window;
+mod audit;
+mod cover_all;
+pub mod detection_queue;
+mod display_change;
+mod idle;
 mod monitor_state;
+mod mosaic_lock;
+mod multi_monitor;
+pub mod perf;
+pub mod recording;
+mod taskbar;
+mod visibility;
 
+pub use active_window::{is_enabled as is_following_active_window, set_enabled as set_follow_active_window};
+pub use taskbar::{is_enabled as is_excluding_taskbar, set_enabled as set_exclude_taskbar};
+pub use visibility::{is_enabled as is_pausing_when_hidden, set_enabled as set_pause_when_hidden};
+pub use idle::{ensure_watcher as ensure_idle_watcher, release_idle_resources};
 pub use monitor_state::MonitorState;
 
-use log::{error, debug, info};
+use log::{error, debug, info, warn};
+use serde::Serialize;
 use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::sync::OnceLock;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Mutex as StdMutex;
 
-use crate::{ai::{faces}, api::emitter, config, monitor::{MonitorInfo, screen_shot}, overlay};
-use crate::utils::rect::Rect;
+use crate::{ai::{faces, tracker}, api::emitter, config, monitor::{CaptureError, MonitorInfo, screen_shot}, overlay};
+use crate::config::{DetectionErrorPolicy, DownscaleFilter};
+use crate::utils::rect::{merge_close, nms, Rect};
+use crate::utils::sync::lock_or_recover;
 
 static THREAD: Mutex<Option<std::thread::JoinHandle<()>>> = Mutex::new(None);
 
 // 预取下一帧：单帧缓冲 + 去重控制
-static NEXT_FRAME: OnceLock<Mutex<Option<screen_shot::Image>>> = OnceLock::new();
+static NEXT_FRAME: OnceLock<Mutex<Option<screen_shot::CaptureOutcome>>> = OnceLock::new();
 static PREFETCHING: AtomicBool = AtomicBool::new(false);
 static CAPTURE_LOCK: OnceLock<StdMutex<()>> = OnceLock::new();
+// 流水线代次：实时配置变化时递增，检测期间代次变化的结果视为过期并丢弃
+static PIPELINE_GENERATION: AtomicU64 = AtomicU64::new(0);
+// 最近一次由检测结果生成的遮罩对应的 (overlay 序号, 流水线代次)：两者都未变时说明当前遮罩仍是该次检测的结果，
+// 画面无新帧时可直接沿用；其他途径更新过遮罩（全屏遮罩、清空、暂停、锁定等）或流水线重置后必须重新检测
+static DETECTED_SEQ: AtomicU64 = AtomicU64::new(u64::MAX);
+static DETECTED_GEN: AtomicU64 = AtomicU64::new(u64::MAX);
+// 最近一次检测的时间与遮罩框（显示器坐标）：detection_interval 内的帧沿用这些框，供录制与烘焙遮罩使用
+static LAST_DETECTION: StdMutex<Option<(std::time::Instant, Vec<(Rect, f32)>)>> = StdMutex::new(None);
+// 每次启动监控后的首帧截图耗时仅记录一次，用于衡量预热效果
+static FIRST_FRAME_PENDING: AtomicBool = AtomicBool::new(false);
+// 截图是否处于连续失败中（用于只提示一次）
+static CAPTURE_FAILING: AtomicBool = AtomicBool::new(false);
+// 手动暂停：监控线程与 overlay 保留，只停止截图与检测（区别于 stop_monitoring 的整体拆除）
+static MANUALLY_PAUSED: AtomicBool = AtomicBool::new(false);
 
-fn next_frame_buf() -> &'static Mutex<Option<screen_shot::Image>> {
+fn next_frame_buf() -> &'static Mutex<Option<screen_shot::CaptureOutcome>> {
     NEXT_FRAME.get_or_init(|| Mutex::new(None))
 }
 
+// 最近一次截图的原始帧（未缩放、不含遮罩），仅在显式请求时导出
+static LAST_FRAME: OnceLock<Mutex<Option<screen_shot::Image>>> = OnceLock::new();
+
+fn last_frame_buf() -> &'static Mutex<Option<screen_shot::Image>> {
+    LAST_FRAME.get_or_init(|| Mutex::new(None))
+}
+
+fn remember_last_frame(image: screen_shot::Image) {
+    *lock_or_recover(last_frame_buf(), "last_frame") = Some(image);
+}
+
+/// 将最近一次截图编码为 PNG；这是显式的隐私敏感操作，调用时记录日志
+pub fn get_last_frame_png() -> Result<Option<Vec<u8>>, String> {
+    let guard = lock_or_recover(last_frame_buf(), "last_frame");
+    match guard.as_ref() {
+        Some(image) => {
+            warn!("[get_last_frame_png] exporting last captured frame {}x{} on explicit request", image.width, image.height);
+            image.to_png().map(Some)
+        }
+        None => {
+            info!("[get_last_frame_png] no captured frame available");
+            Ok(None)
+        }
+    }
+}
+
+// 分辨率变化或显示器插拔后重新枚举显示器，按设备名（无设备名时按序号）找回工作显示器，找不到时回退到主显示器。
+// 几何变化时缓存的 duplication 与输出已不匹配：在截图锁内释放截图资源与该显示器的方法统计，丢弃预取帧，
+// 更新工作显示器并按新几何重建 overlay；返回本轮使用的显示器
+fn refresh_working_monitor(current: MonitorInfo) -> MonitorInfo {
+    let monitors = match crate::monitor::monitor::list_monitors() {
+        Ok(m) => m,
+        Err(e) => {
+            error!("[refresh_working_monitor] list monitors failed: {}", e);
+            return current;
+        }
+    };
+    emitter::emit_monitors_changed(&monitors);
+    let found = match &current.name {
+        Some(name) => monitors.iter().find(|m| m.name.as_ref() == Some(name)),
+        None => monitors.iter().find(|m| m.id == current.id),
+    };
+    let (monitor, fell_back) = match found {
+        Some(m) => (m.clone(), false),
+        None => match crate::monitor::monitor::resolve_monitor_by_name("") {
+            Ok(r) => r,
+            Err(e) => {
+                error!("[refresh_working_monitor] no monitor available: {}", e);
+                return current;
+            }
+        },
+    };
+    let unchanged = monitor.id == current.id
+        && (monitor.x, monitor.y, monitor.width, monitor.height) == (current.x, current.y, current.width, current.height)
+        && monitor.scale_factor == current.scale_factor;
+    if unchanged {
+        info!("[refresh_working_monitor] working monitor {} unchanged", monitor.id);
+        return current;
+    }
+    info!(
+        "[refresh_working_monitor] working monitor changed: {} {}x{}@({}, {}) -> {} {}x{}@({}, {})",
+        current.id, current.width, current.height, current.x, current.y, monitor.id, monitor.width, monitor.height, monitor.x, monitor.y
+    );
+    {
+        let _g = lock_or_recover(CAPTURE_LOCK.get_or_init(|| StdMutex::new(())), "capture_lock");
+        lock_or_recover(next_frame_buf(), "next_frame").take();
+        screen_shot::release_capture_resources();
+        screen_shot::reset_capture_stats(Some(current.id));
+        screen_shot::reset_capture_stats(Some(monitor.id));
+    }
+    reset_pipeline();
+    DETECTED_SEQ.store(u64::MAX, Ordering::SeqCst);
+    MonitorState::set_working(Some(monitor.clone())).unwrap();
+    // overlay 在异步任务中重建，不阻塞监控线程（停止监控时会在主线程 join 监控线程）；其间监控已停止则不再创建
+    let overlay_monitor = monitor.clone();
+    tauri::async_runtime::spawn(async move {
+        if !MonitorState::is_working_set() {
+            return;
+        }
+        if let Err(e) = overlay::create_labeled_overlay_window(&overlay_monitor, overlay::MAIN_OVERLAY_LABEL).await {
+            error!("[refresh_working_monitor] recreate overlay failed: {}", e);
+            emitter::emit_error("overlay_create_failed", &e, Some("refresh_working_monitor"));
+        }
+    });
+    emitter::emit_working_monitor(&monitor);
+    if fell_back {
+        emitter::emit_toast(&format!("显示器 {} 已断开，已改用主显示器", current.name.as_deref().unwrap_or("")));
+    }
+    monitor
+}
+
+// monitoring.region：只截取显示器的局部区域（显示器内坐标），由截图层裁剪到显示器范围
+fn configured_region() -> Option<Rect> {
+    config::get_config().and_then(|c| c.monitoring).and_then(|m| m.region)
+}
+
+// 录制画面尺寸：配置了截图区域时为区域大小（与每帧截图一致），否则为整个显示器
+pub fn capture_size(monitor: &MonitorInfo) -> (i32, i32) {
+    match configured_region().and_then(|r| screen_shot::clamp_region(monitor, &r)) {
+        Some(r) => (r.width, r.height),
+        None => (monitor.width, monitor.height),
+    }
+}
+
+// 支持排查：按需截取指定显示器的一帧（PNG），显式调用并记录日志。
+// 与监控循环共用截图锁；截图会消耗 DXGI 的新帧，因此让监控下一帧重新检测，避免误判为画面未变化
+pub fn capture_still_png(monitor_id: usize) -> Result<Vec<u8>, String> {
+    let monitor = crate::monitor::monitor::list_monitors()?
+        .into_iter()
+        .find(|m| m.id == monitor_id)
+        .ok_or_else(|| format!("monitor {} not found", monitor_id))?;
+    let image = {
+        let _g = lock_or_recover(CAPTURE_LOCK.get_or_init(|| StdMutex::new(())), "capture_lock");
+        let image = screen_shot::capture_monitor_image(&monitor)?;
+        DETECTED_SEQ.store(u64::MAX, Ordering::SeqCst);
+        image
+    };
+    warn!("[capture_still_png] exporting still frame {}x{} of monitor {} on explicit request", image.width, image.height, monitor_id);
+    image.to_png()
+}
+
+// 在截图锁内丢弃预取帧并释放截图资源：脱离监控线程的预取线程可能仍在截图，不能在其使用中拆除设备与 duplication
+fn release_capture_resources_locked() {
+    let _g = lock_or_recover(CAPTURE_LOCK.get_or_init(|| StdMutex::new(())), "capture_lock");
+    lock_or_recover(next_frame_buf(), "next_frame").take();
+    screen_shot::release_capture_resources();
+}
+
 fn spawn_prefetch() {
     // 避免并发重复预取
     if PREFETCHING
@@ -41,14 +202,16 @@ fn spawn_prefetch() {
         if let Ok(monitor) = monitor {
             // 截图时仅持有 CAPTURE_LOCK；写入帧缓存时再短暂获取 NEXT_FRAME 锁，
             // 锁顺序固定：先 CAPTURE_LOCK 后 NEXT_FRAME，避免与主循环相反顺序造成死锁。
-            let _g = CAPTURE_LOCK.get_or_init(|| StdMutex::new(())).lock();
-            if let Ok(img) = screen_shot::capture_monitor_image(&monitor) {
+            let _g = lock_or_recover(CAPTURE_LOCK.get_or_init(|| StdMutex::new(())), "capture_lock");
+            if let Ok(outcome) = screen_shot::capture_monitor_region(&monitor, configured_region().as_ref(), gpu_capture_scale(&monitor)) {
                 drop(_g);
-                if let Ok(mut guard) = next_frame_buf().lock() {
-                    if log::max_level() == log::LevelFilter::Debug {
-                        emitter::emit_image(&img);
+                {
+                    let mut guard = lock_or_recover(next_frame_buf(), "next_frame");
+                    // 烘焙遮罩模式下 image 事件只发送处理后的帧，避免原始画面外泄
+                    if log::max_level() == log::LevelFilter::Debug && !bake_mosaic_enabled() {
+                        emitter::emit_image(&outcome.image);
                     }
-                    *guard = Some(img);
+                    *guard = Some(screen_shot::CaptureOutcome { from_prefetch: true, ..outcome });
                 }
             }
         }
@@ -57,48 +220,185 @@ fn spawn_prefetch() {
     });
 }
 
-pub async fn set_working_monitor(monitor: MonitorInfo) {
-    overlay::create_overlay_window(&monitor).await;
+// overlay 创建失败时不开始监控（遮罩无处显示），返回错误并上报
+pub async fn set_working_monitor(monitor: MonitorInfo) -> Result<(), String> {
+    // 预热截图资源，失败不影响启动（首帧会按需创建）
+    if let Err(e) = screen_shot::prewarm_capture(&monitor) {
+        error!("[set_working_monitor] prewarm capture failed: {}", e);
+    }
+    if let Err(e) = overlay::create_labeled_overlay_window(&monitor, overlay::MAIN_OVERLAY_LABEL).await {
+        error!("[set_working_monitor] create overlay failed: {}", e);
+        emitter::emit_error("overlay_create_failed", &e, Some("set_working_monitor"));
+        return Err(format!("create overlay failed: {}", e));
+    }
     MonitorState::set_working(Some(monitor)).unwrap();
     run();
+    Ok(())
 }
 
+/// 按设备名启动监控；找不到时回退到主显示器并提示，返回实际使用的显示器
+pub async fn set_working_monitor_by_name(name: &str) -> Result<MonitorInfo, String> {
+    let (monitor, fell_back) = crate::monitor::monitor::resolve_monitor_by_name(name)?;
+    if fell_back && !name.is_empty() {
+        emitter::emit_toast(&format!("未找到显示器 {}，已改用主显示器", name));
+    }
+    set_working_monitor(monitor.clone()).await?;
+    emitter::emit_working_monitor(&monitor);
+    Ok(monitor)
+}
+
+/// 实时配置（阈值、覆盖模式、区域等）变化后调用：丢弃预取帧、排队中的检测任务与跟踪的窗口，
+/// 并使正在进行的检测结果失效，下一轮基于新配置重新截图并全帧检测
+pub fn reset_pipeline() {
+    PIPELINE_GENERATION.fetch_add(1, Ordering::SeqCst);
+    lock_or_recover(next_frame_buf(), "next_frame").take();
+    let dropped = detection_queue::clear_pending();
+    active_window::reset();
+    tracker::reset();
+    info!("[reset_pipeline] pipeline reset, dropped {} pending detection jobs", dropped);
+}
+
+/// 暂停截图与检测并清空遮罩，overlay 窗口保留，恢复时无需重建；返回是否为本次新暂停
+pub fn pause_monitoring() -> Result<bool, String> {
+    let monitor = working_monitor().ok_or("monitoring is not running")?;
+    if MANUALLY_PAUSED.swap(true, Ordering::SeqCst) {
+        return Ok(false);
+    }
+    // 使进行中的检测结果失效，避免暂停后又被推送上屏
+    reset_pipeline();
+    perf::reset_frame_clock();
+    overlay::overlay::clear_held_mosaics();
+    overlay::overlay::apply_mosaic_with_angle(Vec::new(), None, 1.0, monitor.scale_factor);
+    info!("[pause_monitoring] monitoring paused");
+    Ok(true)
+}
+
+/// 恢复截图与检测；返回此前是否处于暂停
+pub fn resume_monitoring() -> Result<bool, String> {
+    working_monitor().ok_or("monitoring is not running")?;
+    let was_paused = MANUALLY_PAUSED.swap(false, Ordering::SeqCst);
+    if was_paused {
+        info!("[resume_monitoring] monitoring resumed");
+    }
+    Ok(was_paused)
+}
+
+/// 当前工作显示器（未开始监控时为 None）
+pub fn working_monitor() -> Option<MonitorInfo> {
+    MonitorState::get_working().ok()
+}
+
+/// 常规停止：若配置了 stop_fade_ms，遮罩先淡出再关闭 overlay
 pub fn stop_monitoring() {
-    overlay::close_overlay_window();
+    let fade_ms = config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.stop_fade_ms)
+        .unwrap_or(0);
+    stop_monitoring_with_fade(fade_ms);
+}
+
+/// 紧急停止（应用退出/窗口关闭/异常路径）：跳过淡出动画，立即关闭
+pub fn stop_monitoring_immediately() {
+    stop_monitoring_with_fade(0);
+}
+
+fn stop_monitoring_with_fade(fade_ms: u64) {
     MonitorState::set_working(None).unwrap();
-    if let Some(window) = crate::overlay::OverlayState::get_window() {
-        window.close().unwrap();
-    }
-    // 停止线程
-    if let Ok(mut guard) = THREAD.lock() {
-        if let Some(thread) = guard.take() {
-            thread.join().unwrap();
+    recording::stop();
+    cover_all::set(0);
+    mosaic_lock::set(0, true);
+    visibility::leave_pause();
+    MANUALLY_PAUSED.store(false, Ordering::SeqCst);
+    // 停止线程：先于淡出，确保动画期间不会有新的检测结果覆盖
+    if let Some(thread) = lock_or_recover(&THREAD, "monitoring_thread").take() {
+        // 监控线程 panic 时 join 返回 Err，仅记录，避免在此处连锁 panic
+        if thread.join().is_err() {
+            error!("[stop_monitoring] monitoring thread panicked");
         }
     }
+    multi_monitor::stop_all();
+    // 释放截图资源与未消费的预取帧，下次开始监控时惰性重建
+    release_capture_resources_locked();
+    lock_or_recover(last_frame_buf(), "last_frame").take();
+    perf::reset_frame_clock();
+    overlay::overlay::fade_out_mosaics(fade_ms);
+    overlay::overlay::clear_test_boxes();
+    overlay::overlay::clear_held_mosaics();
+    overlay::close_overlay_window();
+}
+
+// 每轮读取，配置热加载后立即生效。防止 0ms 忙等占用CPU与事件通道：钳制到至少 ~120fps
+fn loop_interval() -> u64 {
+    config::get_config()
+        .and_then(|c| c.monitoring)
+        .map(|m| m.interval)
+        .unwrap_or(8)
+        .clamp(8, 1000)
 }
 
 pub fn run() {
-    let cfg_interval = config::get_config().unwrap().monitoring.unwrap().interval;
-    // 防止 0ms 忙等占用CPU与事件通道：钳制到至少 ~120fps
-    let interval = if cfg_interval < 8 { 8 } else { cfg_interval.min(1000) };
-    if let Ok(mut guard) = THREAD.lock() {
-        *guard = Some(std::thread::spawn(move || {
-            unsafe {
-                // 1. 每个线程要初始化COM
-                let result = CoInitializeEx(None, COINIT_MULTITHREADED);
-                if result.is_err() {
-                    error!("CoInitializeEx failed: {result:?}");
+    visibility::ensure_listener();
+    display_change::ensure_listener();
+    {
+        // 检查与创建在同一把锁内完成：快速重复启动（如双击）时只保留一个监控循环。
+        // 循环每轮读取工作显示器，切换显示器时沿用已在运行的循环即可
+        let mut guard = lock_or_recover(&THREAD, "monitoring_thread");
+        let spawned = ensure_loop_thread(&mut guard, || {
+            FIRST_FRAME_PENDING.store(true, Ordering::SeqCst);
+            DETECTED_SEQ.store(u64::MAX, Ordering::SeqCst);
+            overlay::overlay::refresh_emit_interval();
+            std::thread::spawn(move || {
+                unsafe {
+                    // 1. 每个线程要初始化COM
+                    let result = CoInitializeEx(None, COINIT_MULTITHREADED);
+                    if result.is_err() {
+                        error!("CoInitializeEx failed: {result:?}");
+                    }
                 }
-            }
-            loop {
-                if !MonitorState::is_working_set() {
-                    break;
+                loop {
+                    if !MonitorState::is_working_set() {
+                        break;
+                    }
+                    if MANUALLY_PAUSED.load(Ordering::SeqCst) {
+                        std::thread::sleep(visibility::PAUSED_POLL);
+                        continue;
+                    }
+                    cal();
+                    std::thread::sleep(std::time::Duration::from_millis(loop_interval()));
                 }
-                cal();
-                std::thread::sleep(std::time::Duration::from_millis(interval));
-            }
-        }));
+            })
+        });
+        if !spawned {
+            info!("[run] monitoring loop already running, reuse it");
+        }
     }
+    sync_extra_monitors();
+}
+
+/// 按 monitoring.extra_monitors 启停副显示器的并行监控；未在监控时不做任何事
+pub fn sync_extra_monitors() {
+    if let Some(monitor) = working_monitor() {
+        multi_monitor::sync(&monitor);
+    }
+}
+
+// slot 中的线程仍在运行时沿用之并返回 false；否则回收已结束的旧线程，调用 spawn 新建并返回 true。
+// 调用方需持有保护 slot 的锁，使检查与创建原子完成
+fn ensure_loop_thread<F>(slot: &mut Option<std::thread::JoinHandle<()>>, spawn: F) -> bool
+where
+    F: FnOnce() -> std::thread::JoinHandle<()>,
+{
+    if let Some(existing) = slot.take() {
+        if !existing.is_finished() {
+            *slot = Some(existing);
+            return false;
+        }
+        if existing.join().is_err() {
+            error!("[run] previous monitoring thread panicked");
+        }
+    }
+    *slot = Some(spawn());
+    true
 }
 
 fn cal() {
@@ -109,30 +409,74 @@ fn cal() {
         return;
     }
     let monitor = monitor.unwrap();
+    let monitor = if display_change::take_pending() {
+        let monitor = refresh_working_monitor(monitor);
+        // 副显示器的插拔与几何变化同样在此处理
+        multi_monitor::sync(&monitor);
+        monitor
+    } else {
+        monitor
+    };
+
+    // 临时全屏遮罩期间不截图、不检测
+    if cover_all::is_active() {
+        cover_full_monitor(&monitor);
+        return;
+    }
+
+    // 遮罩不可见（overlay 被隐藏或显示器关闭）时暂停截图与检测以节能。
+    // 暂停时改为全屏遮罩并丢弃预取帧：画面恢复后先保持全屏遮罩，直到第一帧新的检测结果替换，不会沿用过期遮罩造成暴露
+    // 睡眠/唤醒后设备已失效：在截图锁内释放（预取线程可能正在截图），恢复后首帧重建。
+    // 放在暂停判断之前，手动暂停期间错过的睡眠也会在恢复后的首轮处理
+    if visibility::take_release_pending() {
+        release_capture_resources_locked();
+        info!("[cal] capture resources released for suspend/resume");
+    }
+    if visibility::should_pause() {
+        if visibility::enter_pause() {
+            if visibility::is_suspended() {
+                info!("[cal] system suspended, pausing detection");
+            } else {
+                info!("[cal] overlay not visible, pausing detection");
+            }
+            cover_full_monitor(&monitor);
+            lock_or_recover(next_frame_buf(), "next_frame").take();
+        }
+        std::thread::sleep(visibility::PAUSED_POLL);
+        return;
+    }
+    if visibility::leave_pause() {
+        info!("[cal] overlay visible again, resuming detection");
+    }
 
     // 截图耗时统计开始
     let screenshot_start = std::time::Instant::now();
 
     // 优先使用上一轮预取的帧；若无，则在不持有 NEXT_FRAME 锁的情况下进行截图，
     // 以避免与预取线程形成相反的锁顺序（CAPTURE_LOCK -> NEXT_FRAME）而死锁。
-    let mut from_prefetch: Option<screen_shot::Image> = None;
-    if let Ok(mut guard) = next_frame_buf().lock() {
-        from_prefetch = guard.take();
-    }
-    let image_result: Result<screen_shot::Image, String> = if let Some(img) = from_prefetch {
+    let prefetched: Option<screen_shot::CaptureOutcome> = lock_or_recover(next_frame_buf(), "next_frame").take();
+    let capture_result: Result<screen_shot::CaptureOutcome, CaptureError> = if let Some(outcome) = prefetched {
         debug!("[cal] use prefetched frame");
-        Ok(img)
+        Ok(outcome)
     } else {
-        let _g = CAPTURE_LOCK.get_or_init(|| StdMutex::new(())).lock();
-        screen_shot::capture_monitor_image(&monitor)
+        let _g = lock_or_recover(CAPTURE_LOCK.get_or_init(|| StdMutex::new(())), "capture_lock");
+        screen_shot::capture_monitor_region(&monitor, configured_region().as_ref(), gpu_capture_scale(&monitor))
     };
 
     // 输出截图用时（info级别）
     let screenshot_elapsed_ms = screenshot_start.elapsed().as_millis();
     info!("[perf] prefetched screenshot {} ms", screenshot_elapsed_ms);
+    if FIRST_FRAME_PENDING.swap(false, Ordering::SeqCst) {
+        info!("[perf] first frame screenshot {} ms", screenshot_elapsed_ms);
+    }
 
-    match image_result {
-        Ok(image) => {
+    match capture_result {
+        Ok(outcome) => {
+            CAPTURE_FAILING.store(false, Ordering::SeqCst);
+            // 帧归因：截图耗时与方法取自截图本身（预取帧为预取时的耗时），而非本轮等待时间
+            let frame_unchanged = outcome.is_unchanged();
+            let screen_shot::CaptureOutcome { image, method: capture_method, elapsed: capture_elapsed, from_prefetch, scale: image_scale, region: captured, .. } = outcome;
+            debug!("[cal] capture via {} in {:?} (prefetched={}, scale={:.3})", capture_method.name(), capture_elapsed, from_prefetch, image_scale);
             // 诊断：若数据大小刚好等于 width*height*4 但画面仍是空白，输出一次警告
             if image.data.len() == (image.width as usize * image.height as usize * 4) {
                 // 简要采样首尾像素，辅助判断是否纯色
@@ -153,27 +497,117 @@ fn cal() {
             // 若人脸模型未就绪，则跳过本轮检测，但保证输出两行日志
             if !crate::ai::faces::is_face_model_ready() {
                 debug!("[cal] face model not ready, skip detection");
+                // 模型加载失败：静态遮罩模式，不检测而遮住整个显示器
+                if crate::ai::faces::is_face_model_failed() {
+                    cover_full_monitor(&monitor);
+                }
                 info!("[perf] face_detection 0 ms");
+                remember_last_frame(image);
                 return;
             }
 
-            // 读取监控配置中的 capture_scale，对截图进行可选下采样
-            let capture_scale = config::get_config()
-                .and_then(|c| c.monitoring)
-                .and_then(|m| m.capture_scale)
-                .unwrap_or(1.0);
+            // 画面没有新帧且当前遮罩仍是上次检测的结果：跳过检测与推送，沿用当前遮罩
+            if frame_unchanged && skip_unchanged_frames() && mosaics_from_last_detection() {
+                debug!("[cal] frame unchanged, keep current mosaics");
+                remember_last_frame(image);
+                return;
+            }
+
+            // 距上次检测不足 detection_interval 且当前遮罩仍是那次检测的结果：只截图不检测，沿用上次的遮罩框
+            if let Some(items) = reusable_detection() {
+                debug!("[cal] within detection_interval, reuse {} rects", items.len());
+                remember_last_frame(image);
+                let mosaic_scale = config::get_config()
+                    .and_then(|c| c.monitoring)
+                    .map(|m| m.mosaic_scale)
+                    .unwrap_or(1.0f32);
+                // 跟踪开启时按各框的运动速度外推，遮罩随之移动；仍视为检测结果，下一帧可继续沿用
+                let items = if tracker::is_enabled() && !mosaic_lock::is_active() {
+                    let (predicted, colors) = tracker::predict(&Rect::new(0, 0, monitor.width, monitor.height));
+                    let mut shown = predicted.clone();
+                    shown.extend(multi_monitor::spill_into(monitor.id));
+                    crate::overlay::overlay::apply_mosaic_with_angle(shown, colors, mosaic_scale, monitor.scale_factor);
+                    DETECTED_SEQ.store(overlay::overlay::current_seq(), Ordering::SeqCst);
+                    predicted
+                } else {
+                    items
+                };
+                push_recording_frame(captured.as_ref(), &items, mosaic_scale);
+                if bake_mosaic_enabled() {
+                    emit_baked_frame(&monitor, captured.as_ref(), &items, mosaic_scale);
+                }
+                return;
+            }
 
-            let mut resize_ratio = 1.0f32;
-            let detection_image = if capture_scale > 0.0 && capture_scale < 0.9999 {
-                resize_ratio = capture_scale.max(0.1);
-                downscale_image_bgra(&image, resize_ratio)
+            // 跟随活动窗口：仅在前台窗口区域内检测；窗口最小化/隐藏、不在本显示器或不在截图区域内时暂停并清空遮罩
+            let window = if active_window::is_enabled() {
+                let tracked = active_window::tracked_rect(&monitor)
+                    .and_then(|r| match &captured {
+                        Some(c) => r.intersection(c),
+                        None => Some(r),
+                    });
+                match tracked {
+                    Some(r) => Some(r),
+                    None => {
+                        if !mosaic_lock::is_active() {
+                            crate::overlay::overlay::apply_mosaic_with_angle(Vec::new(), None, 1.0, monitor.scale_factor);
+                        }
+                        info!("[perf] face_detection 0 ms");
+                        remember_last_frame(image);
+                        return;
+                    }
+                }
             } else {
-                image.clone()
+                None
             };
 
+            // 检测区域（显示器坐标）：活动窗口区域，或截图区域本身；检测结果据其左上角平移回显示器坐标
+            let region = window.clone().or_else(|| captured.clone());
+            let (cx, cy) = captured.as_ref().map(|c| (c.x, c.y)).unwrap_or((0, 0));
+
+            // 截图可能已在 GPU 上缩小（image_scale < 1）：区域换到截图坐标并按同一倍率换算后裁剪，剩余倍率再在 CPU 上缩放；
+            // resize_ratio 始终是检测图相对显示器物理像素的总倍率
+            let cropped = window
+                .as_ref()
+                .map(|r| crop_image_bgra(&image, &scale_rect_into(&Rect::new(r.x - cx, r.y - cy, r.width, r.height), image_scale, &image)));
+            let source = cropped.as_ref().unwrap_or(&image);
+            let resize_ratio = detection_resize_ratio(&monitor, source, image_scale);
+            let cpu_ratio = resize_ratio / image_scale;
+            let detection_image = Arc::new(if cpu_ratio < 0.9999 {
+                downscale_for_detection(source, cpu_ratio)
+            } else {
+                source.clone()
+            });
+            drop(cropped);
+            // 原图此后不再使用，移入最近帧缓存（供支持排查时显式导出）
+            remember_last_frame(image);
+
+            // 可选多尺度检测：两个及以上尺度时逐一检测并 NMS 融合
+            let det_cfg = config::get_config().and_then(|c| c.face).map(|f| f.detection);
+            let multi_scales = det_cfg
+                .as_ref()
+                .and_then(|d| d.multi_scales.clone())
+                .filter(|s| s.len() > 1);
+            let multi_scale_iou = det_cfg.and_then(|d| d.multi_scale_iou).unwrap_or(0.4);
+
             // 人脸检测耗时统计开始
             let face_start = std::time::Instant::now();
-            match faces::detect_faces_with_angle(&detection_image) {
+            // 审计依赖 Python 侧最近一次判定，仅单尺度检测时坐标与检测图一致
+            let single_scale = multi_scales.is_none();
+            let job_image = Arc::clone(&detection_image);
+            let (detect_result, generation) = match run_detection(monitor.id, move || match multi_scales {
+                Some(scales) => detect_faces_multi_scale(&job_image, &scales, multi_scale_iou),
+                None => faces::detect_faces_with_angle(&job_image),
+            }) {
+                Some(r) => r,
+                None => return,
+            };
+            // 叠加马赛克：mosaic_scale 控制马赛克矩形自身放大比例；dpi_scale 用于前端坐标换算
+            let mosaic_scale = config::get_config()
+                .and_then(|c| c.monitoring)
+                .map(|m| m.mosaic_scale)
+                .unwrap_or(1.0f32);
+            match detect_result {
                 Ok(rects_with_angle) => {
                     // 输出人脸检测用时（info级别）
                     let face_elapsed_ms = face_start.elapsed().as_millis();
@@ -183,6 +617,22 @@ fn cal() {
                         debug!("[cal] no faces detected");
                     }
 
+                    // 覆盖面积下限（原分辨率像素²）：过滤过小的人脸（如背景照片），不影响检测器召回；
+                    // 下限按检测图缩放比换算
+                    let min_cover_area = config::get_config()
+                        .and_then(|c| c.monitoring)
+                        .and_then(|m| m.min_cover_area_px)
+                        .unwrap_or(0);
+                    let min_cover_area_det = ((min_cover_area as f64) * (resize_ratio as f64).powi(2)).round() as u64;
+                    let rects_with_angle = filter_min_cover_area(rects_with_angle, min_cover_area_det);
+
+                    if single_scale && audit::is_enabled() {
+                        match faces::last_decisions() {
+                            Ok(decisions) => audit::record(&detection_image, &decisions),
+                            Err(e) => debug!("[cal] read last decisions failed: {}", e),
+                        }
+                    }
+
                     // 将检测框从缩小坐标系映射回原始分辨率
                     let mapped_rects_with_angle: Vec<(Rect, f32)> = if (resize_ratio - 1.0).abs() < f32::EPSILON {
                         rects_with_angle
@@ -190,14 +640,48 @@ fn cal() {
                         let inv = 1.0f32 / resize_ratio;
                         rects_with_angle
                             .into_iter()
-                            .map(|(r, a)| (Rect::new(
-                                ((r.x as f32) * inv).round() as i32,
-                                ((r.y as f32) * inv).round() as i32,
-                                ((r.width as f32) * inv).round() as i32,
-                                ((r.height as f32) * inv).round() as i32,
-                            ), a))
+                            .map(|(r, a)| (r.scaled_outward(inv, inv), a))
                             .collect()
                     };
+                    // 跟随活动窗口或只截取局部区域时检测在区域图上进行，再平移回显示器坐标
+                    let mapped_rects_with_angle: Vec<(Rect, f32)> = match &region {
+                        Some(r) => mapped_rects_with_angle
+                            .into_iter()
+                            .map(|(m, a)| (Rect::new(m.x + r.x, m.y + r.y, m.width, m.height).with_score(m.score), a))
+                            .collect(),
+                        None => mapped_rects_with_angle,
+                    };
+                    // 排除任务栏区域：中心落在任务栏内的框（图标、头像等）不遮罩
+                    let mapped_rects_with_angle = if taskbar::is_enabled() {
+                        exclude_regions(mapped_rects_with_angle, &taskbar::excluded_rects(&monitor))
+                    } else {
+                        mapped_rects_with_angle
+                    };
+                    // 遮罩框流水线：外扩 pad 后合并相邻/重叠框（避免相邻人脸间的缝隙），再裁剪到 overlay 覆盖范围
+                    let mapped_rects_with_angle = inflate_and_merge(mapped_rects_with_angle, &multi_monitor::overlay_extent(&monitor), RedactionParams::from_config());
+                    // 按 overlay 所在显示器切分跨接缝的框：落在副显示器上的部分交给其 overlay，这里只保留本显示器的部分
+                    let mapped_rects_with_angle = multi_monitor::route(&monitor, mapped_rects_with_angle);
+
+                    // 纯色 auto 样式：把最终框换回检测图坐标系，对放大后的遮罩框取周边平均色
+                    let colors: Option<Vec<String>> = if crate::overlay::overlay::cover_style().samples_color() {
+                        let (ox, oy) = region.as_ref().map(|r| (r.x, r.y)).unwrap_or((0, 0));
+                        Some(
+                            mapped_rects_with_angle
+                                .iter()
+                                .map(|(r, _)| {
+                                    let det = Rect::new(
+                                        ((r.x - ox) as f32 * resize_ratio).round() as i32,
+                                        ((r.y - oy) as f32 * resize_ratio).round() as i32,
+                                        (r.width as f32 * resize_ratio).round() as i32,
+                                        (r.height as f32 * resize_ratio).round() as i32,
+                                    );
+                                    sample_surrounding_color(&detection_image, &det.scaled_about_center(mosaic_scale))
+                                })
+                                .collect(),
+                        )
+                    } else {
+                        None
+                    };
 
                     // 对前端 app 布局发送映射回原分辨率的检测框
                     let just_rects: Vec<Rect> = mapped_rects_with_angle.iter().map(|(r, _)| r.clone()).collect();
@@ -206,28 +690,66 @@ fn cal() {
                     // 追加发送带角度的事件（新事件名），供前端有能力时使用
                     let angle_items: Vec<emitter::FaceAngleEventItem> = mapped_rects_with_angle
                         .iter()
-                        .map(|(r, a)| emitter::FaceAngleEventItem { x: r.x, y: r.y, width: r.width, height: r.height, angle: *a })
+                        .map(|(r, a)| emitter::FaceAngleEventItem { x: r.x, y: r.y, width: r.width, height: r.height, angle: *a, score: r.score })
                         .collect();
                     emitter::emit_frame_info_with_angle(angle_items);
 
-                    // 叠加马赛克：mosaic_scale 控制马赛克矩形自身放大比例；dpi_scale 用于前端坐标换算
-                    let mosaic_scale = config::get_config()
-                        .and_then(|c| c.monitoring)
-                        .map(|m| m.mosaic_scale)
-                        .unwrap_or(1.0f32);
-                    let rects_for_mosaic_with_angle = mapped_rects_with_angle.clone();
-                    crate::overlay::overlay::apply_mosaic_with_angle(rects_for_mosaic_with_angle, mosaic_scale, monitor.scale_factor);
+                    perf::record(
+                        monitor.id,
+                        capture_elapsed.as_millis() as u64,
+                        from_prefetch,
+                        face_elapsed_ms as u64,
+                        capture_method.name(),
+                        mapped_rects_with_angle.len(),
+                    );
+                    emit_detection_cycle(
+                        &monitor,
+                        capture_elapsed.as_millis() as u64,
+                        face_elapsed_ms as u64,
+                        capture_method.name(),
+                        mapped_rects_with_angle.len(),
+                        single_scale,
+                    );
+
+                    push_recording_frame(captured.as_ref(), &mapped_rects_with_angle, mosaic_scale);
+
+                    if bake_mosaic_enabled() {
+                        emit_baked_frame(&monitor, captured.as_ref(), &mapped_rects_with_angle, mosaic_scale);
+                    }
+
+                    // 遮罩锁定期间检测照常进行（上面的统计与事件不受影响），只是不更新遮罩
+                    if mosaic_lock::is_active() {
+                        debug!("[cal] mosaics locked, ignoring {} detections", mapped_rects_with_angle.len());
+                    } else {
+                        let mut rects_for_mosaic_with_angle = mapped_rects_with_angle.clone();
+                        if tracker::is_enabled() {
+                            tracker::update(&rects_for_mosaic_with_angle, colors.as_deref());
+                        }
+                        // 相邻显示器检测到、越过接缝落在本显示器上的部分追加在后（不参与取色与跟踪）
+                        rects_for_mosaic_with_angle.extend(multi_monitor::spill_into(monitor.id));
+                        crate::overlay::overlay::apply_mosaic_with_angle(rects_for_mosaic_with_angle, colors, mosaic_scale, monitor.scale_factor);
+                        DETECTED_SEQ.store(overlay::overlay::current_seq(), Ordering::SeqCst);
+                        DETECTED_GEN.store(generation, Ordering::SeqCst);
+                        *lock_or_recover(&LAST_DETECTION, "last_detection") = Some((std::time::Instant::now(), mapped_rects_with_angle));
+                    }
                 }
                 Err(e) => {
                     // 输出人脸检测用时（即便失败也记录耗时）
                     let face_elapsed_ms = face_start.elapsed().as_millis();
                     info!("[perf] face_detection {} ms", face_elapsed_ms);
                     error!("[cal] face processing failed: {}", e);
+                    emitter::emit_error("detection_failed", &e, Some("cal"));
+                    apply_detection_error_policy(&monitor);
                 }
             }
         }
         Err(e) => {
             error!("[cal] screen shot failed: {}", e);
+            emitter::emit_error("capture_failed", &e.to_string(), Some(&format!("monitor {}", monitor.id)));
+            // 连续失败只提示一次，截图恢复后再次失败时重新提示
+            if !CAPTURE_FAILING.swap(true, Ordering::SeqCst) {
+                emitter::emit_toast(&format!("截图失败：{}", e.hint()));
+            }
             // 即便截图失败，也保证两行日志输出
             info!("[perf] face_detection 0 ms");
             return;  // 优雅退出而不是 panic
@@ -235,6 +757,539 @@ fn cal() {
     }
 }
 
+// 在多个尺度上检测并映射回输入图坐标，按 NMS 融合重复框
+fn detect_faces_multi_scale(image: &screen_shot::Image, scales: &[f32], iou_threshold: f32) -> Result<Vec<(Rect, f32)>, String> {
+    let mut all: Vec<(Rect, f32)> = Vec::new();
+    let mut last_err: Option<String> = None;
+    for &scale in scales {
+        let scale = scale.clamp(0.1, 1.0);
+        let scaled = if scale < 0.9999 { downscale_for_detection(image, scale) } else { image.clone() };
+        let inv_x = image.width as f32 / scaled.width.max(1) as f32;
+        let inv_y = image.height as f32 / scaled.height.max(1) as f32;
+        match faces::detect_faces_with_angle(&scaled) {
+            Ok(items) => {
+                debug!("[detect_faces_multi_scale] scale {} -> {} faces", scale, items.len());
+                all.extend(items.into_iter().map(|(r, a)| (r.scaled_outward(inv_x, inv_y), a)));
+            }
+            Err(e) => {
+                error!("[detect_faces_multi_scale] scale {} failed: {}", scale, e);
+                last_err = Some(e);
+            }
+        }
+    }
+    if all.is_empty() {
+        if let Some(e) = last_err {
+            return Err(e);
+        }
+    }
+    let rects: Vec<Rect> = all.iter().map(|(r, _)| r.clone()).collect();
+    let keep = nms(&rects, iou_threshold);
+    Ok(keep.into_iter().map(|i| all[i].clone()).collect())
+}
+
+// 取矩形外一圈像素（宽度约为短边的 1/8，至少 2px）的平均色，返回 #rrggbb；矩形贴边时只用可用部分
+fn sample_surrounding_color(image: &screen_shot::Image, rect: &Rect) -> String {
+    let ring = (rect.width.min(rect.height) / 8).max(2);
+    let x0 = (rect.x - ring).max(0);
+    let y0 = (rect.y - ring).max(0);
+    let x1 = (rect.x + rect.width + ring).min(image.width);
+    let y1 = (rect.y + rect.height + ring).min(image.height);
+    let stride = image.width as usize * 4;
+    let (mut sb, mut sg, mut sr, mut n) = (0u64, 0u64, 0u64, 0u64);
+    // 隔点采样即可得到稳定均值，降低大框的开销
+    for y in (y0..y1).step_by(2) {
+        for x in (x0..x1).step_by(2) {
+            if rect.contains_point(x, y) {
+                continue;
+            }
+            let i = y as usize * stride + x as usize * 4;
+            if i + 2 < image.data.len() {
+                sb += image.data[i] as u64;
+                sg += image.data[i + 1] as u64;
+                sr += image.data[i + 2] as u64;
+                n += 1;
+            }
+        }
+    }
+    if n == 0 {
+        return "#000000".to_string();
+    }
+    format!("#{:02x}{:02x}{:02x}", sr / n, sg / n, sb / n)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScaleBench {
+    pub scale: f32,
+    pub width: i32,
+    pub height: i32,
+    pub latency_ms: f64,
+    pub face_count: usize,
+}
+
+// 基准测试任务在检测队列中的独立槽位，不会替换实时监控的帧
+const BENCHMARK_QUEUE_ID: usize = usize::MAX;
+
+/// 截取一帧，按各缩放倍率（即 capture_scale）分别检测并计时，结果按倍率升序返回
+pub fn benchmark_detection(monitor: &MonitorInfo, scales: &[f32]) -> Result<Vec<ScaleBench>, String> {
+    let mut scales: Vec<f32> = scales.iter().map(|s| s.clamp(0.1, 1.0)).collect();
+    if scales.is_empty() {
+        return Err("no scales to benchmark".to_string());
+    }
+    scales.sort_by(|a, b| a.total_cmp(b));
+    scales.dedup();
+
+    let image = {
+        let _g = lock_or_recover(CAPTURE_LOCK.get_or_init(|| StdMutex::new(())), "capture_lock");
+        screen_shot::capture_monitor_image(monitor)?
+    };
+    info!("[benchmark_detection] captured {}x{}, scales {:?}", image.width, image.height, scales);
+
+    // 预热一次，避免首次推理的初始化开销计入第一个尺度
+    let warmup = Arc::new(downscale_for_detection(&image, scales[0]));
+    let _ = detection_queue::run_latest(BENCHMARK_QUEUE_ID, move || faces::detect_faces_with_angle(&warmup));
+
+    let total = scales.len();
+    let mut results = Vec::with_capacity(total);
+    for (i, &scale) in scales.iter().enumerate() {
+        let scaled = Arc::new(if scale < 0.9999 { downscale_for_detection(&image, scale) } else { image.clone() });
+        let (width, height) = (scaled.width, scaled.height);
+        let (elapsed, detected) = detection_queue::run_latest(BENCHMARK_QUEUE_ID, move || {
+            let start = std::time::Instant::now();
+            let r = faces::detect_faces_with_angle(&scaled);
+            (start.elapsed(), r)
+        })
+        .ok_or("benchmark job was dropped")?;
+        let faces = detected?;
+        let bench = ScaleBench {
+            scale,
+            width,
+            height,
+            latency_ms: elapsed.as_secs_f64() * 1000.0,
+            face_count: faces.len(),
+        };
+        info!("[benchmark_detection] {:?}", bench);
+        results.push(bench);
+        emitter::emit_benchmark_progress(emitter::BenchmarkProgress { done: i + 1, total, scale });
+    }
+    Ok(results)
+}
+
+// 丢弃中心点落在任一排除区域内的框
+fn exclude_regions(items: Vec<(Rect, f32)>, regions: &[Rect]) -> Vec<(Rect, f32)> {
+    if regions.is_empty() {
+        return items;
+    }
+    items
+        .into_iter()
+        .filter(|(r, _)| {
+            let (cx, cy) = (r.x + r.width / 2, r.y + r.height / 2);
+            !regions.iter().any(|e| e.contains_point(cx, cy))
+        })
+        .collect()
+}
+
+// 经检测队列串行执行（latest-wins），返回结果与提交时的流水线代数；
+// 被同一显示器更新的帧替换、或执行期间流水线被重置（结果基于旧配置）时返回 None，调用方跳过本帧
+fn run_detection<R, F>(monitor_id: usize, f: F) -> Option<(R, u64)>
+where
+    R: Send + 'static,
+    F: FnOnce() -> R + Send + 'static,
+{
+    let generation = PIPELINE_GENERATION.load(Ordering::SeqCst);
+    let result = match detection_queue::run_latest(monitor_id, f) {
+        Some(r) => r,
+        None => {
+            debug!("[cal] detection job superseded by a newer frame");
+            return None;
+        }
+    };
+    if PIPELINE_GENERATION.load(Ordering::SeqCst) != generation {
+        debug!("[cal] pipeline reset during detection, discard stale result");
+        return None;
+    }
+    Some((result, generation))
+}
+
+// 切分到某个 overlay 上的遮罩框：该显示器本地的物理像素坐标，scale_factor 为该显示器自身的 DPI 缩放（overlay 据此换算逻辑坐标）
+struct OverlayPieces {
+    monitor_id: usize,
+    scale_factor: f64,
+    items: Vec<(Rect, f32)>,
+}
+
+// 框先从 monitor 本地坐标换算到桌面坐标，再与各 overlay 显示器的边界求交，切分后换回各自的本地坐标；
+// 每个 overlay 都返回一项（可能为空），调用方据此替换该来源此前落在各 overlay 上的部分
+fn split_for_overlays(items: Vec<(Rect, f32)>, monitor: &MonitorInfo, overlays: &[MonitorInfo]) -> Vec<OverlayPieces> {
+    let mut pieces: Vec<OverlayPieces> = overlays
+        .iter()
+        .map(|m| OverlayPieces { monitor_id: m.id, scale_factor: m.scale_factor, items: Vec::new() })
+        .collect();
+    for (r, a) in items {
+        let desktop = Rect::new(r.x + monitor.x, r.y + monitor.y, r.width, r.height);
+        for (id, local) in crate::monitor::monitor::split_rect_across_monitors(&desktop, overlays) {
+            if let Some(p) = pieces.iter_mut().find(|p| p.monitor_id == id) {
+                p.items.push((local.with_score(r.score), a));
+            }
+        }
+    }
+    pieces
+}
+
+fn cover_full_monitor(monitor: &MonitorInfo) {
+    let full = Rect::new(0, 0, monitor.width, monitor.height);
+    crate::overlay::overlay::apply_mosaic_with_angle(vec![(full, 0.0)], None, 1.0, monitor.scale_factor);
+}
+
+/// 在 duration_ms 内无视检测结果遮住整个工作显示器，到期自动恢复；0 表示立即取消
+pub fn cover_all_for(duration_ms: u64) -> Result<(), String> {
+    let monitor = working_monitor().ok_or("monitoring is not running")?;
+    cover_all::set(duration_ms);
+    if duration_ms > 0 {
+        cover_full_monitor(&monitor);
+    }
+    Ok(())
+}
+
+/// 在 duration_ms 内保持当前遮罩集合、忽略新的检测结果，到期自动解锁；0 表示立即解锁
+pub fn lock_mosaics(duration_ms: u64) -> Result<(), String> {
+    if working_monitor().is_none() {
+        return Err("monitoring is not running".to_string());
+    }
+    mosaic_lock::set(duration_ms, false);
+    Ok(())
+}
+
+// 遮罩是否处于锁定期：锁定期间保持中的遮罩也不淡出
+pub fn mosaics_locked() -> bool {
+    mosaic_lock::is_active()
+}
+
+// 检测出错时按 on_detection_error 处理遮罩，避免瞬时异常导致人脸暴露
+fn apply_detection_error_policy(monitor: &MonitorInfo) {
+    let policy = config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.on_detection_error)
+        .unwrap_or_default();
+    match policy {
+        DetectionErrorPolicy::HoldLast => debug!("[cal] detection failed, holding last mosaics"),
+        DetectionErrorPolicy::FailClosed => warn!("[cal] detection failed, covering the whole monitor"),
+        DetectionErrorPolicy::Clear => debug!("[cal] detection failed, clearing mosaics"),
+    }
+    if let Some(items) = detection_error_payload(policy, monitor.width, monitor.height) {
+        crate::overlay::overlay::apply_mosaic_with_angle(items, None, 1.0, monitor.scale_factor);
+    }
+}
+
+// 检测出错时要下发的遮罩：None 表示不下发（保留上一帧），否则为整屏遮罩或空集合
+fn detection_error_payload(policy: DetectionErrorPolicy, monitor_width: i32, monitor_height: i32) -> Option<Vec<(Rect, f32)>> {
+    match policy {
+        DetectionErrorPolicy::HoldLast => None,
+        DetectionErrorPolicy::FailClosed => Some(vec![(Rect::new(0, 0, monitor_width, monitor_height), 0.0)]),
+        DetectionErrorPolicy::Clear => Some(Vec::new()),
+    }
+}
+
+// 遮罩框外扩/合并参数，取自 monitoring.redaction_*；负的 gap 与非正的 IoU 视为关闭
+#[derive(Debug, Clone, Copy, Default)]
+struct RedactionParams {
+    pad: i32,
+    merge_gap: Option<i32>,
+    merge_iou: Option<f32>,
+}
+
+impl RedactionParams {
+    fn from_config() -> Self {
+        let m = config::get_config().and_then(|c| c.monitoring);
+        Self {
+            pad: m.as_ref().and_then(|m| m.redaction_pad_px).unwrap_or(0),
+            merge_gap: m.as_ref().and_then(|m| m.redaction_merge_gap_px).filter(|g| *g >= 0),
+            merge_iou: m.as_ref().and_then(|m| m.redaction_merge_iou).filter(|t| *t > 0.0),
+        }
+    }
+}
+
+// 外扩 pad 后按 merge_gap / merge_iou 合并，最后裁剪到 bounds（本地坐标；有相邻 overlay 时为全部 overlay 的外包矩形，
+// 越过接缝的部分随后切分到相邻显示器）；合并后的框不再对应单张人脸，角度置 0、分数置空
+fn inflate_and_merge(items: Vec<(Rect, f32)>, bounds: &Rect, params: RedactionParams) -> Vec<(Rect, f32)> {
+    let RedactionParams { pad, merge_gap, merge_iou } = params;
+    if pad == 0 && merge_gap.is_none() && merge_iou.is_none() {
+        return items;
+    }
+    let inflated: Vec<(Rect, f32)> = items.into_iter().map(|(r, a)| (r.inflate(pad).with_score(r.score), a)).collect();
+    let merged: Vec<(Rect, f32)> = if merge_gap.is_none() && merge_iou.is_none() {
+        inflated
+    } else {
+        let before = inflated.len();
+        let rects = merge_close(inflated.iter().map(|(r, _)| r.clone()).collect(), merge_gap, merge_iou);
+        if rects.len() == before {
+            inflated
+        } else {
+            rects.into_iter().map(|r| (r, 0.0)).collect()
+        }
+    };
+    merged
+        .into_iter()
+        .filter_map(|(r, a)| r.intersection(bounds).map(|c| (c.with_score(r.score), a)))
+        .collect()
+}
+
+// 丢弃面积小于 min_area（物理像素²）的框；恰好等于下限的保留
+fn filter_min_cover_area(items: Vec<(Rect, f32)>, min_area: u64) -> Vec<(Rect, f32)> {
+    if min_area == 0 {
+        return items;
+    }
+    let before = items.len();
+    let kept: Vec<(Rect, f32)> = items
+        .into_iter()
+        .filter(|(r, _)| (r.width.max(0) as u64) * (r.height.max(0) as u64) >= min_area)
+        .collect();
+    if kept.len() < before {
+        debug!("[cal] dropped {} faces below min_cover_area_px={}", before - kept.len(), min_area);
+    }
+    kept
+}
+
+// GPU 降采样时截图倍率：开启 gpu_downscale 且 capture_scale < 1 时为 capture_scale，
+// 录制需要原分辨率帧，录制期间返回 1
+// 指定显示器生效的 [monitoring]（已合并 [monitor_overrides] 中该显示器的覆盖项）
+fn monitoring_config_for(monitor: &MonitorInfo) -> Option<config::MonitoringConfig> {
+    config::get_effective_config(monitor.name.as_deref()).and_then(|c| c.monitoring)
+}
+
+fn gpu_capture_scale(monitor: &MonitorInfo) -> f32 {
+    let cfg = match monitoring_config_for(monitor) {
+        Some(m) => m,
+        None => return 1.0,
+    };
+    let capture_scale = cfg.capture_scale.unwrap_or(1.0);
+    if !cfg.gpu_downscale.unwrap_or(false) || recording::is_active() || !(capture_scale > 0.0 && capture_scale < 0.9999) {
+        return 1.0;
+    }
+    capture_scale.max(0.1)
+}
+
+fn skip_unchanged_frames() -> bool {
+    config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.skip_unchanged_frames)
+        .unwrap_or(true)
+}
+
+// monitoring.detection_interval（ms）：检测的最小间隔，截图仍按 interval 进行；0 或缺省时每帧检测
+fn detection_interval_ms() -> u64 {
+    config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.detection_interval)
+        .unwrap_or(0)
+}
+
+// 可沿用的上次检测结果：未到检测间隔，且当前遮罩仍是该次检测生成的（其间遮罩被其他途径更新或流水线重置时重新检测）
+fn reusable_detection() -> Option<Vec<(Rect, f32)>> {
+    let interval = detection_interval_ms();
+    if interval == 0 || !mosaics_from_last_detection() {
+        return None;
+    }
+    match lock_or_recover(&LAST_DETECTION, "last_detection").as_ref() {
+        Some((at, items)) if at.elapsed().as_millis() < interval as u128 => Some(items.clone()),
+        _ => None,
+    }
+}
+
+// 录制中：提交原图与放大后的遮罩框（换到截图坐标），由录制线程合成
+fn push_recording_frame(captured: Option<&Rect>, items: &[(Rect, f32)], mosaic_scale: f32) {
+    if !recording::is_active() {
+        return;
+    }
+    let Some(frame) = lock_or_recover(last_frame_buf(), "last_frame").clone() else {
+        return;
+    };
+    let (cx, cy) = captured.map(|c| (c.x, c.y)).unwrap_or((0, 0));
+    let rects = items
+        .iter()
+        .map(|(r, _)| {
+            let m = overlay::overlay::mosaic_rect(r, mosaic_scale);
+            Rect::new(m.x - cx, m.y - cy, m.width, m.height)
+        })
+        .collect();
+    recording::push_frame(frame, rects);
+}
+
+fn mosaics_from_last_detection() -> bool {
+    DETECTED_SEQ.load(Ordering::SeqCst) == overlay::overlay::current_seq()
+        && DETECTED_GEN.load(Ordering::SeqCst) == PIPELINE_GENERATION.load(Ordering::SeqCst)
+}
+
+fn bake_mosaic_enabled() -> bool {
+    config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.bake_mosaic)
+        .unwrap_or(false)
+}
+
+// 在最近一帧的副本上像素化遮罩框后发送；框为原分辨率坐标，GPU 降采样时按帧的实际尺寸换算
+// captured 为该帧的截图区域（None 为整个显示器），遮罩框先换到区域坐标再按帧尺寸缩放
+fn emit_baked_frame(monitor: &MonitorInfo, captured: Option<&Rect>, items: &[(Rect, f32)], mosaic_scale: f32) {
+    let Some(mut frame) = lock_or_recover(last_frame_buf(), "last_frame").clone() else {
+        return;
+    };
+    let area = captured.cloned().unwrap_or_else(|| Rect::new(0, 0, monitor.width, monitor.height));
+    let sx = frame.width as f32 / area.width.max(1) as f32;
+    let sy = frame.height as f32 / area.height.max(1) as f32;
+    let rects: Vec<Rect> = items
+        .iter()
+        .map(|(r, _)| {
+            let m = overlay::overlay::mosaic_rect(r, mosaic_scale);
+            Rect::new(m.x - area.x, m.y - area.y, m.width, m.height).scaled_outward(sx, sy)
+        })
+        .collect();
+    let block = overlay::overlay::pixelate_block_px().map(|b| ((b as f32 * sx).round() as u32).max(1)).unwrap_or(0);
+    crate::mosaic::pixelate_regions(&mut frame, &rects, block);
+    emitter::emit_processed_image(frame);
+}
+
+// 节流发送 detection-cycle 事件：距上次发送不足 detection_event_min_interval_ms 时跳过。
+// 人名取自 Python 侧最近一次检测，多尺度时只对应最后一个尺度，故仅单尺度时附带
+fn emit_detection_cycle(monitor: &MonitorInfo, capture_ms: u64, detection_ms: u64, method: &str, face_count: usize, single_scale: bool) {
+    static LAST_EMIT: StdMutex<Option<std::time::Instant>> = StdMutex::new(None);
+    let m = config::get_config().and_then(|c| c.monitoring);
+    let min_interval = m.as_ref().and_then(|m| m.detection_event_min_interval_ms).unwrap_or(200);
+    {
+        let mut last = lock_or_recover(&LAST_EMIT, "detection_cycle_emit");
+        if last.is_some_and(|t| t.elapsed().as_millis() < min_interval as u128) {
+            return;
+        }
+        *last = Some(std::time::Instant::now());
+    }
+    let redact = m.and_then(|m| m.redact_person_names).unwrap_or(true);
+    let matched_persons = if redact || !single_scale {
+        None
+    } else {
+        faces::last_matches().map_err(|e| debug!("[cal] read last matches failed: {}", e)).ok()
+    };
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    emitter::emit_detection_cycle(emitter::DetectionCycleEvent {
+        monitor_id: monitor.id,
+        timestamp,
+        capture_ms,
+        detection_ms,
+        method: method.to_string(),
+        face_count,
+        matched_persons,
+    });
+}
+
+fn detection_max_dim() -> Option<u32> {
+    config::get_config()
+        .and_then(|c| c.face)
+        .and_then(|f| f.detection.max_dim)
+        .filter(|d| *d > 0)
+        // 过小时人脸已无法检出，至少保留 160px
+        .map(|d| d.max(160))
+}
+
+// 检测图相对显示器物理像素的总倍率：capture_scale（0.1~1.0）与截图自身倍率取小，再受 detection.max_dim 限制长边
+fn detection_resize_ratio(monitor: &MonitorInfo, source: &screen_shot::Image, image_scale: f32) -> f32 {
+    let capture_scale = monitoring_config_for(monitor)
+        .and_then(|m| m.capture_scale)
+        .unwrap_or(1.0);
+    let target_ratio = if capture_scale > 0.0 && capture_scale < 0.9999 { capture_scale.max(0.1) } else { 1.0 };
+    let mut resize_ratio = target_ratio.min(image_scale);
+    // detection.max_dim：检测图长边的绝对上限，限制每帧经 FFI 传给 Python 的数据量
+    if let Some(max_dim) = detection_max_dim() {
+        let long_edge = source.width.max(source.height) as f32 / image_scale;
+        if long_edge * resize_ratio > max_dim as f32 {
+            resize_ratio = max_dim as f32 / long_edge;
+            debug!("[cal] detection image capped to {} px (ratio {:.3})", max_dim, resize_ratio);
+        }
+    }
+    resize_ratio
+}
+
+// 将显示器物理像素坐标的矩形换算到倍率为 scale 的图像上，并与图像求交（至少 1px）
+fn scale_rect_into(rect: &Rect, scale: f32, image: &screen_shot::Image) -> Rect {
+    if scale >= 0.9999 {
+        return rect.clone();
+    }
+    let x = ((rect.x as f32 * scale).round() as i32).clamp(0, (image.width - 1).max(0));
+    let y = ((rect.y as f32 * scale).round() as i32).clamp(0, (image.height - 1).max(0));
+    let w = ((rect.width as f32 * scale).round() as i32).clamp(1, (image.width - x).max(1));
+    let h = ((rect.height as f32 * scale).round() as i32).clamp(1, (image.height - y).max(1));
+    Rect::new(x, y, w, h)
+}
+
+// 按矩形（已与图像求交）裁剪 BGRA 图像
+fn crop_image_bgra(src: &screen_shot::Image, rect: &Rect) -> screen_shot::Image {
+    let src_stride = src.width as usize * 4;
+    let row_len = rect.width as usize * 4;
+    let mut data = Vec::with_capacity(row_len * rect.height as usize);
+    for y in rect.y..rect.y + rect.height {
+        let start = y as usize * src_stride + rect.x as usize * 4;
+        data.extend_from_slice(&src.data[start..start + row_len]);
+    }
+    screen_shot::Image { width: rect.width, height: rect.height, data }
+}
+
+// 送检图像的下采样：按 downscale_filter 选择区域平均（缺省）或最近邻
+fn downscale_for_detection(src: &screen_shot::Image, scale: f32) -> screen_shot::Image {
+    let filter = config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.downscale_filter)
+        .unwrap_or_default();
+    match filter {
+        DownscaleFilter::Nearest => downscale_image_bgra(src, scale),
+        DownscaleFilter::Area => downscale_image_bgra_area(src, scale),
+    }
+}
+
+// 区域平均缩放 BGRA 图像：目标像素取其在源图中覆盖区域（至少 1 像素）的均值
+fn downscale_image_bgra_area(src: &screen_shot::Image, scale: f32) -> screen_shot::Image {
+    let src_w = src.width.max(1) as usize;
+    let src_h = src.height.max(1) as usize;
+    let dst_w = ((src.width as f32) * scale).round().max(1.0) as usize;
+    let dst_h = ((src.height as f32) * scale).round().max(1.0) as usize;
+    if dst_w == src_w && dst_h == src_h {
+        return src.clone();
+    }
+
+    // 预先计算每个目标列覆盖的源列区间
+    let x_spans: Vec<(usize, usize)> = (0..dst_w)
+        .map(|dx| {
+            let x0 = (dx * src_w) / dst_w;
+            let x1 = (((dx + 1) * src_w) / dst_w).max(x0 + 1).min(src_w);
+            (x0, x1)
+        })
+        .collect();
+
+    let mut dst = vec![0u8; dst_w * dst_h * 4];
+    let mut row_sum = vec![0u32; src_w * 4];
+    for dy in 0..dst_h {
+        let y0 = (dy * src_h) / dst_h;
+        let y1 = (((dy + 1) * src_h) / dst_h).max(y0 + 1).min(src_h);
+        // 先对覆盖的源行逐列求和，再按列区间求和
+        row_sum.iter_mut().for_each(|v| *v = 0);
+        for sy in y0..y1 {
+            let row = &src.data[sy * src_w * 4..(sy + 1) * src_w * 4];
+            for (acc, &v) in row_sum.iter_mut().zip(row) {
+                *acc += v as u32;
+            }
+        }
+        let rows = (y1 - y0) as u32;
+        for (dx, &(x0, x1)) in x_spans.iter().enumerate() {
+            let n = rows * (x1 - x0) as u32;
+            let didx = (dy * dst_w + dx) * 4;
+            for c in 0..4 {
+                let sum: u32 = (x0..x1).map(|sx| row_sum[sx * 4 + c]).sum();
+                dst[didx + c] = ((sum + n / 2) / n) as u8;
+            }
+        }
+    }
+
+    screen_shot::Image { width: dst_w as i32, height: dst_h as i32, data: dst }
+}
+
 // 最近邻快速缩放 BGRA 图像
 fn downscale_image_bgra(src: &screen_shot::Image, scale: f32) -> screen_shot::Image {
     let src_w = src.width.max(1) as usize;
@@ -262,4 +1317,163 @@ fn downscale_image_bgra(src: &screen_shot::Image, scale: f32) -> screen_shot::Im
     }
 
     screen_shot::Image { width: dst_w as i32, height: dst_h as i32, data: dst }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boxes(sizes: &[(i32, i32)]) -> Vec<(Rect, f32)> {
+        sizes.iter().map(|&(w, h)| (Rect::new(0, 0, w, h), 0.0)).collect()
+    }
+
+    fn sizes(items: &[(Rect, f32)]) -> Vec<(i32, i32)> {
+        items.iter().map(|(r, _)| (r.width, r.height)).collect()
+    }
+
+    #[test]
+    fn filter_min_cover_area_keeps_boxes_at_the_limit() {
+        // 100 = 10x10 恰好等于下限保留；99 = 9x11 少 1 被丢弃
+        let kept = filter_min_cover_area(boxes(&[(10, 10), (9, 11), (20, 5)]), 100);
+        assert_eq!(sizes(&kept), vec![(10, 10), (20, 5)]);
+    }
+
+    #[test]
+    fn filter_min_cover_area_zero_passes_everything_through() {
+        let kept = filter_min_cover_area(boxes(&[(0, 0), (1, 1), (-3, 4)]), 0);
+        assert_eq!(sizes(&kept), vec![(0, 0), (1, 1), (-3, 4)]);
+    }
+
+    fn rects(items: &[(Rect, f32)]) -> Vec<(i32, i32, i32, i32)> {
+        items.iter().map(|(r, _)| (r.x, r.y, r.width, r.height)).collect()
+    }
+
+    #[test]
+    fn inflate_and_merge_without_params_is_identity() {
+        let items = vec![(Rect::new(-5, -5, 10, 10).with_score(Some(0.9)), 12.0)];
+        let out = inflate_and_merge(items, &Rect::new(0, 0, 100, 100), RedactionParams::default());
+        assert_eq!(rects(&out), vec![(-5, -5, 10, 10)]);
+        assert_eq!(out[0].1, 12.0);
+        assert_eq!(out[0].0.score, Some(0.9));
+    }
+
+    #[test]
+    fn inflate_and_merge_merges_boxes_that_touch_after_padding() {
+        // 两框相距 8px：外扩 4px 后恰好贴合，gap=0 即合并为外接框，角度与分数清空
+        let items = vec![
+            (Rect::new(10, 10, 20, 20).with_score(Some(0.8)), 5.0),
+            (Rect::new(38, 10, 20, 20).with_score(Some(0.7)), -5.0),
+        ];
+        let params = RedactionParams { pad: 4, merge_gap: Some(0), merge_iou: None };
+        let out = inflate_and_merge(items, &Rect::new(0, 0, 200, 200), params);
+        assert_eq!(rects(&out), vec![(6, 6, 56, 28)]);
+        assert_eq!(out[0].1, 0.0);
+        assert_eq!(out[0].0.score, None);
+    }
+
+    #[test]
+    fn inflate_and_merge_keeps_disjoint_boxes_apart_without_enough_padding() {
+        let items = vec![(Rect::new(10, 10, 20, 20), 5.0), (Rect::new(38, 10, 20, 20), -5.0)];
+        let params = RedactionParams { pad: 3, merge_gap: Some(0), merge_iou: None };
+        let out = inflate_and_merge(items, &Rect::new(0, 0, 200, 200), params);
+        assert_eq!(rects(&out), vec![(7, 7, 26, 26), (35, 7, 26, 26)]);
+        assert_eq!(out[0].1, 5.0);
+        assert_eq!(out[1].1, -5.0);
+    }
+
+    #[test]
+    fn inflate_and_merge_clamps_to_monitor_edges() {
+        let items = vec![
+            (Rect::new(2, 3, 10, 10).with_score(Some(0.5)), 0.0),
+            (Rect::new(92, 90, 10, 10), 0.0),
+            (Rect::new(300, 300, 10, 10), 0.0),
+        ];
+        let params = RedactionParams { pad: 5, merge_gap: None, merge_iou: None };
+        let out = inflate_and_merge(items, &Rect::new(0, 0, 100, 100), params);
+        // 越过左上/右下边缘的部分被裁掉，完全在显示器外的框被丢弃；裁剪保留分数
+        assert_eq!(rects(&out), vec![(0, 0, 17, 18), (87, 85, 13, 15)]);
+        assert_eq!(out[0].0.score, Some(0.5));
+    }
+
+    #[test]
+    fn split_for_overlays_maps_a_straddling_box_into_each_monitor_with_its_own_scale() {
+        // 左屏 1920x1080@100%，右屏 3840x2160@150% 紧贴其右侧、上沿对齐
+        let left = MonitorInfo { id: 0, x: 0, y: 0, width: 1920, height: 1080, scale_factor: 1.0, name: None };
+        let right = MonitorInfo { id: 1, x: 1920, y: 0, width: 3840, height: 2160, scale_factor: 1.5, name: None };
+        let overlays = [left.clone(), right.clone()];
+
+        // 左屏的框越过右缘 60px：两块分别落在各自 overlay 的本地坐标，并带上各自的缩放
+        let items = vec![(Rect::new(1860, 500, 120, 150).with_score(Some(0.9)), 10.0)];
+        let pieces = split_for_overlays(items, &left, &overlays);
+        assert_eq!(pieces.iter().map(|p| (p.monitor_id, p.scale_factor)).collect::<Vec<_>>(), vec![(0, 1.0), (1, 1.5)]);
+        assert_eq!(rects(&pieces[0].items), vec![(1860, 500, 60, 150)]);
+        assert_eq!(rects(&pieces[1].items), vec![(0, 500, 60, 150)]);
+        // 同样 60 物理像素宽，在右屏 overlay 上只占 40 逻辑像素
+        assert_eq!(pieces[1].items[0].0.width as f64 / pieces[1].scale_factor, 40.0);
+        assert_eq!((pieces[1].items[0].0.score, pieces[1].items[0].1), (Some(0.9), 10.0));
+
+        // 右屏的框越过左缘，但位于左屏下沿（1080）以下：左屏一项为空，右屏保留裁剪后的部分
+        let items = vec![(Rect::new(-40, 2000, 100, 100), 0.0)];
+        let pieces = split_for_overlays(items, &right, &overlays);
+        assert!(pieces[0].items.is_empty());
+        assert_eq!(rects(&pieces[1].items), vec![(0, 2000, 60, 100)]);
+    }
+
+    #[test]
+    fn repeated_starts_keep_a_single_loop() {
+        use std::sync::atomic::{AtomicBool, AtomicUsize};
+        use std::sync::Arc;
+
+        let slot: StdMutex<Option<std::thread::JoinHandle<()>>> = StdMutex::new(None);
+        let spawned = AtomicUsize::new(0);
+        let stop = Arc::new(AtomicBool::new(false));
+        let start = || {
+            let stop = Arc::clone(&stop);
+            ensure_loop_thread(&mut lock_or_recover(&slot, "slot"), || {
+                spawned.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || {
+                    while !stop.load(Ordering::SeqCst) {
+                        std::thread::sleep(std::time::Duration::from_millis(1));
+                    }
+                })
+            })
+        };
+
+        // 并发快速重复启动（如双击）：只创建一个循环
+        let started: usize = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..8).map(|_| s.spawn(|| start() as usize)).collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+        assert_eq!(started, 1);
+        assert_eq!(spawned.load(Ordering::SeqCst), 1);
+        assert!(!start());
+
+        // 旧循环退出后再次启动会回收它并新建
+        stop.store(true, Ordering::SeqCst);
+        while !lock_or_recover(&slot, "slot").as_ref().unwrap().is_finished() {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        stop.store(false, Ordering::SeqCst);
+        assert!(start());
+        assert_eq!(spawned.load(Ordering::SeqCst), 2);
+        stop.store(true, Ordering::SeqCst);
+        lock_or_recover(&slot, "slot").take().unwrap().join().unwrap();
+    }
+
+    #[test]
+    fn detection_error_hold_last_sends_nothing() {
+        assert!(detection_error_payload(DetectionErrorPolicy::HoldLast, 1920, 1080).is_none());
+    }
+
+    #[test]
+    fn detection_error_fail_closed_covers_the_whole_monitor() {
+        let items = detection_error_payload(DetectionErrorPolicy::FailClosed, 1920, 1080).unwrap();
+        assert_eq!(rects(&items), vec![(0, 0, 1920, 1080)]);
+        assert_eq!(items[0].1, 0.0);
+    }
+
+    #[test]
+    fn detection_error_clear_sends_an_empty_set() {
+        let items = detection_error_payload(DetectionErrorPolicy::Clear, 1920, 1080).unwrap();
+        assert!(items.is_empty());
+    }
+}
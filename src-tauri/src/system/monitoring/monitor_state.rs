@@ -2,6 +2,7 @@ use std::sync::Mutex;
 
 use once_cell::sync::Lazy;
 use crate::monitor::MonitorInfo;
+use crate::utils::sync::lock_or_recover;
 
 static MONITOR_STATE: Lazy<Mutex<Option<MonitorState>>> = Lazy::new(|| Mutex::new(None));
 
@@ -14,24 +15,23 @@ impl MonitorState {
 
     /// 设置全局实例
     pub fn set_working(monitor: Option<MonitorInfo>) -> Result<(), Box<dyn std::error::Error>> {
+        crate::config::set_active_monitor(monitor.as_ref().and_then(|m| m.name.clone()));
         if let Some(monitor) = monitor {
-            let mut guard = MONITOR_STATE.lock().map_err(|e| format!("Failed to lock app mutex: {}", e))?;
-            *guard = Some(MonitorState { working_monitor: monitor });
+            *lock_or_recover(&MONITOR_STATE, "monitor_state") = Some(MonitorState { working_monitor: monitor });
         } else {
-            let mut guard = MONITOR_STATE.lock().map_err(|e| format!("Failed to lock app mutex: {}", e))?;
-            *guard = None;
+            *lock_or_recover(&MONITOR_STATE, "monitor_state") = None;
         }
         Ok(())
     }
 
     /// 获取全局实例
     pub fn get_working() -> Result<MonitorInfo, Box<dyn std::error::Error>> {
-        let guard = MONITOR_STATE.lock().map_err(|e| format!("Failed to lock app mutex: {}", e))?;
+        let guard = lock_or_recover(&MONITOR_STATE, "monitor_state");
         guard.clone().ok_or_else(|| "current monitor not set".into()).map(|state| state.working_monitor)
     }
 
     /// 检查是否已初始化
     pub fn is_working_set() -> bool {
-        MONITOR_STATE.lock().map(|guard| guard.is_some()).unwrap_or(false)
+        lock_or_recover(&MONITOR_STATE, "monitor_state").is_some()
     }
 }
\ No newline at end of file
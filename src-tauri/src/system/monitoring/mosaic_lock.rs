@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::info;
+use once_cell::sync::Lazy;
+
+use crate::api::emitter;
+use crate::utils::sync::lock_or_recover;
+
+// 锁定遮罩：截止时间之前 cal() 照常截图与检测（性能统计不受影响），但不用检测结果更新遮罩，保持锁定时的遮罩集合
+static LOCKED_UNTIL: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+// 每次设置递增，旧的解锁线程据此退出，避免重复提示
+static LOCK_GEN: AtomicU64 = AtomicU64::new(0);
+
+// 到期后的清理与提示由解锁线程负责，这里只比较时间
+pub fn is_active() -> bool {
+    matches!(*lock_or_recover(&LOCKED_UNTIL, "mosaic_lock"), Some(t) if Instant::now() < t)
+}
+
+// duration_ms 为 0 时解锁；重复调用以最后一次为准。quiet 为 true 时不提示（停止监控时）
+pub fn set(duration_ms: u64, quiet: bool) {
+    let generation = LOCK_GEN.fetch_add(1, Ordering::SeqCst) + 1;
+    if duration_ms == 0 {
+        if lock_or_recover(&LOCKED_UNTIL, "mosaic_lock").take().is_some() {
+            info!("[mosaic_lock] unlocked");
+            if !quiet {
+                emitter::emit_toast("遮罩已解锁");
+            }
+        }
+        return;
+    }
+    let until = Instant::now() + Duration::from_millis(duration_ms);
+    *lock_or_recover(&LOCKED_UNTIL, "mosaic_lock") = Some(until);
+    info!("[mosaic_lock] locked for {} ms", duration_ms);
+    emitter::emit_toast(&format!("遮罩已锁定 {} 秒，期间忽略新的检测结果", (duration_ms as f32 / 1000.0).ceil() as u64));
+
+    // 到期自动解锁并提示
+    std::thread::spawn(move || {
+        std::thread::sleep(until.saturating_duration_since(Instant::now()));
+        if LOCK_GEN.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        if lock_or_recover(&LOCKED_UNTIL, "mosaic_lock").take().is_some() {
+            info!("[mosaic_lock] lock expired");
+            emitter::emit_toast("遮罩锁定已结束");
+        }
+    });
+}
@@ -0,0 +1,557 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use log::{debug, error, info, warn};
+use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
+
+use crate::ai::faces;
+use crate::api::emitter;
+use crate::config;
+use crate::monitor::{screen_shot, MonitorInfo};
+use crate::overlay;
+use crate::utils::rect::Rect;
+use crate::utils::sync::lock_or_recover;
+
+use super::{cover_all, mosaic_lock, taskbar, visibility, MonitorState};
+
+// 副显示器并行监控：工作显示器之外（monitoring.extra_monitors），每个显示器一对线程——
+// 截图线程（含单帧预取）与检测线程。截图线程各自初始化 COM，使用本显示器独立的 DirectXResourceManager 与截图锁，
+// 锁顺序与工作显示器一致：截图时只持有截图锁，写入帧缓存时再短暂获取帧缓存锁。
+// 检测线程把任务提交到共享的 detection_queue（按显示器 latest-wins、轮转），结果投递到该显示器自己的 overlay，
+// 越过接缝的框按各 overlay 的显示器切分，落在相邻显示器上的部分投递到相邻显示器的 overlay。
+// 跟随活动窗口、截图区域、录制与烘焙遮罩只作用于工作显示器
+
+// 检测线程等待新帧的超时：超时后检查停止标志
+const FRAME_WAIT: Duration = Duration::from_millis(100);
+
+struct Shared {
+    monitor: MonitorInfo,
+    label: String,
+    // 与 Worker 共享的停止标志
+    stop: Arc<AtomicBool>,
+    capture_lock: Mutex<()>,
+    next_frame: Mutex<Option<screen_shot::CaptureOutcome>>,
+    frame_ready: Condvar,
+}
+
+struct Worker {
+    monitor: MonitorInfo,
+    stop: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+static WORKERS: OnceLock<Mutex<HashMap<usize, Worker>>> = OnceLock::new();
+// 当前的 overlay 集合（工作显示器 + 副显示器），切分遮罩框时使用；与 WORKERS 分开加锁，
+// 检测线程读取时不会与 sync 中 join 检测线程的一方互相等待
+static OVERLAY_SET: OnceLock<Mutex<Vec<MonitorInfo>>> = OnceLock::new();
+// 各 overlay 上的遮罩来源：目标显示器 -> (来源显示器 -> 目标本地坐标的框)；来源等于目标时为其自身的检测结果
+static COVERAGE: OnceLock<Mutex<HashMap<usize, HashMap<usize, Vec<(Rect, f32)>>>>> = OnceLock::new();
+
+fn workers() -> &'static Mutex<HashMap<usize, Worker>> {
+    WORKERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn overlay_set() -> &'static Mutex<Vec<MonitorInfo>> {
+    OVERLAY_SET.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+fn coverage() -> &'static Mutex<HashMap<usize, HashMap<usize, Vec<(Rect, f32)>>>> {
+    COVERAGE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 按 monitoring.extra_monitors 启停副显示器的截图/检测线程与 overlay：
+/// 开始监控、显示器插拔/分辨率变化与配置变化后调用，可重复调用
+pub fn sync(working: &MonitorInfo) {
+    let names = config::get_config()
+        .and_then(|c| c.monitoring)
+        .and_then(|m| m.extra_monitors)
+        .unwrap_or_default();
+    let wanted = if names.is_empty() {
+        Vec::new()
+    } else {
+        match crate::monitor::monitor::list_monitors() {
+            Ok(all) => wanted_monitors(working, &all, &names),
+            Err(e) => {
+                error!("[multi_monitor] list monitors failed: {}", e);
+                return;
+            }
+        }
+    };
+    let mut guard = lock_or_recover(workers(), "extra_monitor_workers");
+    // 与停止监控竞争：停止时先清除工作显示器再 stop_all，持锁后再次确认，避免停止后又启动线程
+    if !MonitorState::is_working_set() {
+        return;
+    }
+    let removed = sync_workers(&mut guard, &wanted, start_worker);
+    let mut overlays = vec![working.clone()];
+    overlays.extend(wanted);
+    // 丢弃已不在集合中的显示器留下的遮罩来源
+    lock_or_recover(coverage(), "overlay_coverage").retain(|target, sources| {
+        sources.retain(|source, _| overlays.iter().any(|m| m.id == *source));
+        overlays.iter().any(|m| m.id == *target)
+    });
+    *lock_or_recover(overlay_set(), "overlay_set") = overlays;
+    drop(guard);
+    for id in removed {
+        overlay::close_labeled_overlay_window(&overlay::extra_overlay_label(id));
+    }
+}
+
+/// 停止全部副显示器线程并关闭其 overlay（停止监控时调用）
+pub fn stop_all() {
+    let stopped: Vec<Worker> = lock_or_recover(workers(), "extra_monitor_workers").drain().map(|(_, w)| w).collect();
+    lock_or_recover(overlay_set(), "overlay_set").clear();
+    lock_or_recover(coverage(), "overlay_coverage").clear();
+    if stopped.is_empty() {
+        return;
+    }
+    let ids: Vec<usize> = stopped.iter().map(|w| w.monitor.id).collect();
+    stop_workers(stopped);
+    for id in ids {
+        overlay::close_labeled_overlay_window(&overlay::extra_overlay_label(id));
+    }
+    info!("[multi_monitor] all extra monitors stopped");
+}
+
+// 参与切分的 overlay：来源显示器不在集合中时（尚未同步）只有它自己
+fn overlays_for(source: &MonitorInfo) -> Vec<MonitorInfo> {
+    let set = lock_or_recover(overlay_set(), "overlay_set").clone();
+    if set.iter().any(|m| m.id == source.id) {
+        set
+    } else {
+        vec![source.clone()]
+    }
+}
+
+/// 全部 overlay 的外包矩形（source 本地坐标）：遮罩框外扩后裁剪到这里，越过接缝的部分再切分到相邻显示器
+pub(super) fn overlay_extent(source: &MonitorInfo) -> Rect {
+    let overlays = overlays_for(source);
+    let left = overlays.iter().map(|m| m.x).min().unwrap_or(source.x);
+    let top = overlays.iter().map(|m| m.y).min().unwrap_or(source.y);
+    let right = overlays.iter().map(|m| m.x + m.width).max().unwrap_or(source.x + source.width);
+    let bottom = overlays.iter().map(|m| m.y + m.height).max().unwrap_or(source.y + source.height);
+    Rect::new(left - source.x, top - source.y, right - left, bottom - top)
+}
+
+/// 把 source 的遮罩框（本地坐标）按 overlay 切分：落在其他 overlay 上的部分记为该 overlay 的外来遮罩并立即重发
+/// （工作显示器的 overlay 则令其下一轮重新检测后合并），返回落在 source 自身 overlay 上的部分
+pub(super) fn route(source: &MonitorInfo, items: Vec<(Rect, f32)>) -> Vec<(Rect, f32)> {
+    let overlays = overlays_for(source);
+    if overlays.len() == 1 {
+        return super::split_for_overlays(items, source, &overlays).pop().map(|p| p.items).unwrap_or_default();
+    }
+    let working_id = super::working_monitor().map(|m| m.id);
+    let mut own = Vec::new();
+    let mut cov = lock_or_recover(coverage(), "overlay_coverage");
+    for piece in super::split_for_overlays(items, source, &overlays) {
+        let target = piece.monitor_id;
+        let sources = cov.entry(target).or_default();
+        if target == source.id {
+            sources.insert(target, piece.items.clone());
+            own = piece.items;
+            continue;
+        }
+        let before = sources.insert(source.id, piece.items.clone()).unwrap_or_default();
+        if before.is_empty() && piece.items.is_empty() {
+            continue;
+        }
+        if Some(target) == working_id {
+            // 工作显示器有自己的遮罩状态（保持、锁定、跟踪等）：不在这里推送，令其下一轮重新检测并带上外来部分
+            super::DETECTED_SEQ.store(u64::MAX, Ordering::SeqCst);
+        } else if hold_state().is_none() && !mosaic_lock::is_active() {
+            let Some(monitor) = overlays.iter().find(|m| m.id == target) else {
+                continue;
+            };
+            let shown: Vec<(Rect, f32)> = sources.values().flatten().cloned().collect();
+            emit_to_overlay(monitor, piece.scale_factor, shown);
+        }
+    }
+    own
+}
+
+/// 其他显示器越过接缝落在 target 上的遮罩框（target 本地坐标）
+pub(super) fn spill_into(target: usize) -> Vec<(Rect, f32)> {
+    lock_or_recover(coverage(), "overlay_coverage")
+        .get(&target)
+        .map(|sources| sources.iter().filter(|(s, _)| **s != target).flat_map(|(_, v)| v.iter().cloned()).collect())
+        .unwrap_or_default()
+}
+
+// 推送到副显示器的 overlay，dpi_scale 取该显示器自身的缩放
+fn emit_to_overlay(monitor: &MonitorInfo, dpi_scale: f64, items: Vec<(Rect, f32)>) {
+    let mosaic_scale = super::monitoring_config_for(monitor)
+        .map(|m| m.mosaic_scale)
+        .unwrap_or(1.0f32);
+    let bounds = Rect::new(0, 0, monitor.width, monitor.height);
+    overlay::overlay::apply_mosaic_to_overlay(&overlay::extra_overlay_label(monitor.id), items, mosaic_scale, dpi_scale, &bounds);
+}
+
+fn is_active(monitor_id: usize) -> bool {
+    lock_or_recover(workers(), "extra_monitor_workers").contains_key(&monitor_id)
+}
+
+// 按设备名选出需要并行监控的显示器；"*" 表示全部，工作显示器本身始终排除
+fn wanted_monitors(working: &MonitorInfo, all: &[MonitorInfo], names: &[String]) -> Vec<MonitorInfo> {
+    let everything = names.iter().any(|n| n == "*");
+    all.iter()
+        .filter(|m| m.id != working.id)
+        .filter(|m| everything || m.name.as_ref().is_some_and(|name| names.contains(name)))
+        .cloned()
+        .collect()
+}
+
+fn same_geometry(a: &MonitorInfo, b: &MonitorInfo) -> bool {
+    (a.x, a.y, a.width, a.height) == (b.x, b.y, b.width, b.height) && a.scale_factor == b.scale_factor
+}
+
+// 对比当前与期望的显示器集合：返回需要停止的显示器序号与需要启动的显示器；几何变化的显示器先停后启
+fn plan_changes(current: &[MonitorInfo], wanted: &[MonitorInfo]) -> (Vec<usize>, Vec<MonitorInfo>) {
+    let stop = current
+        .iter()
+        .filter(|c| !wanted.iter().any(|w| w.id == c.id && same_geometry(w, c)))
+        .map(|c| c.id)
+        .collect();
+    let start = wanted
+        .iter()
+        .filter(|w| !current.iter().any(|c| c.id == w.id && same_geometry(w, c)))
+        .cloned()
+        .collect();
+    (stop, start)
+}
+
+// 先通知全部线程停止再逐个 join，多个显示器的停止等待不叠加
+fn stop_workers(stopped: Vec<Worker>) {
+    for worker in &stopped {
+        worker.stop.store(true, Ordering::SeqCst);
+    }
+    for worker in stopped {
+        for thread in worker.threads {
+            if thread.join().is_err() {
+                error!("[multi_monitor] worker thread of monitor {} panicked", worker.monitor.id);
+            }
+        }
+    }
+}
+
+// 使 workers 与 wanted 一致，返回已停止且不再需要的显示器序号。调用方需持有保护 workers 的锁
+fn sync_workers<F>(workers: &mut HashMap<usize, Worker>, wanted: &[MonitorInfo], mut start: F) -> Vec<usize>
+where
+    F: FnMut(&MonitorInfo) -> Worker,
+{
+    let current: Vec<MonitorInfo> = workers.values().map(|w| w.monitor.clone()).collect();
+    let (stop_ids, start_monitors) = plan_changes(&current, wanted);
+    let stopped: Vec<Worker> = stop_ids.iter().filter_map(|id| workers.remove(id)).collect();
+    stop_workers(stopped);
+    for monitor in &start_monitors {
+        workers.insert(monitor.id, start(monitor));
+    }
+    stop_ids.into_iter().filter(|id| !wanted.iter().any(|w| w.id == *id)).collect()
+}
+
+fn start_worker(monitor: &MonitorInfo) -> Worker {
+    info!(
+        "[multi_monitor] start monitor {} {}x{}@({}, {})",
+        monitor.id, monitor.width, monitor.height, monitor.x, monitor.y
+    );
+    let label = overlay::extra_overlay_label(monitor.id);
+    let stop = Arc::new(AtomicBool::new(false));
+    let shared = Arc::new(Shared {
+        monitor: monitor.clone(),
+        label: label.clone(),
+        stop: Arc::clone(&stop),
+        capture_lock: Mutex::new(()),
+        next_frame: Mutex::new(None),
+        frame_ready: Condvar::new(),
+    });
+    // overlay 在异步任务中创建；创建前后都确认该显示器仍在监控，避免与随后的停止竞争而留下孤立窗口
+    let overlay_monitor = monitor.clone();
+    tauri::async_runtime::spawn(async move {
+        if !is_active(overlay_monitor.id) {
+            return;
+        }
+        if let Err(e) = overlay::create_labeled_overlay_window(&overlay_monitor, &label).await {
+            error!("[multi_monitor] create overlay for monitor {} failed: {}", overlay_monitor.id, e);
+            return;
+        }
+        if !is_active(overlay_monitor.id) {
+            overlay::close_labeled_overlay_window(&label);
+        }
+    });
+    let capture = {
+        let shared = Arc::clone(&shared);
+        std::thread::spawn(move || capture_loop(&shared))
+    };
+    let detection = {
+        let shared = Arc::clone(&shared);
+        std::thread::spawn(move || detection_loop(&shared))
+    };
+    Worker { monitor: monitor.clone(), stop, threads: vec![capture, detection] }
+}
+
+// 是否暂停截图与检测，以及暂停期间的遮罩：与工作显示器一致，临时全屏遮罩与遮罩不可见时全屏遮罩（Some(true)），
+// 手动暂停时清空（Some(false)），None 表示正常检测
+fn hold_state() -> Option<bool> {
+    if cover_all::is_active() {
+        Some(true)
+    } else if super::MANUALLY_PAUSED.load(Ordering::SeqCst) {
+        Some(false)
+    } else if visibility::should_pause() {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+// 本显示器的截图间隔（已合并 [monitor_overrides]），与工作显示器一致钳制到 8~1000ms
+fn capture_interval(monitor: &MonitorInfo) -> Duration {
+    let interval = super::monitoring_config_for(monitor).map(|m| m.interval).unwrap_or(8);
+    Duration::from_millis(interval.clamp(8, 1000))
+}
+
+fn capture_loop(shared: &Shared) {
+    unsafe {
+        // 每个截图线程都要初始化 COM
+        let result = CoInitializeEx(None, COINIT_MULTITHREADED);
+        if result.is_err() {
+            error!("CoInitializeEx failed: {result:?}");
+        }
+    }
+    let monitor = &shared.monitor;
+    let mut failing = false;
+    while !shared.stop.load(Ordering::SeqCst) {
+        // 单帧预取：上一帧尚未被检测线程取走时不再截图
+        if hold_state().is_some() || lock_or_recover(&shared.next_frame, "extra_next_frame").is_some() {
+            std::thread::sleep(capture_interval(monitor));
+            continue;
+        }
+        // 截图时仅持有本显示器的截图锁；写入帧缓存时再短暂获取帧缓存锁，锁顺序固定为先截图锁后帧缓存
+        let captured = {
+            let _g = lock_or_recover(&shared.capture_lock, "extra_capture_lock");
+            screen_shot::capture_monitor_region(monitor, None, super::gpu_capture_scale(monitor))
+        };
+        match captured {
+            Ok(outcome) => {
+                failing = false;
+                *lock_or_recover(&shared.next_frame, "extra_next_frame") = Some(outcome);
+                shared.frame_ready.notify_one();
+            }
+            Err(e) => {
+                // 连续失败只上报一次
+                if !failing {
+                    error!("[multi_monitor] capture monitor {} failed: {}", monitor.id, e);
+                    emitter::emit_error("capture_failed", &e.to_string(), Some(&format!("monitor {}", monitor.id)));
+                }
+                failing = true;
+            }
+        }
+        std::thread::sleep(capture_interval(monitor));
+    }
+    // 在本显示器的截图锁内丢弃预取帧并释放截图资源，其余显示器不受影响
+    let _g = lock_or_recover(&shared.capture_lock, "extra_capture_lock");
+    lock_or_recover(&shared.next_frame, "extra_next_frame").take();
+    screen_shot::release_monitor_capture_resources(monitor.id);
+    screen_shot::reset_capture_stats(Some(monitor.id));
+    debug!("[multi_monitor] capture thread of monitor {} exited", monitor.id);
+}
+
+fn detection_loop(shared: &Shared) {
+    let monitor = &shared.monitor;
+    let full = Rect::new(0, 0, monitor.width, monitor.height);
+    // 最近一次检测所在的流水线代次：画面未变化且代次未变时沿用当前遮罩
+    let mut detected_generation: Option<u64> = None;
+    // 当前已下发的暂停遮罩（全屏或清空），只在进入或切换时下发一次
+    let mut held: Option<bool> = None;
+    while !shared.stop.load(Ordering::SeqCst) {
+        if let Some(cover) = hold_state() {
+            if held != Some(cover) {
+                let items = if cover { vec![(full.clone(), 0.0)] } else { Vec::new() };
+                overlay::overlay::apply_mosaic_to_overlay(&shared.label, items, 1.0, monitor.scale_factor, &full);
+                held = Some(cover);
+            }
+            // 暂停前预取的帧已过期，恢复后重新截图
+            lock_or_recover(&shared.next_frame, "extra_next_frame").take();
+            detected_generation = None;
+            std::thread::sleep(FRAME_WAIT);
+            continue;
+        }
+        held = None;
+        let outcome = {
+            let slot = lock_or_recover(&shared.next_frame, "extra_next_frame");
+            let (mut slot, _) = shared
+                .frame_ready
+                .wait_timeout_while(slot, FRAME_WAIT, |f| f.is_none() && !shared.stop.load(Ordering::SeqCst))
+                .unwrap_or_else(|e| e.into_inner());
+            slot.take()
+        };
+        let Some(outcome) = outcome else {
+            continue;
+        };
+        if !faces::is_face_model_ready() {
+            // 模型加载失败：静态遮罩模式，遮住整个显示器
+            if faces::is_face_model_failed() {
+                overlay::overlay::apply_mosaic_to_overlay(&shared.label, vec![(full.clone(), 0.0)], 1.0, monitor.scale_factor, &full);
+            }
+            continue;
+        }
+        let generation = super::PIPELINE_GENERATION.load(Ordering::SeqCst);
+        if outcome.is_unchanged() && super::skip_unchanged_frames() && detected_generation == Some(generation) {
+            continue;
+        }
+        if let Some(generation) = detect_and_apply(shared, outcome) {
+            detected_generation = Some(generation);
+        }
+    }
+    debug!("[multi_monitor] detection thread of monitor {} exited", monitor.id);
+}
+
+// 检测一帧并推送到本显示器的 overlay，返回检测所在的流水线代次；被替换或结果过期时返回 None
+fn detect_and_apply(shared: &Shared, outcome: screen_shot::CaptureOutcome) -> Option<u64> {
+    let monitor = &shared.monitor;
+    let screen_shot::CaptureOutcome { image, scale: image_scale, .. } = outcome;
+    let resize_ratio = super::detection_resize_ratio(monitor, &image, image_scale);
+    let cpu_ratio = resize_ratio / image_scale;
+    let detection_image = Arc::new(if cpu_ratio < 0.9999 {
+        super::downscale_for_detection(&image, cpu_ratio)
+    } else {
+        image
+    });
+    let job_image = Arc::clone(&detection_image);
+    let (result, generation) = super::run_detection(monitor.id, move || faces::detect_faces_with_angle(&job_image))?;
+    match result {
+        Ok(rects_with_angle) => {
+            let min_cover_area = super::monitoring_config_for(monitor)
+                .and_then(|m| m.min_cover_area_px)
+                .unwrap_or(0);
+            let min_cover_area_det = ((min_cover_area as f64) * (resize_ratio as f64).powi(2)).round() as u64;
+            let rects_with_angle = super::filter_min_cover_area(rects_with_angle, min_cover_area_det);
+            let inv = 1.0f32 / resize_ratio;
+            let mapped: Vec<(Rect, f32)> = rects_with_angle
+                .into_iter()
+                .map(|(r, a)| (r.scaled_outward(inv, inv), a))
+                .collect();
+            let mapped = if taskbar::is_enabled() {
+                super::exclude_regions(mapped, &taskbar::excluded_rects(monitor))
+            } else {
+                mapped
+            };
+            let mapped = super::inflate_and_merge(mapped, &overlay_extent(monitor), super::RedactionParams::from_config());
+            debug!("[multi_monitor] monitor {}: {} faces", monitor.id, mapped.len());
+            if mosaic_lock::is_active() {
+                debug!("[multi_monitor] mosaics locked, ignoring {} detections on monitor {}", mapped.len(), monitor.id);
+            } else {
+                // 越过接缝的部分交给相邻显示器的 overlay；本显示器显示自身部分与相邻显示器落过来的部分
+                let mut shown = route(monitor, mapped);
+                shown.extend(spill_into(monitor.id));
+                emit_to_overlay(monitor, monitor.scale_factor, shown);
+            }
+            Some(generation)
+        }
+        Err(e) => {
+            warn!("[multi_monitor] detection on monitor {} failed: {}", monitor.id, e);
+            emitter::emit_error("detection_failed", &e, Some(&format!("monitor {}", monitor.id)));
+            let policy = config::get_config()
+                .and_then(|c| c.monitoring)
+                .and_then(|m| m.on_detection_error)
+                .unwrap_or_default();
+            if let Some(items) = super::detection_error_payload(policy, monitor.width, monitor.height) {
+                let full = Rect::new(0, 0, monitor.width, monitor.height);
+                overlay::overlay::apply_mosaic_to_overlay(&shared.label, items, 1.0, monitor.scale_factor, &full);
+            }
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    fn monitor(id: usize, name: &str) -> MonitorInfo {
+        MonitorInfo { id, x: id as i32 * 1920, y: 0, width: 1920, height: 1080, scale_factor: 1.0, name: Some(name.to_string()) }
+    }
+
+    // 模拟截图/检测线程：启动时计入 live，收到停止后退出并扣除
+    fn spawn_fake(monitor: &MonitorInfo, live: &Arc<AtomicUsize>) -> Worker {
+        let stop = Arc::new(AtomicBool::new(false));
+        let threads = (0..2)
+            .map(|_| {
+                let (stop, live) = (Arc::clone(&stop), Arc::clone(live));
+                live.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || {
+                    while !stop.load(Ordering::SeqCst) {
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                    live.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        Worker { monitor: monitor.clone(), stop, threads }
+    }
+
+    #[test]
+    fn wanted_monitors_excludes_the_working_monitor() {
+        let all = [monitor(0, "A"), monitor(1, "B"), monitor(2, "C")];
+        let ids = |v: Vec<MonitorInfo>| v.into_iter().map(|m| m.id).collect::<Vec<_>>();
+        assert_eq!(ids(wanted_monitors(&all[0], &all, &["*".to_string()])), vec![1, 2]);
+        assert_eq!(ids(wanted_monitors(&all[1], &all, &["B".to_string(), "C".to_string()])), vec![2]);
+        assert!(wanted_monitors(&all[0], &all, &["D".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn cycling_monitor_sets_keeps_one_worker_pair_per_wanted_monitor() {
+        let all: Vec<MonitorInfo> = (0..4).map(|i| monitor(i, &format!("D{}", i))).collect();
+        let live = Arc::new(AtomicUsize::new(0));
+        let mut workers: HashMap<usize, Worker> = HashMap::new();
+        let mut started = 0usize;
+        // 确定性的伪随机序列（xorshift），每轮选出一个显示器子集
+        let mut seed: u32 = 0x9E37_79B9;
+        for round in 0..300 {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            let mut wanted: Vec<MonitorInfo> = all.iter().filter(|m| (seed >> m.id) & 1 == 1).cloned().collect();
+            // 每隔几轮改变一个显示器的分辨率：其线程应被重启
+            if round % 7 == 0 {
+                if let Some(m) = wanted.first_mut() {
+                    m.width += 1;
+                }
+            }
+            sync_workers(&mut workers, &wanted, |m| {
+                started += 1;
+                spawn_fake(m, &live)
+            });
+            let mut keys: Vec<usize> = workers.keys().copied().collect();
+            keys.sort();
+            assert_eq!(keys, wanted.iter().map(|m| m.id).collect::<Vec<_>>());
+            for m in &wanted {
+                assert!(same_geometry(&workers[&m.id].monitor, m));
+            }
+            assert_eq!(live.load(Ordering::SeqCst), wanted.len() * 2);
+        }
+        assert!(started > 4);
+        let removed = sync_workers(&mut workers, &[], |_| unreachable!());
+        assert!(workers.is_empty());
+        assert!(removed.len() <= 4);
+        assert_eq!(live.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn geometry_change_restarts_without_reporting_removal() {
+        let live = Arc::new(AtomicUsize::new(0));
+        let mut workers: HashMap<usize, Worker> = HashMap::new();
+        let a = monitor(1, "A");
+        sync_workers(&mut workers, std::slice::from_ref(&a), |m| spawn_fake(m, &live));
+        let resized = MonitorInfo { width: 2560, ..a.clone() };
+        let removed = sync_workers(&mut workers, std::slice::from_ref(&resized), |m| spawn_fake(m, &live));
+        // 重启的显示器 overlay 由新线程重建，不应被当作移除而关闭
+        assert!(removed.is_empty());
+        assert_eq!(workers[&1].monitor.width, 2560);
+        assert_eq!(live.load(Ordering::SeqCst), 2);
+        assert_eq!(sync_workers(&mut workers, &[], |_| unreachable!()), vec![1]);
+        assert_eq!(live.load(Ordering::SeqCst), 0);
+    }
+}
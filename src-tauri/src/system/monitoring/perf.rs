@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::utils::sync::lock_or_recover;
+
+// 逐帧性能环形缓冲：仅保留最近 PERF_RING_CAPACITY 帧，用于导出报告附在问题反馈中
+const PERF_RING_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PerfSample {
+    pub timestamp: i64,
+    pub monitor_id: usize,
+    pub capture_ms: u64,
+    pub from_prefetch: bool,
+    pub detection_ms: u64,
+    pub method: String,
+    pub face_count: usize,
+    pub fps: f32,
+}
+
+// 诊断面板用的最近 N 帧汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct PerfStats {
+    pub frames: Vec<PerfSample>,
+    pub avg_capture_ms: f32,
+    pub avg_detection_ms: f32,
+    // 按这 N 帧首尾时间戳计算的实际帧率，少于两帧时为 0
+    pub effective_fps: f32,
+    // 各截图方法在这 N 帧中的使用次数
+    pub methods: Vec<(String, usize)>,
+}
+
+struct PerfRing {
+    samples: VecDeque<PerfSample>,
+    last_frame_at: Option<Instant>,
+}
+
+static PERF_RING: Lazy<Mutex<PerfRing>> = Lazy::new(|| {
+    Mutex::new(PerfRing { samples: VecDeque::with_capacity(PERF_RING_CAPACITY), last_frame_at: None })
+});
+
+// fps 取与上一帧的间隔换算的瞬时值
+pub fn record(monitor_id: usize, capture_ms: u64, from_prefetch: bool, detection_ms: u64, method: &str, face_count: usize) {
+    let now = Instant::now();
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    let mut ring = lock_or_recover(&PERF_RING, "perf_ring");
+    let fps = ring
+        .last_frame_at
+        .map(|prev| now.duration_since(prev).as_secs_f32())
+        .filter(|dt| *dt > 0.0)
+        .map(|dt| 1.0 / dt)
+        .unwrap_or(0.0);
+    ring.last_frame_at = Some(now);
+    if ring.samples.len() >= PERF_RING_CAPACITY {
+        ring.samples.pop_front();
+    }
+    ring.samples.push_back(PerfSample {
+        timestamp,
+        monitor_id,
+        capture_ms,
+        from_prefetch,
+        detection_ms,
+        method: method.to_string(),
+        face_count,
+        fps,
+    });
+}
+
+// 停止监控后重置帧间隔，避免下一次启动的首帧 fps 偏低
+pub fn reset_frame_clock() {
+    lock_or_recover(&PERF_RING, "perf_ring").last_frame_at = None;
+}
+
+pub fn samples() -> Vec<PerfSample> {
+    lock_or_recover(&PERF_RING, "perf_ring").samples.iter().cloned().collect()
+}
+
+pub fn stats(last_n: usize) -> PerfStats {
+    let frames: Vec<PerfSample> = {
+        let ring = lock_or_recover(&PERF_RING, "perf_ring");
+        let skip = ring.samples.len().saturating_sub(last_n);
+        ring.samples.iter().skip(skip).cloned().collect()
+    };
+    let n = frames.len().max(1) as f32;
+    let avg_capture_ms = frames.iter().map(|s| s.capture_ms as f32).sum::<f32>() / n;
+    let avg_detection_ms = frames.iter().map(|s| s.detection_ms as f32).sum::<f32>() / n;
+    let effective_fps = match (frames.first(), frames.last()) {
+        (Some(first), Some(last)) if last.timestamp > first.timestamp => {
+            (frames.len() - 1) as f32 * 1000.0 / (last.timestamp - first.timestamp) as f32
+        }
+        _ => 0.0,
+    };
+    let mut methods: Vec<(String, usize)> = Vec::new();
+    for s in &frames {
+        match methods.iter_mut().find(|(m, _)| *m == s.method) {
+            Some((_, count)) => *count += 1,
+            None => methods.push((s.method.clone(), 1)),
+        }
+    }
+    PerfStats { frames, avg_capture_ms, avg_detection_ms, effective_fps, methods }
+}
+
+// 环形缓冲内各帧人脸数的分布：(人脸数, 帧数)，按人脸数升序，只列出出现过的人脸数
+pub fn face_count_histogram() -> Vec<(usize, u32)> {
+    let mut counts: std::collections::BTreeMap<usize, u32> = std::collections::BTreeMap::new();
+    for s in lock_or_recover(&PERF_RING, "perf_ring").samples.iter() {
+        *counts.entry(s.face_count).or_insert(0) += 1;
+    }
+    counts.into_iter().collect()
+}
+
+// 按扩展名导出：.csv 为带表头的 CSV，其余为 JSON 数组；返回写入的行数
+pub fn export_report(path: &str) -> Result<usize, String> {
+    let samples = samples();
+    let content = if path.to_ascii_lowercase().ends_with(".csv") {
+        let mut out = String::from("timestamp,monitor_id,capture_ms,from_prefetch,detection_ms,method,face_count,fps\n");
+        for s in &samples {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{},{:.2}\n",
+                s.timestamp, s.monitor_id, s.capture_ms, s.from_prefetch, s.detection_ms, s.method, s.face_count, s.fps
+            ));
+        }
+        out
+    } else {
+        serde_json::to_string_pretty(&samples).map_err(|e| format!("Failed to serialize perf report: {}", e))?
+    };
+    std::fs::write(path, content).map_err(|e| format!("Failed to write perf report {}: {}", path, e))?;
+    Ok(samples.len())
+}
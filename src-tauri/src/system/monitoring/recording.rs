@@ -0,0 +1,178 @@
+use std::io::Write;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use log::{error, info, warn};
+use once_cell::sync::Lazy;
+
+use crate::api::emitter;
+use crate::config;
+use crate::monitor::screen_shot::Image;
+use crate::mosaic;
+use crate::utils::rect::Rect;
+use crate::utils::sync::lock_or_recover;
+
+// 会话录制：监控循环提交最新一帧原图与遮罩框，录制线程按固定帧率合成（遮罩区域像素化）后写入 ffmpeg 的标准输入。
+// 无新帧时重复上一帧以保持时间轴；编码器不可用时 start 直接返回错误，不影响监控。
+
+struct Session {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+    path: String,
+}
+
+static SESSION: Lazy<Mutex<Option<Session>>> = Lazy::new(|| Mutex::new(None));
+static LATEST: Lazy<Mutex<Option<(Image, Vec<Rect>)>>> = Lazy::new(|| Mutex::new(None));
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+// 提交最新一帧（显示器物理像素坐标的遮罩框），仅保留最新
+pub fn push_frame(image: Image, rects: Vec<Rect>) {
+    if !is_active() {
+        return;
+    }
+    *lock_or_recover(&LATEST, "recording_latest") = Some((image, rects));
+}
+
+#[cfg(target_os = "windows")]
+fn configure_cmd_hide_window(cmd: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000; // CREATE_NO_WINDOW
+    cmd.creation_flags(CREATE_NO_WINDOW);
+}
+
+#[cfg(not(target_os = "windows"))]
+fn configure_cmd_hide_window(_cmd: &mut Command) {}
+
+// x264/yuv420p 要求宽高为偶数
+fn even(v: i32) -> i32 {
+    (v.max(2) / 2) * 2
+}
+
+pub fn start(path: &str, width: i32, height: i32) -> Result<(), String> {
+    let mut session = lock_or_recover(&SESSION, "recording_session");
+    if session.is_some() {
+        return Err("recording already in progress".to_string());
+    }
+    let cfg = config::get_config().and_then(|c| c.recording).unwrap_or_default();
+    let ffmpeg = cfg.ffmpeg_path.clone().unwrap_or_else(|| "ffmpeg".to_string());
+    let fps = cfg.fps.unwrap_or(10).clamp(1, 60);
+    let scale = cfg.scale.unwrap_or(1.0).clamp(0.1, 1.0);
+    let max_seconds = cfg.max_seconds.unwrap_or(3600);
+    let out_w = even(((width as f32) * scale).round() as i32);
+    let out_h = even(((height as f32) * scale).round() as i32);
+
+    let mut cmd = Command::new(&ffmpeg);
+    cmd.args(["-hide_banner", "-loglevel", "error", "-y"])
+        .args(["-f", "rawvideo", "-pix_fmt", "bgra"])
+        .args(["-s", &format!("{}x{}", width, height)])
+        .args(["-r", &fps.to_string()])
+        .args(["-i", "-"])
+        .args(["-vf", &format!("scale={}:{}", out_w, out_h)])
+        .args(["-c:v", "libx264", "-preset", "veryfast", "-pix_fmt", "yuv420p"])
+        .args(["-t", &max_seconds.to_string()]);
+    if let Some(mb) = cfg.max_mb.filter(|m| *m > 0) {
+        cmd.args(["-fs", &(mb * 1024 * 1024).to_string()]);
+    }
+    cmd.arg(path).stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null());
+    configure_cmd_hide_window(&mut cmd);
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("video encoder unavailable ({}): {}", ffmpeg, e))?;
+    let stdin = child.stdin.take().ok_or("failed to open encoder stdin")?;
+
+    info!(
+        "[recording] start {} ({}x{} -> {}x{} @ {} fps, max {} s)",
+        path, width, height, out_w, out_h, fps, max_seconds
+    );
+    lock_or_recover(&LATEST, "recording_latest").take();
+    ACTIVE.store(true, Ordering::Relaxed);
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_flag = Arc::clone(&stop);
+    let frame_interval = Duration::from_millis(1000 / fps as u64);
+    let limit = Duration::from_secs(max_seconds);
+    let handle = std::thread::spawn(move || {
+        write_loop(child, stdin, width, height, frame_interval, limit, stop_flag);
+    });
+    *session = Some(Session { stop, handle, path: path.to_string() });
+    Ok(())
+}
+
+fn write_loop(
+    mut child: Child,
+    mut stdin: ChildStdin,
+    width: i32,
+    height: i32,
+    frame_interval: Duration,
+    limit: Duration,
+    stop: Arc<AtomicBool>,
+) {
+    let started = Instant::now();
+    let mut frame: Vec<u8> = vec![0u8; (width as usize) * (height as usize) * 4];
+    let mut next_tick = Instant::now();
+    let mut frames: u64 = 0;
+    let mut auto_stopped = false;
+    while !stop.load(Ordering::Relaxed) {
+        if started.elapsed() >= limit {
+            info!("[recording] max duration reached");
+            auto_stopped = true;
+            break;
+        }
+        if let Some((image, rects)) = lock_or_recover(&LATEST, "recording_latest").take() {
+            if image.width == width && image.height == height {
+                let mut image = image;
+                // 块大小随框缩放，与 overlay 默认的马赛克效果一致
+                mosaic::pixelate_regions(&mut image, &rects, 0);
+                frame = image.data;
+            } else {
+                warn!("[recording] frame size {}x{} does not match {}x{}, skipped", image.width, image.height, width, height);
+            }
+        }
+        if let Err(e) = stdin.write_all(&frame) {
+            // 编码器已退出（如达到 -fs 上限或编码失败）
+            warn!("[recording] encoder stopped accepting frames: {}", e);
+            auto_stopped = true;
+            break;
+        }
+        frames += 1;
+        next_tick += frame_interval;
+        let now = Instant::now();
+        if next_tick > now {
+            std::thread::sleep(next_tick - now);
+        } else {
+            next_tick = now;
+        }
+    }
+    drop(stdin);
+    match child.wait() {
+        Ok(status) => info!("[recording] encoder exited with {} after {} frames", status, frames),
+        Err(e) => error!("[recording] wait encoder failed: {}", e),
+    }
+    ACTIVE.store(false, Ordering::Relaxed);
+    if auto_stopped {
+        emitter::emit_toast("录制已自动停止");
+        // 自然结束时清理会话，允许再次开始录制
+        lock_or_recover(&SESSION, "recording_session").take();
+    }
+}
+
+// 停止录制并等待编码器写完文件，返回输出路径；未在录制时返回 None
+pub fn stop() -> Option<String> {
+    let session = lock_or_recover(&SESSION, "recording_session").take();
+    let session = session?;
+    session.stop.store(true, Ordering::Relaxed);
+    if session.handle.join().is_err() {
+        error!("[recording] writer thread panicked");
+    }
+    ACTIVE.store(false, Ordering::Relaxed);
+    lock_or_recover(&LATEST, "recording_latest").take();
+    info!("[recording] stopped {}", session.path);
+    Some(session.path)
+}
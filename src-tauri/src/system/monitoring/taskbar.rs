@@ -0,0 +1,61 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use log::debug;
+use windows::Win32::Foundation::POINT;
+use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, MonitorFromPoint, MONITORINFO, MONITOR_DEFAULTTONEAREST};
+
+use crate::config;
+use crate::monitor::MonitorInfo;
+use crate::utils::rect::Rect;
+
+// 排除任务栏区域：0 = 沿用配置，1 = 运行时关闭，2 = 运行时开启
+const EXCLUDE_FROM_CONFIG: u8 = 0;
+const EXCLUDE_OFF: u8 = 1;
+const EXCLUDE_ON: u8 = 2;
+static EXCLUDE_OVERRIDE: AtomicU8 = AtomicU8::new(EXCLUDE_FROM_CONFIG);
+
+pub fn is_enabled() -> bool {
+    match EXCLUDE_OVERRIDE.load(Ordering::Relaxed) {
+        EXCLUDE_ON => true,
+        EXCLUDE_OFF => false,
+        _ => config::get_config()
+            .and_then(|c| c.monitoring)
+            .and_then(|m| m.exclude_taskbar)
+            .unwrap_or(true),
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    EXCLUDE_OVERRIDE.store(if enabled { EXCLUDE_ON } else { EXCLUDE_OFF }, Ordering::Relaxed);
+}
+
+// 任务栏/停靠栏占用的区域（显示器内坐标）：显示器全区域减去工作区后剩余的边条。
+// 自动隐藏的任务栏不占用工作区，此时返回空，不排除任何区域（避免把整条屏幕边缘当作任务栏）
+pub fn excluded_rects(monitor: &MonitorInfo) -> Vec<Rect> {
+    let center = POINT { x: monitor.x + monitor.width / 2, y: monitor.y + monitor.height / 2 };
+    let mut info = MONITORINFO { cbSize: std::mem::size_of::<MONITORINFO>() as u32, ..Default::default() };
+    let ok = unsafe {
+        let hmonitor = MonitorFromPoint(center, MONITOR_DEFAULTTONEAREST);
+        GetMonitorInfoW(hmonitor, &mut info).as_bool()
+    };
+    if !ok {
+        debug!("[taskbar] GetMonitorInfoW failed for monitor {}", monitor.id);
+        return Vec::new();
+    }
+    let (m, w) = (info.rcMonitor, info.rcWork);
+    let (mw, mh) = (m.right - m.left, m.bottom - m.top);
+    let mut rects = Vec::new();
+    if w.top > m.top {
+        rects.push(Rect::new(0, 0, mw, w.top - m.top));
+    }
+    if w.bottom < m.bottom {
+        rects.push(Rect::new(0, w.bottom - m.top, mw, m.bottom - w.bottom));
+    }
+    if w.left > m.left {
+        rects.push(Rect::new(0, 0, w.left - m.left, mh));
+    }
+    if w.right < m.right {
+        rects.push(Rect::new(w.right - m.left, 0, m.right - w.right, mh));
+    }
+    rects
+}
@@ -0,0 +1,205 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use log::{error, info};
+use windows::core::w;
+use windows::Win32::Foundation::{HANDLE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::System::Power::{RegisterSuspendResumeNotification, POWERBROADCAST_SETTING};
+use windows::Win32::System::SystemServices::GUID_CONSOLE_DISPLAY_STATE;
+use windows::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW, RegisterPowerSettingNotification,
+    TranslateMessage, DEVICE_NOTIFY_WINDOW_HANDLE, HWND_MESSAGE, MSG, PBT_APMRESUMEAUTOMATIC, PBT_APMRESUMESUSPEND,
+    PBT_APMSUSPEND, PBT_POWERSETTINGCHANGE, WINDOW_EX_STYLE, WINDOW_STYLE, WM_POWERBROADCAST, WNDCLASSW,
+};
+
+use crate::config;
+use crate::utils::sync::lock_or_recover;
+
+// 遮罩不可见时暂停检测：overlay 窗口被隐藏，或显示器已关闭（GUID_CONSOLE_DISPLAY_STATE，针对整个控制台而非单个显示器）。
+// 显示器状态由一个仅消息窗口接收电源通知后写入原子量，cal() 每轮只读原子量与 overlay 状态，开销可忽略。
+
+// 暂停检测：0 = 沿用配置，1 = 运行时关闭，2 = 运行时开启
+const PAUSE_FROM_CONFIG: u8 = 0;
+const PAUSE_OFF: u8 = 1;
+const PAUSE_ON: u8 = 2;
+static PAUSE_OVERRIDE: AtomicU8 = AtomicU8::new(PAUSE_FROM_CONFIG);
+
+// 暂停期间的轮询间隔：恢复后最多延迟这么久才重新检测（期间保持全屏遮罩）
+pub const PAUSED_POLL: Duration = Duration::from_millis(200);
+
+static DISPLAY_ON: AtomicBool = AtomicBool::new(true);
+static PAUSED: AtomicBool = AtomicBool::new(false);
+static LISTENER: OnceLock<()> = OnceLock::new();
+
+// 系统睡眠/唤醒：睡眠前后 GPU 设备状态会重置，缓存的截图资源随之失效。
+// 睡眠时暂停并释放截图资源；唤醒后再等待 RESUME_GRACE（驱动与桌面复制尚未就绪时截图会连续失败），
+// 期间再释放一次睡眠中可能残留的资源，恢复后由首帧惰性重建
+const RESUME_GRACE: Duration = Duration::from_secs(2);
+static SUSPENDED: AtomicBool = AtomicBool::new(false);
+static RESUMED_AT: Mutex<Option<Instant>> = Mutex::new(None);
+// 睡眠或唤醒后待 cal() 释放截图资源
+static RELEASE_PENDING: AtomicBool = AtomicBool::new(false);
+
+pub fn is_enabled() -> bool {
+    match PAUSE_OVERRIDE.load(Ordering::Relaxed) {
+        PAUSE_ON => true,
+        PAUSE_OFF => false,
+        _ => config::get_config()
+            .and_then(|c| c.monitoring)
+            .and_then(|m| m.pause_when_hidden)
+            .unwrap_or(true),
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    PAUSE_OVERRIDE.store(if enabled { PAUSE_ON } else { PAUSE_OFF }, Ordering::Relaxed);
+}
+
+// 系统睡眠中或刚唤醒（RESUME_GRACE 内）：不受 pause_when_hidden 开关影响，始终暂停
+pub fn is_suspended() -> bool {
+    if SUSPENDED.load(Ordering::SeqCst) {
+        return true;
+    }
+    let mut resumed_at = lock_or_recover(&RESUMED_AT, "resumed_at");
+    match *resumed_at {
+        Some(t) if t.elapsed() < RESUME_GRACE => true,
+        Some(_) => {
+            resumed_at.take();
+            info!("[visibility] resume grace period over, resuming capture");
+            false
+        }
+        None => false,
+    }
+}
+
+// 取出待释放标记（睡眠或唤醒后各置位一次）
+pub fn take_release_pending() -> bool {
+    RELEASE_PENDING.swap(false, Ordering::SeqCst)
+}
+
+// 是否应暂停本轮截图与检测
+pub fn should_pause() -> bool {
+    if is_suspended() {
+        return true;
+    }
+    if !is_enabled() {
+        return false;
+    }
+    if !DISPLAY_ON.load(Ordering::Relaxed) {
+        return true;
+    }
+    // overlay 尚未创建时不暂停（创建过程中 exists 为 false）
+    let status = crate::overlay::overlay_status();
+    status.exists && !status.visible
+}
+
+// 进入暂停状态，返回是否为本次新进入
+pub fn enter_pause() -> bool {
+    !PAUSED.swap(true, Ordering::SeqCst)
+}
+
+// 离开暂停状态，返回此前是否处于暂停
+pub fn leave_pause() -> bool {
+    PAUSED.swap(false, Ordering::SeqCst)
+}
+
+// 启动显示器电源状态监听（仅一次）
+pub fn ensure_listener() {
+    LISTENER.get_or_init(|| {
+        std::thread::spawn(|| {
+            if let Err(e) = unsafe { run_listener() } {
+                error!("[visibility] display power listener failed: {}", e);
+            }
+        });
+    });
+}
+
+unsafe fn run_listener() -> Result<(), String> {
+    let instance = GetModuleHandleW(None).map_err(|e| format!("GetModuleHandleW failed: {e}"))?;
+    let class_name = w!("ScreenGhostPowerListener");
+    let wc = WNDCLASSW {
+        lpfnWndProc: Some(wnd_proc),
+        hInstance: instance.into(),
+        lpszClassName: class_name,
+        ..Default::default()
+    };
+    if RegisterClassW(&wc) == 0 {
+        return Err("RegisterClassW failed".to_string());
+    }
+    let hwnd = CreateWindowExW(
+        WINDOW_EX_STYLE::default(),
+        class_name,
+        w!(""),
+        WINDOW_STYLE::default(),
+        0,
+        0,
+        0,
+        0,
+        Some(HWND_MESSAGE),
+        None,
+        Some(instance.into()),
+        None,
+    )
+    .map_err(|e| format!("CreateWindowExW failed: {e}"))?;
+    RegisterPowerSettingNotification(HANDLE(hwnd.0), &GUID_CONSOLE_DISPLAY_STATE, DEVICE_NOTIFY_WINDOW_HANDLE)
+        .map_err(|e| format!("RegisterPowerSettingNotification failed: {e}"))?;
+    // 仅消息窗口收不到广播的睡眠/唤醒通知，需显式注册
+    RegisterSuspendResumeNotification(HANDLE(hwnd.0), DEVICE_NOTIFY_WINDOW_HANDLE)
+        .map_err(|e| format!("RegisterSuspendResumeNotification failed: {e}"))?;
+    info!("[visibility] display power listener started");
+
+    let mut msg = MSG::default();
+    while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+        let _ = TranslateMessage(&msg);
+        DispatchMessageW(&msg);
+    }
+    Ok(())
+}
+
+fn on_suspend() {
+    if !SUSPENDED.swap(true, Ordering::SeqCst) {
+        info!("[visibility] system suspending, pausing capture and releasing resources");
+        lock_or_recover(&RESUMED_AT, "resumed_at").take();
+        RELEASE_PENDING.store(true, Ordering::SeqCst);
+    }
+}
+
+// 自动唤醒（PBT_APMRESUMEAUTOMATIC）与用户唤醒（PBT_APMRESUMESUSPEND）可能先后到达，只处理首个
+fn on_resume() {
+    if SUSPENDED.swap(false, Ordering::SeqCst) {
+        info!("[visibility] system resumed, waiting {:?} before re-initializing capture", RESUME_GRACE);
+        *lock_or_recover(&RESUMED_AT, "resumed_at") = Some(Instant::now());
+        RELEASE_PENDING.store(true, Ordering::SeqCst);
+    }
+}
+
+extern "system" fn wnd_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if msg == WM_POWERBROADCAST {
+        match wparam.0 as u32 {
+            PBT_APMSUSPEND => {
+                on_suspend();
+                return LRESULT(1);
+            }
+            PBT_APMRESUMEAUTOMATIC | PBT_APMRESUMESUSPEND => {
+                on_resume();
+                return LRESULT(1);
+            }
+            _ => {}
+        }
+    }
+    if msg == WM_POWERBROADCAST && wparam.0 as u32 == PBT_POWERSETTINGCHANGE {
+        let setting = unsafe { &*(lparam.0 as *const POWERBROADCAST_SETTING) };
+        if setting.PowerSetting == GUID_CONSOLE_DISPLAY_STATE && setting.DataLength >= 4 {
+            // 0 = 关闭，1 = 开启，2 = 变暗（仍可见）
+            let state = unsafe { std::ptr::read_unaligned(setting.Data.as_ptr() as *const u32) };
+            let on = state != 0;
+            if DISPLAY_ON.swap(on, Ordering::Relaxed) != on {
+                info!("[visibility] display state changed: {}", if on { "on" } else { "off" });
+            }
+        }
+        return LRESULT(1);
+    }
+    unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+}
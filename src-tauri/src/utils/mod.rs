@@ -1,2 +1,4 @@
+pub mod base64;
 pub mod logger;
-pub mod rect;
\ No newline at end of file
+pub mod rect;
+pub mod sync;
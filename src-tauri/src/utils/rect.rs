@@ -6,6 +6,9 @@ pub struct Rect {
     pub y: i32,
     pub width: i32,
     pub height: i32,
+    // 检测置信度/识别相似度（仅检测结果携带）；几何运算产生的新矩形不继承，需要时由调用方以 with_score 带上
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
 }
 
 impl Rect {
@@ -15,9 +18,34 @@ impl Rect {
             y,
             width,
             height,
+            score: None,
         }
     }
 
+    pub fn with_score(mut self, score: Option<f32>) -> Self {
+        self.score = score;
+        self
+    }
+
+    // 以中心为基准等比缩放（与 overlay 放大遮罩框的公式一致）
+    pub fn scaled_about_center(&self, s: f32) -> Rect {
+        let new_w_f = (self.width as f32) * s;
+        let new_h_f = (self.height as f32) * s;
+        let dx = ((new_w_f - self.width as f32) / 2.0).round() as i32;
+        let dy = ((new_h_f - self.height as f32) / 2.0).round() as i32;
+        Rect::new(self.x - dx, self.y - dy, new_w_f.round() as i32, new_h_f.round() as i32)
+    }
+
+    // 以坐标原点为基准缩放（检测图坐标 -> 原分辨率），左上角向下取整、右下角向上取整，
+    // 避免 x 与 width 分别四舍五入使右/下边缘少 1px；负坐标同样适用。保留 score
+    pub fn scaled_outward(&self, sx: f32, sy: f32) -> Rect {
+        let x0 = (self.x as f32 * sx).floor() as i32;
+        let y0 = (self.y as f32 * sy).floor() as i32;
+        let x1 = ((self.x + self.width) as f32 * sx).ceil() as i32;
+        let y1 = ((self.y + self.height) as f32 * sy).ceil() as i32;
+        Rect::new(x0, y0, x1 - x0, y1 - y0).with_score(self.score)
+    }
+
     pub fn contains_point(&self, x: i32, y: i32) -> bool {
         x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
     }
@@ -53,6 +81,41 @@ impl Rect {
         self.width * self.height
     }
 
+    pub fn iou(&self, other: &Rect) -> f32 {
+        let inter = match self.intersection(other) {
+            Some(r) => r.area() as f32,
+            None => return 0.0,
+        };
+        let union = self.area() as f32 + other.area() as f32 - inter;
+        if union <= 0.0 { 0.0 } else { inter / union }
+    }
+
+    // 四边各外扩 pad（负值为内缩，宽高不小于 0）
+    pub fn inflate(&self, pad: i32) -> Rect {
+        Rect::new(
+            self.x - pad,
+            self.y - pad,
+            (self.width + pad * 2).max(0),
+            (self.height + pad * 2).max(0),
+        )
+    }
+
+    // 同时包含两个矩形的最小外接矩形
+    pub fn union(&self, other: &Rect) -> Rect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Rect::new(x, y, right - x, bottom - y)
+    }
+
+    // 两矩形之间的间隙（水平与垂直间距取大者），相交或相接时为 0
+    pub fn gap(&self, other: &Rect) -> i32 {
+        let dx = (self.x.max(other.x) - (self.x + self.width).min(other.x + other.width)).max(0);
+        let dy = (self.y.max(other.y) - (self.y + self.height).min(other.y + other.height)).max(0);
+        dx.max(dy)
+    }
+
     pub fn subtract(&self, other: &Rect) -> Vec<Rect> {
         if !self.intersects(other) {
             return vec![self.clone()];
@@ -104,3 +167,55 @@ impl Rect {
         result
     }
 }
+
+// 非极大值抑制：无置信度时按面积从大到小保留，剔除与已保留框 IoU 超过阈值的框，返回保留的下标
+pub fn nms(rects: &[Rect], iou_threshold: f32) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..rects.len()).collect();
+    order.sort_by(|&a, &b| rects[b].area().cmp(&rects[a].area()));
+    let mut keep: Vec<usize> = Vec::new();
+    for i in order {
+        if keep.iter().all(|&k| rects[k].iou(&rects[i]) <= iou_threshold) {
+            keep.push(i);
+        }
+    }
+    keep
+}
+
+// 反复合并满足条件（间隙不超过 max_gap，或 IoU 不低于 min_iou）的两个框，直至没有可合并的框
+pub fn merge_close(rects: Vec<Rect>, max_gap: Option<i32>, min_iou: Option<f32>) -> Vec<Rect> {
+    if max_gap.is_none() && min_iou.is_none() {
+        return rects;
+    }
+    let should_merge = |a: &Rect, b: &Rect| {
+        max_gap.map(|g| a.gap(b) <= g).unwrap_or(false) || min_iou.map(|t| a.iou(b) >= t).unwrap_or(false)
+    };
+    let mut rects = rects;
+    'outer: loop {
+        for i in 0..rects.len() {
+            for j in (i + 1)..rects.len() {
+                if should_merge(&rects[i], &rects[j]) {
+                    let other = rects.swap_remove(j);
+                    rects[i] = rects[i].union(&other);
+                    continue 'outer;
+                }
+            }
+        }
+        return rects;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled_outward_floors_and_ceils_negative_coordinates() {
+        // 检测图 0.5 倍：(-961, -101) 起 3x3 的框映射回原分辨率后左上向下取整、右下向上取整
+        let r = Rect::new(-961, -101, 3, 3).with_score(Some(0.7)).scaled_outward(2.0, 2.0);
+        assert_eq!((r.x, r.y, r.width, r.height), (-1922, -202, 6, 6));
+        assert_eq!(r.score, Some(0.7));
+        let r = Rect::new(-961, -101, 3, 3).scaled_outward(1.0 / 0.3, 1.0 / 0.3);
+        assert_eq!((r.x, r.y), (-3204, -337));
+        assert_eq!((r.x + r.width, r.y + r.height), (-3193, -326));
+    }
+}
@@ -0,0 +1,40 @@
+use log::warn;
+use std::sync::{Mutex, MutexGuard};
+
+// 获取锁；若持锁线程 panic 导致中毒，记录日志并清除中毒标记后继续使用内部数据，
+// 避免一次 panic 让监控等子系统永久停摆（.ok() 返回 None 或 unwrap 连锁 panic）
+pub fn lock_or_recover<'a, T>(mutex: &'a Mutex<T>, name: &str) -> MutexGuard<'a, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        warn!("[lock_or_recover] mutex {} was poisoned, recovering", name);
+        mutex.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn recovers_inner_data_and_clears_poison() {
+        let mutex = Arc::new(Mutex::new(vec![1, 2]));
+        let m = Arc::clone(&mutex);
+        let result = thread::spawn(move || {
+            let mut guard = m.lock().unwrap();
+            guard.push(3);
+            panic!("poison the mutex");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(mutex.is_poisoned());
+
+        {
+            let guard = lock_or_recover(&mutex, "test");
+            assert_eq!(*guard, vec![1, 2, 3]);
+        }
+        assert!(!mutex.is_poisoned());
+        assert!(mutex.lock().is_ok());
+    }
+}
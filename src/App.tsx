@@ -11,6 +11,7 @@ interface MonitorInfo {
   width: number;
   height: number;
   scale_factor: number;
+  name?: string | null;
 }
 
 interface Rect {
@@ -27,7 +28,8 @@ interface FaceAngleItem extends Rect {
 interface ImagePayload {
   width: number;
   height: number;
-  data: number[]; // BGRA
+  data?: number[]; // BGRA
+  jpeg?: string; // 调试预览帧：按带宽自适应质量的 JPEG（base64）
 }
 
 // 后端直接 emit Vec<Rect>，前端按数组解析
@@ -47,6 +49,28 @@ function App() {
     loadMonitors();
   }, []);
 
+  // Backend may start monitoring on its own (default_monitor_name in config)
+  useEffect(() => {
+    const unlisten = listen<MonitorInfo>("working-monitor", (event) => {
+      console.log("working-monitor received:", event.payload);
+      setSelectedMonitor(event.payload);
+    });
+    return () => {
+      unlisten.then(fn => fn()).catch(err => console.error("Failed to cleanup working-monitor listener", err));
+    };
+  }, []);
+
+  // Resolution change or monitor hotplug: backend re-enumerates monitors
+  useEffect(() => {
+    const unlisten = listen<MonitorInfo[]>("monitors-changed", (event) => {
+      console.log("monitors-changed received:", event.payload);
+      setMonitors(event.payload);
+    });
+    return () => {
+      unlisten.then(fn => fn()).catch(err => console.error("Failed to cleanup monitors-changed listener", err));
+    };
+  }, []);
+
   // Listen for frame_info (face rectangles) events
   useEffect(() => {
     if (!selectedMonitor) return;
@@ -93,12 +117,19 @@ function App() {
       if (!canvas) return;
       const ctx = canvas.getContext('2d');
       if (!ctx) return;
-      const { width, height, data } = img;
+      const { width, height, data, jpeg } = img;
       if (canvas.width !== width || canvas.height !== height) {
         canvas.width = width;
         canvas.height = height;
       }
-      const src = data as number[];
+      if (jpeg) {
+        const frame = new Image();
+        frame.onload = () => ctx.drawImage(frame, 0, 0, width, height);
+        frame.src = `data:image/jpeg;base64,${jpeg}`;
+        return;
+      }
+      if (!data) return;
+      const src = data;
       const rgba = new Uint8ClampedArray(width * height * 4);
       // Convert BGRA -> RGBA
       for (let i = 0, j = 0; i < src.length; i += 4, j += 4) {
@@ -223,7 +254,7 @@ function App() {
                   className={`display-monitor ${isSelected ? 'selected' : ''}`}
                   style={{ left, top, width, height }}
                   onClick={() => handleMonitorClick(m)}
-                  title={`位置(${m.x}, ${m.y}) 尺寸 ${m.width}×${m.height} 缩放 ${m.scale_factor}`}
+                  title={`${m.name ? m.name + ' ' : ''}位置(${m.x}, ${m.y}) 尺寸 ${m.width}×${m.height} 缩放 ${m.scale_factor}`}
                 >
                   <div className="display-label">{m.id + 1}</div>
                 </div>